@@ -0,0 +1,512 @@
+use cosmwasm_std::{
+    attr, coins, to_json_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Order, Response, Uint128, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw_storage_plus::Bound;
+use lst_common::{
+    hub::ExecuteMsg::InsuranceTopUp,
+    insurance_msg::{
+        Claim, ClaimsResponse, Config, ExecuteMsg, FundState, FundStateResponse, InstantiateMsg,
+        PayoutResponse, QueryMsg,
+    },
+    to_checked_address,
+    types::LstResult,
+    ContractError, MigrateMsg,
+};
+
+use crate::{
+    state::{CLAIMS, CONFIG, FUND_STATE},
+    MAX_COVERAGE_RATE,
+};
+
+const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> LstResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)
+        .map_err(|_| ContractError::FailedToInitContract)?;
+
+    let InstantiateMsg {
+        hub_contract,
+        denom,
+        coverage_rate,
+        max_payout,
+        cooldown,
+    } = msg;
+
+    if coverage_rate > MAX_COVERAGE_RATE {
+        return Err(ContractError::InvalidCoverageRate {});
+    }
+
+    let config = Config {
+        owner: info.sender,
+        hub_contract: to_checked_address(deps.as_ref(), &hub_contract)?,
+        denom,
+        coverage_rate,
+        max_payout,
+        cooldown,
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+    FUND_STATE.save(deps.storage, &Default::default())?;
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> LstResult<Response> {
+    match msg {
+        ExecuteMsg::RequestTopUp { loss } => execute_request_top_up(deps, env, info, loss),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            hub_contract,
+            coverage_rate,
+            max_payout,
+            cooldown,
+        } => execute_update_config(
+            deps,
+            info,
+            owner,
+            hub_contract,
+            coverage_rate,
+            max_payout,
+            cooldown,
+        ),
+    }
+}
+
+fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    hub_contract: Option<String>,
+    coverage_rate: Option<Decimal>,
+    max_payout: Option<Uint128>,
+    cooldown: Option<u64>,
+) -> LstResult<Response> {
+    is_authorized_sender(deps.as_ref(), info.sender)?;
+
+    if let Some(rate) = &coverage_rate {
+        if rate > &MAX_COVERAGE_RATE {
+            return Err(ContractError::InvalidCoverageRate {});
+        }
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if let Some(o) = owner {
+        config.owner = to_checked_address(deps.as_ref(), &o)?;
+    }
+    if let Some(h) = hub_contract {
+        config.hub_contract = to_checked_address(deps.as_ref(), &h)?;
+    }
+    if let Some(rate) = coverage_rate {
+        config.coverage_rate = rate;
+    }
+    if let Some(cap) = max_payout {
+        config.max_payout = cap;
+    }
+    if let Some(c) = cooldown {
+        config.cooldown = c;
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::default())
+}
+
+/// Pays the covered share of a slashing loss to the hub, capped by `max_payout` and the fund
+/// balance. Claims during the cooldown or against an empty fund are recorded without a payout,
+/// so the slashing check in the hub never fails because of the fund.
+fn execute_request_top_up(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    loss: Uint128,
+) -> LstResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.hub_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut fund_state = FUND_STATE.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let payout = payout_for(deps.as_ref(), &env, &config, &fund_state, loss)?;
+
+    let id = fund_state.claim_count;
+    fund_state.claim_count += 1;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !payout.is_zero() {
+        fund_state.total_paid_out = fund_state
+            .total_paid_out
+            .checked_add(payout)
+            .map_err(|e| ContractError::Overflow(e.to_string()))?;
+        fund_state.last_payout_time = Some(now);
+
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.hub_contract.to_string(),
+            msg: to_json_binary(&InsuranceTopUp {})?,
+            funds: coins(payout.u128(), &config.denom),
+        }));
+    }
+
+    FUND_STATE.save(deps.storage, &fund_state)?;
+    CLAIMS.save(
+        deps.storage,
+        id,
+        &Claim {
+            id,
+            height: env.block.height,
+            time: now,
+            loss,
+            payout,
+        },
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "request_top_up"),
+        attr("claim_id", id.to_string()),
+        attr("loss", loss),
+        attr("payout", payout),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> LstResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_json_binary(&CONFIG.load(deps.storage)?)?),
+        QueryMsg::FundState {} => Ok(to_json_binary(&query_fund_state(deps, env)?)?),
+        QueryMsg::Payout { loss } => Ok(to_json_binary(&query_payout(deps, env, loss)?)?),
+        QueryMsg::Claims { start_from, limit } => {
+            Ok(to_json_binary(&query_claims(deps, start_from, limit)?)?)
+        }
+    }
+}
+
+fn query_fund_state(deps: Deps, env: Env) -> LstResult<FundStateResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let fund_state = FUND_STATE.load(deps.storage)?;
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address, &config.denom)?
+        .amount;
+
+    Ok(FundStateResponse {
+        balance,
+        total_paid_out: fund_state.total_paid_out,
+        claim_count: fund_state.claim_count,
+        cooldown_end: cooldown_end(&config, fund_state.last_payout_time),
+    })
+}
+
+fn query_payout(deps: Deps, env: Env, loss: Uint128) -> LstResult<PayoutResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let fund_state = FUND_STATE.load(deps.storage)?;
+    let payout = payout_for(deps, &env, &config, &fund_state, loss)?;
+
+    Ok(PayoutResponse { payout })
+}
+
+fn query_claims(
+    deps: Deps,
+    start_from: Option<u64>,
+    limit: Option<u32>,
+) -> LstResult<ClaimsResponse> {
+    let claims = CLAIMS
+        .range(
+            deps.storage,
+            start_from.map(Bound::inclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit.unwrap_or(u32::MAX) as usize)
+        .map(|item| Ok(item?.1))
+        .collect::<LstResult<Vec<_>>>()?;
+
+    Ok(ClaimsResponse { claims })
+}
+
+// The covered share of the loss capped by `max_payout` and the balance, nothing during the cooldown
+fn payout_for(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    fund_state: &FundState,
+    loss: Uint128,
+) -> LstResult<Uint128> {
+    let now = env.block.time.seconds();
+    if cooldown_end(config, fund_state.last_payout_time).is_some_and(|end| now < end) {
+        return Ok(Uint128::zero());
+    }
+
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &config.denom)?
+        .amount;
+    Ok(loss
+        .mul_floor(config.coverage_rate)
+        .min(config.max_payout)
+        .min(balance))
+}
+
+fn cooldown_end(config: &Config, last_payout_time: Option<u64>) -> Option<u64> {
+    last_payout_time.map(|time| time.saturating_add(config.cooldown))
+}
+
+/// This can only be called by the contract ADMIN, enforced by `wasmd` separate from cosmwasm.
+/// See https://github.com/CosmWasm/cosmwasm/issues/926#issuecomment-851259818
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> LstResult<Response> {
+    cw2::ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::default())
+}
+
+fn is_authorized_sender(deps: Deps, sender: Addr) -> LstResult<()> {
+    let Config { owner, .. } = CONFIG.load(deps.storage)?;
+    if sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        attr, coins, from_json,
+        testing::{message_info, mock_dependencies_with_balance, mock_env},
+        to_json_binary, CosmosMsg, Decimal, Uint128, WasmMsg,
+    };
+    use lst_common::{
+        hub::ExecuteMsg as HubExecuteMsg,
+        insurance_msg::{
+            ClaimsResponse, ExecuteMsg, FundStateResponse, InstantiateMsg, PayoutResponse, QueryMsg,
+        },
+        ContractError,
+    };
+
+    use super::{execute, instantiate, query};
+
+    const DENOM: &str = "ubbn";
+
+    #[test]
+    fn test_update_config() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let env = mock_env();
+
+        let owner = deps.api.addr_make("owner");
+        let hub_contract = deps.api.addr_make("hub_contract");
+
+        // coverage above 100% is rejected
+        {
+            let msg = InstantiateMsg {
+                hub_contract: hub_contract.to_string(),
+                denom: DENOM.to_string(),
+                coverage_rate: Decimal::percent(101),
+                max_payout: Uint128::new(1000),
+                cooldown: 100,
+            };
+            let err = instantiate(deps.as_mut(), env.clone(), message_info(&owner, &[]), msg)
+                .unwrap_err();
+            assert_eq!(err, ContractError::InvalidCoverageRate {});
+        }
+
+        let msg = InstantiateMsg {
+            hub_contract: hub_contract.to_string(),
+            denom: DENOM.to_string(),
+            coverage_rate: Decimal::percent(50),
+            max_payout: Uint128::new(1000),
+            cooldown: 100,
+        };
+        instantiate(deps.as_mut(), env.clone(), message_info(&owner, &[]), msg).unwrap();
+
+        let update = |coverage_rate: Decimal| ExecuteMsg::UpdateConfig {
+            owner: None,
+            hub_contract: None,
+            coverage_rate: Some(coverage_rate),
+            max_payout: Some(Uint128::new(2000)),
+            cooldown: Some(200),
+        };
+
+        // only the owner can update the config
+        {
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                message_info(&hub_contract, &[]),
+                update(Decimal::percent(80)),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+
+        {
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                message_info(&owner, &[]),
+                update(Decimal::percent(150)),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidCoverageRate {});
+        }
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&owner, &[]),
+            update(Decimal::percent(80)),
+        )
+        .unwrap();
+
+        let config: lst_common::insurance_msg::Config =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.coverage_rate, Decimal::percent(80));
+        assert_eq!(config.max_payout, Uint128::new(2000));
+        assert_eq!(config.cooldown, 200);
+    }
+
+    #[test]
+    fn test_request_top_up() {
+        let mut deps = mock_dependencies_with_balance(&coins(1500, DENOM));
+        let mut env = mock_env();
+
+        let owner = deps.api.addr_make("owner");
+        let hub_contract = deps.api.addr_make("hub_contract");
+
+        let msg = InstantiateMsg {
+            hub_contract: hub_contract.to_string(),
+            denom: DENOM.to_string(),
+            coverage_rate: Decimal::percent(50),
+            max_payout: Uint128::new(1000),
+            cooldown: 100,
+        };
+        instantiate(deps.as_mut(), env.clone(), message_info(&owner, &[]), msg).unwrap();
+
+        let request = |loss: u128| ExecuteMsg::RequestTopUp {
+            loss: Uint128::new(loss),
+        };
+        let top_up = |amount: u128| {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: hub_contract.to_string(),
+                msg: to_json_binary(&HubExecuteMsg::InsuranceTopUp {}).unwrap(),
+                funds: coins(amount, DENOM),
+            })
+        };
+
+        // only the hub can claim
+        {
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                message_info(&owner, &[]),
+                request(100),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+
+        // half of the loss is covered
+        {
+            let payout: PayoutResponse = from_json(
+                query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::Payout {
+                        loss: Uint128::new(600),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(payout.payout, Uint128::new(300));
+
+            let res = execute(
+                deps.as_mut(),
+                env.clone(),
+                message_info(&hub_contract, &[]),
+                request(600),
+            )
+            .unwrap();
+            assert_eq!(res.messages.len(), 1);
+            assert_eq!(res.messages[0].msg, top_up(300));
+            assert!(res.attributes.contains(&attr("payout", "300")));
+        }
+
+        // claims during the cooldown are recorded without a payout
+        {
+            env.block.time = env.block.time.plus_seconds(99);
+            let res = execute(
+                deps.as_mut(),
+                env.clone(),
+                message_info(&hub_contract, &[]),
+                request(600),
+            )
+            .unwrap();
+            assert!(res.messages.is_empty());
+            assert!(res.attributes.contains(&attr("payout", "0")));
+        }
+
+        // the payout is capped by max_payout
+        {
+            env.block.time = env.block.time.plus_seconds(1);
+            let res = execute(
+                deps.as_mut(),
+                env.clone(),
+                message_info(&hub_contract, &[]),
+                request(5000),
+            )
+            .unwrap();
+            assert_eq!(res.messages[0].msg, top_up(1000));
+        }
+
+        // and by the balance of the fund
+        {
+            deps.querier
+                .bank
+                .update_balance(&env.contract.address, coins(200, DENOM));
+            env.block.time = env.block.time.plus_seconds(100);
+            let res = execute(
+                deps.as_mut(),
+                env.clone(),
+                message_info(&hub_contract, &[]),
+                request(5000),
+            )
+            .unwrap();
+            assert_eq!(res.messages[0].msg, top_up(200));
+        }
+
+        let fund_state: FundStateResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::FundState {}).unwrap()).unwrap();
+        assert_eq!(fund_state.balance, Uint128::new(200));
+        assert_eq!(fund_state.total_paid_out, Uint128::new(1500));
+        assert_eq!(fund_state.claim_count, 4);
+        assert_eq!(
+            fund_state.cooldown_end,
+            Some(env.block.time.seconds() + 100)
+        );
+
+        let claims: ClaimsResponse = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Claims {
+                    start_from: Some(1),
+                    limit: Some(2),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(claims.claims.len(), 2);
+        assert_eq!(claims.claims[0].id, 1);
+        assert_eq!(claims.claims[0].loss, Uint128::new(600));
+        assert!(claims.claims[0].payout.is_zero());
+        assert_eq!(claims.claims[1].payout, Uint128::new(1000));
+    }
+}
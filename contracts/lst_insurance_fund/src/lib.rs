@@ -0,0 +1,8 @@
+use cosmwasm_std::Decimal;
+
+pub mod contract;
+mod state;
+pub mod testing;
+
+// a top-up covers at most the whole slashing loss
+pub const MAX_COVERAGE_RATE: Decimal = Decimal::one();
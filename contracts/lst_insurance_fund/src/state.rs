@@ -0,0 +1,10 @@
+use cw_storage_plus::{Item, Map};
+use lst_common::insurance_msg::{Claim, Config, FundState};
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Payout totals over all claims
+pub const FUND_STATE: Item<FundState> = Item::new("fund_state");
+
+/// Claims made by the hub keyed by id
+pub const CLAIMS: Map<u64, Claim> = Map::new("claims");
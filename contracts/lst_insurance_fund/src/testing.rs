@@ -0,0 +1,42 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use crate::contract::{execute, instantiate, query};
+use cosmwasm_std::{Addr, Decimal, Env, Uint128};
+use cw_multi_test::{Contract, ContractWrapper};
+use lst_common::babylon::{EpochingMsg, EpochingQuery};
+use lst_common::insurance_msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use lst_common::testing::{BabylonApp, TestingContract};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InsuranceFundContract {
+    pub addr: Addr,
+    pub init: InstantiateMsg,
+}
+
+impl TestingContract<InstantiateMsg, ExecuteMsg, QueryMsg> for InsuranceFundContract {
+    fn wrapper() -> Box<dyn Contract<EpochingMsg, EpochingQuery>> {
+        Box::new(ContractWrapper::new_with_empty(execute, instantiate, query))
+    }
+
+    fn default_init(app: &mut BabylonApp, _env: &Env) -> InstantiateMsg {
+        InstantiateMsg {
+            hub_contract: Self::get_contract_addr(app, "StakingHubContract").to_string(),
+            denom: "BABY".to_string(),
+            coverage_rate: Decimal::percent(50),
+            max_payout: Uint128::new(1_000_000),
+            cooldown: 24 * 60 * 60,
+        }
+    }
+
+    fn new(app: &mut BabylonApp, env: &Env, msg: Option<InstantiateMsg>) -> Self {
+        let init = msg.unwrap_or(Self::default_init(app, env));
+        let code_id = Self::store_code(app);
+        let addr = Self::instantiate(app, code_id, "InsuranceFundContract", None, &init);
+        Self { addr, init }
+    }
+
+    fn addr(&self) -> &Addr {
+        &self.addr
+    }
+}
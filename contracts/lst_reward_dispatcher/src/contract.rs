@@ -1,21 +1,40 @@
 use cosmwasm_std::{
     attr, to_json_binary, Addr, Attribute, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps,
-    DepsMut, Env, MessageInfo, Response, Uint128, WasmMsg,
+    DepsMut, Env, MessageInfo, Order, Reply, Response, StdError, StdResult, SubMsg, Uint128,
+    Uint256, WasmMsg,
 };
 use cw2::set_contract_version;
+use cw_storage_plus::Bound;
 use lst_common::{
-    hub::{is_paused, ExecuteMsg::StakeRewards},
+    hub::{
+        is_paused,
+        ExecuteMsg::{StakeRewards, StakeRewardsWithFeeShares},
+        HighWaterMarkResponse, QueryMsg as HubQueryMsg,
+    },
     to_checked_address,
     types::LstResult,
     ContractError, MigrateMsg,
 };
 
-use crate::{state::CONFIG, MAX_FEE_RATE};
-use lst_common::rewards_msg::{Config, ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::{
+    state::{
+        record_dispatch, CONFIG, DENOM_POLICIES, DISPATCH_HISTORY, LEGACY_CONFIG, REWARD_STATS,
+        REWARD_STREAM,
+    },
+    MAX_FEE_RATE, MAX_REWARD_STREAM_WINDOW,
+};
+use lst_common::rewards_msg::{
+    compute_fee, Config, DenomPoliciesResponse, DenomPolicy, DispatchHistoryResponse, ExecuteMsg,
+    InstantiateMsg, QueryMsg, RewardStream, VestingRewardsResponse,
+};
+use lst_common::router::ExecuteMsg as RouterExecuteMsg;
 
 const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const SWAP_REPLY_ID: u64 = 1;
+const DISPATCH_REPLY_ID: u64 = 2;
+
 #[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -29,7 +48,7 @@ pub fn instantiate(
     let InstantiateMsg {
         hub_contract,
         reward_denom,
-        fee_addr,
+        fee_recipients,
         fee_rate,
     } = msg;
 
@@ -42,8 +61,13 @@ pub fn instantiate(
         owner: info.sender,
         hub_contract: to_checked_address(deps.as_ref(), &hub_contract)?,
         reward_denom,
-        fee_addr: to_checked_address(deps.as_ref(), &fee_addr)?,
+        fee_recipients: validate_fee_recipients(deps.as_ref(), fee_recipients)?,
         fee_rate,
+        router: None,
+        treasury: None,
+        reward_stream_window: 0,
+        fee_as_shares: false,
+        performance_fee: false,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -58,20 +82,47 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> L
         ExecuteMsg::UpdateConfig {
             owner,
             hub_contract,
-            fee_addr,
+            fee_recipients,
             fee_rate,
-        } => execute_update_config(deps, env, info, owner, hub_contract, fee_addr, fee_rate),
+            router,
+            treasury,
+            reward_stream_window,
+            fee_as_shares,
+            performance_fee,
+        } => execute_update_config(
+            deps,
+            env,
+            info,
+            owner,
+            hub_contract,
+            fee_recipients,
+            fee_rate,
+            router,
+            treasury,
+            reward_stream_window,
+            fee_as_shares,
+            performance_fee,
+        ),
+        ExecuteMsg::SetDenomPolicy { denom, policy } => {
+            execute_set_denom_policy(deps, info, denom, policy)
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn execute_update_config(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     owner: Option<String>,
     hub_contract: Option<String>,
-    fee_addr: Option<String>,
+    fee_recipients: Option<Vec<(String, Decimal)>>,
     fee_rate: Option<Decimal>,
+    router: Option<String>,
+    treasury: Option<String>,
+    reward_stream_window: Option<u64>,
+    fee_as_shares: Option<bool>,
+    performance_fee: Option<bool>,
 ) -> LstResult<Response> {
     is_authorized_sender(deps.as_ref(), info.sender)?;
 
@@ -82,6 +133,16 @@ fn execute_update_config(
         }
     }
 
+    if let Some(window) = reward_stream_window {
+        if window > MAX_REWARD_STREAM_WINDOW {
+            return Err(ContractError::InvalidRewardStreamWindow {});
+        }
+    }
+
+    let fee_recipients = fee_recipients
+        .map(|recipients| validate_fee_recipients(deps.as_ref(), recipients))
+        .transpose()?;
+
     let mut config: Config = query_config(deps.as_ref())?;
 
     // Update config with all provided values in a single operation
@@ -91,25 +152,105 @@ fn execute_update_config(
     if let Some(h) = hub_contract {
         config.hub_contract = to_checked_address(deps.as_ref(), &h)?;
     }
-    if let Some(s) = fee_addr {
-        config.fee_addr = to_checked_address(deps.as_ref(), &s)?;
+    if let Some(recipients) = fee_recipients {
+        config.fee_recipients = recipients;
     }
     if let Some(rate) = fee_rate {
         config.fee_rate = rate;
     }
+    if let Some(r) = router {
+        config.router = Some(to_checked_address(deps.as_ref(), &r)?);
+    }
+    if let Some(t) = treasury {
+        config.treasury = Some(to_checked_address(deps.as_ref(), &t)?);
+    }
+    if let Some(window) = reward_stream_window {
+        config.reward_stream_window = window;
+    }
+    if let Some(as_shares) = fee_as_shares {
+        config.fee_as_shares = as_shares;
+    }
+    if let Some(performance) = performance_fee {
+        config.performance_fee = performance;
+    }
 
     CONFIG.save(deps.storage, &config)?;
     Ok(Response::default())
 }
 
+/// Sets or removes the policy for a non-staking denom. Swapping needs the router and
+/// forwarding needs the treasury to be configured first.
+fn execute_set_denom_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    policy: Option<DenomPolicy>,
+) -> LstResult<Response> {
+    is_authorized_sender(deps.as_ref(), info.sender)?;
+
+    let config = query_config(deps.as_ref())?;
+    if denom == config.reward_denom {
+        return Err(ContractError::InvalidDenomPolicy(
+            "reward denom is always restaked".to_string(),
+        ));
+    }
+
+    let policy_name = match &policy {
+        Some(DenomPolicy::Swap { min_price }) => {
+            if config.router.is_none() {
+                return Err(ContractError::InvalidDenomPolicy(
+                    "router not set".to_string(),
+                ));
+            }
+            if min_price.is_zero() {
+                return Err(ContractError::InvalidDenomPolicy(
+                    "min price must be above zero".to_string(),
+                ));
+            }
+            "swap"
+        }
+        Some(DenomPolicy::Forward {}) => {
+            if config.treasury.is_none() {
+                return Err(ContractError::InvalidDenomPolicy(
+                    "treasury not set".to_string(),
+                ));
+            }
+            "forward"
+        }
+        Some(DenomPolicy::Hold {}) => "hold",
+        None => "none",
+    };
+
+    match &policy {
+        Some(policy) => DENOM_POLICIES.save(deps.storage, &denom, policy)?,
+        None => DENOM_POLICIES.remove(deps.storage, &denom),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_denom_policy"),
+        attr("denom", denom),
+        attr("policy", policy_name),
+    ]))
+}
+
 /// Dispatches rewards to the hub contract.
 ///
 /// This function checks if the hub contract is paused, verifies the sender's authorization,
 /// calculates the fee, and sends the rewards and fee to the respective addresses.
+///
+/// With `performance_fee` the fee is only taken on the rewards lifting the hub exchange rate
+/// above its high-water mark, so holders are not charged while recovering from slashing.
+///
+/// With `fee_as_shares` the fee is not paid out but restaked with the rewards, and the hub
+/// mints it as LST to the fee recipients.
+///
+/// With a reward stream window the rewards are held and released to the hub linearly over
+/// the window instead, so the exchange rate does not jump on each dispatch. Rewards that have
+/// not vested yet roll into the stream started by the next dispatch.
 fn execute_dispatch_rewards(deps: DepsMut, env: Env, info: MessageInfo) -> LstResult<Response> {
     let config = query_config(deps.as_ref())?;
 
-    let hub_addr = config.hub_contract;
+    let hub_addr = config.hub_contract.clone();
     if is_paused(deps.as_ref(), hub_addr.to_string())? {
         return Err(ContractError::HubPaused);
     }
@@ -118,47 +259,158 @@ fn execute_dispatch_rewards(deps: DepsMut, env: Env, info: MessageInfo) -> LstRe
         return Err(ContractError::Unauthorized {});
     }
 
+    let mut stream = REWARD_STREAM.may_load(deps.storage)?.unwrap_or_default();
+    let now = env.block.time.seconds();
+
+    // The balance includes the streamed rewards that were not released yet
     let mut rewards = deps
         .querier
-        .query_balance(env.contract.address, config.reward_denom.clone())?;
+        .query_balance(&env.contract.address, config.reward_denom.clone())?;
+    rewards.amount = rewards
+        .amount
+        .checked_sub(stream.amount)
+        .map_err(|e| ContractError::Overflow(e.to_string()))?;
+    let gross = rewards.amount;
 
     let mut messages: Vec<CosmosMsg> = vec![];
+    let mut sub_msgs: Vec<SubMsg> = vec![];
     let mut attrs: Vec<Attribute> = vec![];
+    let mut fee_shares: Vec<(Addr, Uint128)> = vec![];
+
+    let chargeable = if config.performance_fee {
+        let high_water_mark: HighWaterMarkResponse = deps
+            .querier
+            .query_wasm_smart(&hub_addr, &HubQueryMsg::HighWaterMark {})?;
+        // Streamed rewards are not staked by the hub yet but already count towards the recovery
+        let recovery_amount = high_water_mark
+            .recovery_amount
+            .saturating_sub(stream.amount);
+
+        attrs.push(attr(
+            "high_water_mark",
+            high_water_mark.high_water_mark.to_string(),
+        ));
+        rewards.amount.saturating_sub(recovery_amount)
+    } else {
+        rewards.amount
+    };
 
-    let reward_fee_amt = compute_fee(rewards.amount, config.fee_rate);
+    let reward_fee_amt = compute_fee(chargeable, config.fee_rate);
+    if config.performance_fee {
+        attrs.push(attr("performance_fee", reward_fee_amt.to_string()));
+    }
 
     if !reward_fee_amt.is_zero() {
         let fee_coin = Coin {
-            denom: config.reward_denom,
+            denom: config.reward_denom.clone(),
             amount: reward_fee_amt,
         };
 
         attrs.push(attr("fee", fee_coin.to_string()));
 
-        messages.push(
-            BankMsg::Send {
-                to_address: config.fee_addr.to_string(),
-                amount: vec![fee_coin],
+        for (recipient, amount) in split_fee(reward_fee_amt, &config.fee_recipients)? {
+            if amount.is_zero() {
+                continue;
             }
-            .into(),
-        );
+
+            let share = Coin {
+                denom: config.reward_denom.clone(),
+                amount,
+            };
+
+            attrs.push(attr("fee_recipient", format!("{}:{}", recipient, share)));
+
+            if !config.fee_as_shares {
+                messages.push(
+                    BankMsg::Send {
+                        to_address: recipient.to_string(),
+                        amount: vec![share],
+                    }
+                    .into(),
+                );
+            }
+            fee_shares.push((recipient, amount));
+        }
     }
 
     rewards.amount = rewards
         .amount
         .checked_sub(reward_fee_amt)
         .map_err(|e| ContractError::Overflow(e.to_string()))?;
+    let net = rewards.amount;
+
+    if config.reward_stream_window == 0 {
+        rewards.amount += stream.amount;
+        stream = RewardStream::default();
+    } else {
+        let vested = stream.vested(now);
+        rewards.amount = vested;
+
+        // The unvested rest keeps its end time and the new rewards get the full window, the merged
+        // stream ends at their amount-weighted average so earlier rewards are not stretched out
+        let unvested = stream.amount - vested;
+        let amount = unvested + net;
+        let duration = if amount.is_zero() {
+            0
+        } else {
+            let weighted = unvested.full_mul(stream.end_time.saturating_sub(now))
+                + net.full_mul(config.reward_stream_window);
+            // an average of two durations within the window always fits
+            Uint128::try_from(weighted / Uint256::from(amount))
+                .map_or(config.reward_stream_window, |duration| {
+                    duration.u128() as u64
+                })
+        };
+        stream = RewardStream {
+            amount,
+            start_time: now,
+            end_time: now + duration,
+        };
+
+        attrs.push(attr("streamed", net.to_string()));
+    }
+    REWARD_STREAM.save(deps.storage, &stream)?;
+
+    // The record holds the current exchange rate until the restaked rewards update it in `reply`
+    let exchange_rate: Decimal = deps
+        .querier
+        .query_wasm_smart(&hub_addr, &HubQueryMsg::ExchangeRate {})?;
+    let dispatch_id = record_dispatch(deps.storage, &env, gross, &fee_shares, net, exchange_rate)?;
+
+    // The fee is restaked right away, only the rewards are streamed
+    let hub_msg = if config.fee_as_shares && !fee_shares.is_empty() {
+        rewards.amount += reward_fee_amt;
+        StakeRewardsWithFeeShares {
+            fee_shares: fee_shares
+                .iter()
+                .map(|(recipient, amount)| (recipient.to_string(), *amount))
+                .collect(),
+        }
+    } else {
+        StakeRewards {}
+    };
 
     if !rewards.amount.is_zero() {
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: hub_addr.to_string(),
-            msg: to_json_binary(&StakeRewards {})?,
-            funds: vec![rewards.clone()],
-        }));
+        sub_msgs.push(
+            SubMsg::reply_on_success(
+                WasmMsg::Execute {
+                    contract_addr: hub_addr.to_string(),
+                    msg: to_json_binary(&hub_msg)?,
+                    funds: vec![rewards.clone()],
+                },
+                DISPATCH_REPLY_ID,
+            )
+            .with_payload(dispatch_id.to_be_bytes().to_vec()),
+        );
     }
 
+    let (mut policy_msgs, mut policy_attrs) = apply_denom_policies(deps.as_ref(), &env, &config)?;
+    sub_msgs.append(&mut policy_msgs);
+    attrs.append(&mut policy_attrs);
+
     Ok(Response::new()
         .add_messages(messages)
+        .add_submessages(sub_msgs)
         .add_attributes(vec![
             attr("action", "claim_rewards"),
             attr("reward_amt", rewards.to_string()),
@@ -166,13 +418,151 @@ fn execute_dispatch_rewards(deps: DepsMut, env: Env, info: MessageInfo) -> LstRe
         .add_attributes(attrs))
 }
 
+/// Builds the messages handling the non-staking denoms held by the dispatcher.
+///
+/// Swaps are sent through the router with the output paid back to the dispatcher, so the
+/// proceeds are restaked on the next dispatch. A swap that fails, e.g. below the minimum price,
+/// is caught in `reply` and the balance is held until the next attempt.
+fn apply_denom_policies(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+) -> LstResult<(Vec<SubMsg>, Vec<Attribute>)> {
+    let mut messages: Vec<SubMsg> = vec![];
+    let mut attrs: Vec<Attribute> = vec![];
+
+    let policies = DENOM_POLICIES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (denom, policy) in policies {
+        let balance = deps.querier.query_balance(&env.contract.address, &denom)?;
+        if balance.amount.is_zero() {
+            continue;
+        }
+
+        match (policy, &config.router, &config.treasury) {
+            (DenomPolicy::Swap { min_price }, Some(router), _) => {
+                let min_output = balance.amount.mul_ceil(min_price);
+
+                attrs.push(attr("swap", balance.to_string()));
+                messages.push(
+                    SubMsg::reply_on_error(
+                        WasmMsg::Execute {
+                            contract_addr: router.to_string(),
+                            msg: to_json_binary(&RouterExecuteMsg::Swap {
+                                ask_denom: config.reward_denom.clone(),
+                                min_output,
+                            })?,
+                            funds: vec![balance],
+                        },
+                        SWAP_REPLY_ID,
+                    )
+                    .with_payload(denom.into_bytes()),
+                );
+            }
+            (DenomPolicy::Forward {}, _, Some(treasury)) => {
+                attrs.push(attr("forward", balance.to_string()));
+                messages.push(SubMsg::new(BankMsg::Send {
+                    to_address: treasury.to_string(),
+                    amount: vec![balance],
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    Ok((messages, attrs))
+}
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> LstResult<Response> {
+    match msg.id {
+        DISPATCH_REPLY_ID => {
+            let id = u64::from_be_bytes(
+                msg.payload
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| StdError::generic_err("Invalid dispatch id"))?,
+            );
+            let config = query_config(deps.as_ref())?;
+            let exchange_rate: Decimal = deps
+                .querier
+                .query_wasm_smart(&config.hub_contract, &HubQueryMsg::ExchangeRate {})?;
+
+            DISPATCH_HISTORY.update(deps.storage, id, |record| -> LstResult<_> {
+                let mut record =
+                    record.ok_or_else(|| StdError::generic_err("Dispatch record not found"))?;
+                record.exchange_rate = exchange_rate;
+                Ok(record)
+            })?;
+
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "record_exchange_rate"),
+                attr("exchange_rate", exchange_rate.to_string()),
+            ]))
+        }
+        SWAP_REPLY_ID => Ok(Response::new().add_attributes(vec![
+            attr("action", "swap_failed"),
+            attr("denom", String::from_utf8_lossy(msg.payload.as_slice())),
+            attr("error", msg.result.into_result().err().unwrap_or_default()),
+        ])),
+        id => Err(StdError::generic_err(format!("Unknown reply id: {}", id)).into()),
+    }
+}
+
 #[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> LstResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> LstResult<Binary> {
     match msg {
         QueryMsg::Config {} => Ok(to_json_binary(&query_config(deps)?)?),
+        QueryMsg::DenomPolicies {} => Ok(to_json_binary(&query_denom_policies(deps)?)?),
+        QueryMsg::VestingRewards {} => Ok(to_json_binary(&query_vesting_rewards(deps, env)?)?),
+        QueryMsg::RewardStats {} => Ok(to_json_binary(
+            &REWARD_STATS.may_load(deps.storage)?.unwrap_or_default(),
+        )?),
+        QueryMsg::DispatchHistory { start_from, limit } => Ok(to_json_binary(
+            &query_dispatch_history(deps, start_from, limit)?,
+        )?),
     }
 }
 
+fn query_vesting_rewards(deps: Deps, env: Env) -> LstResult<VestingRewardsResponse> {
+    let stream = REWARD_STREAM.may_load(deps.storage)?.unwrap_or_default();
+    let vested = stream.vested(env.block.time.seconds());
+
+    Ok(VestingRewardsResponse {
+        vesting: stream.amount - vested,
+        releasable: vested,
+        end_time: stream.end_time,
+    })
+}
+
+fn query_dispatch_history(
+    deps: Deps,
+    start_from: Option<u64>,
+    limit: Option<u32>,
+) -> LstResult<DispatchHistoryResponse> {
+    let history = DISPATCH_HISTORY
+        .range(
+            deps.storage,
+            start_from.map(Bound::inclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit.unwrap_or(u32::MAX) as usize)
+        .map(|item| Ok(item?.1))
+        .collect::<LstResult<Vec<_>>>()?;
+
+    Ok(DispatchHistoryResponse { history })
+}
+
+fn query_denom_policies(deps: Deps) -> LstResult<DenomPoliciesResponse> {
+    let policies = DENOM_POLICIES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(DenomPoliciesResponse { policies })
+}
+
 fn query_config(deps: Deps) -> LstResult<Config> {
     Ok(CONFIG.load(deps.storage)?)
 }
@@ -182,11 +572,80 @@ fn query_config(deps: Deps) -> LstResult<Config> {
 #[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> LstResult<Response> {
     cw2::ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // Configs stored before fee splitting send the whole fee to a single address
+    if CONFIG.load(deps.storage).is_err() {
+        let legacy = LEGACY_CONFIG.load(deps.storage)?;
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                owner: legacy.owner,
+                hub_contract: legacy.hub_contract,
+                reward_denom: legacy.reward_denom,
+                fee_recipients: vec![(legacy.fee_addr, Decimal::one())],
+                fee_rate: legacy.fee_rate,
+                router: None,
+                treasury: None,
+                reward_stream_window: 0,
+                fee_as_shares: false,
+                performance_fee: false,
+            },
+        )?;
+    }
+
     Ok(Response::default())
 }
 
-fn compute_fee(amount: Uint128, fee_rate: Decimal) -> Uint128 {
-    (Decimal::from_ratio(amount, 1u128) * fee_rate).to_uint_ceil()
+/// Validates the fee recipients: the list must be non-empty, addresses unique and valid,
+/// and every weight non-zero with all weights summing to exactly one.
+fn validate_fee_recipients(
+    deps: Deps,
+    recipients: Vec<(String, Decimal)>,
+) -> LstResult<Vec<(Addr, Decimal)>> {
+    if recipients.is_empty() {
+        return Err(ContractError::InvalidFeeRecipients {});
+    }
+
+    let mut checked: Vec<(Addr, Decimal)> = Vec::with_capacity(recipients.len());
+    let mut total_weight = Decimal::zero();
+
+    for (address, weight) in recipients {
+        let address = to_checked_address(deps, &address)?;
+        if weight.is_zero() || checked.iter().any(|(addr, _)| addr == address) {
+            return Err(ContractError::InvalidFeeRecipients {});
+        }
+
+        total_weight = total_weight
+            .checked_add(weight)
+            .map_err(|_| ContractError::InvalidFeeRecipients {})?;
+        checked.push((address, weight));
+    }
+
+    if total_weight != Decimal::one() {
+        return Err(ContractError::InvalidFeeRecipients {});
+    }
+
+    Ok(checked)
+}
+
+/// Splits the fee across the recipients by weight. Each share is rounded down and the
+/// rounding remainder goes to the first recipient, so the whole fee is always paid out.
+fn split_fee(fee: Uint128, recipients: &[(Addr, Decimal)]) -> LstResult<Vec<(Addr, Uint128)>> {
+    let mut shares: Vec<(Addr, Uint128)> = recipients
+        .iter()
+        .map(|(addr, weight)| (addr.clone(), fee.mul_floor(*weight)))
+        .collect();
+
+    let distributed: Uint128 = shares.iter().map(|(_, amount)| amount).sum();
+    let remainder = fee
+        .checked_sub(distributed)
+        .map_err(|e| ContractError::Overflow(e.to_string()))?;
+
+    if let Some((_, first)) = shares.first_mut() {
+        *first += remainder;
+    }
+
+    Ok(shares)
 }
 
 fn is_authorized_sender(deps: Deps, sender: Addr) -> LstResult<()> {
@@ -199,20 +658,28 @@ fn is_authorized_sender(deps: Deps, sender: Addr) -> LstResult<()> {
 
 #[cfg(test)]
 mod tests {
-    use crate::contract::instantiate;
+    use crate::contract::{instantiate, migrate, query, CONTRACT_NAME, DISPATCH_REPLY_ID};
+    use crate::state::{LegacyConfig, LEGACY_CONFIG};
+    use crate::{MAX_DISPATCH_HISTORY, MAX_REWARD_STREAM_WINDOW};
     use cosmwasm_std::{
         attr, coins, from_json,
         testing::{message_info, mock_dependencies, mock_env},
-        to_json_binary, BankMsg, ContractResult, CosmosMsg, Decimal, SubMsg, SystemError,
-        SystemResult, WasmMsg, WasmQuery,
+        to_json_binary, BankMsg, ContractResult, CosmosMsg, Decimal, Deps, Env, Response, SubMsg,
+        SystemError, SystemResult, Uint128, WasmMsg, WasmQuery,
     };
     use lst_common::{
         hub::{ExecuteMsg as HubExecuteMsg, Parameters, QueryMsg as HubQueryMsg},
-        rewards_msg::InstantiateMsg,
-        ContractError,
+        rewards_msg::{
+            DenomPolicy, DispatchRecord, InstantiateMsg, QueryMsg, RewardStats,
+            VestingRewardsResponse,
+        },
+        ContractError, MigrateMsg,
     };
 
-    use super::{execute_dispatch_rewards, execute_update_config, query_config};
+    use super::{
+        execute_dispatch_rewards, execute_set_denom_policy, execute_update_config, query_config,
+        query_denom_policies, query_dispatch_history, split_fee,
+    };
 
     #[test]
     fn test_instantiate() {
@@ -231,7 +698,7 @@ mod tests {
             let msg = InstantiateMsg {
                 hub_contract: hub_contract.to_string(),
                 reward_denom: denom.to_string(),
-                fee_addr: fee_addr.to_string(),
+                fee_recipients: vec![(fee_addr.to_string(), Decimal::one())],
                 fee_rate: "0.1".parse().unwrap(),
             };
 
@@ -242,13 +709,26 @@ mod tests {
             let msg = InstantiateMsg {
                 hub_contract: hub_contract.to_string(),
                 reward_denom: denom.to_string(),
-                fee_addr: fee_addr.to_string(),
+                fee_recipients: vec![(fee_addr.to_string(), Decimal::one())],
                 fee_rate: "0.4".parse().unwrap(),
             };
 
-            let err = instantiate(deps.as_mut(), env, info, msg).unwrap_err();
+            let err = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
             assert_eq!(err, ContractError::InvalidFeeRate {});
         }
+
+        // Weights not summing to one
+        {
+            let msg = InstantiateMsg {
+                hub_contract: hub_contract.to_string(),
+                reward_denom: denom.to_string(),
+                fee_recipients: vec![(fee_addr.to_string(), Decimal::percent(90))],
+                fee_rate: "0.1".parse().unwrap(),
+            };
+
+            let err = instantiate(deps.as_mut(), env, info, msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidFeeRecipients {});
+        }
     }
 
     #[test]
@@ -269,7 +749,7 @@ mod tests {
             let msg = InstantiateMsg {
                 hub_contract: hub_contract.to_string(),
                 reward_denom: denom.to_string(),
-                fee_addr: fee_addr.to_string(),
+                fee_recipients: vec![(fee_addr.to_string(), Decimal::one())],
                 fee_rate,
             };
             instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
@@ -285,6 +765,11 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                None,
+                None,
+                None,
             )
             .unwrap();
 
@@ -292,7 +777,7 @@ mod tests {
             assert_eq!(config.owner, owner);
             assert_eq!(config.hub_contract, hub_contract);
             assert_eq!(config.reward_denom, denom.to_string());
-            assert_eq!(config.fee_addr, fee_addr);
+            assert_eq!(config.fee_recipients, vec![(fee_addr, Decimal::one())]);
             assert_eq!(config.fee_rate, fee_rate);
         }
 
@@ -301,7 +786,8 @@ mod tests {
         // update config
         {
             let new_hub_contract = deps.api.addr_make("new_hub_contract");
-            let new_fee_addr = deps.api.addr_make("new_fee_addr");
+            let treasury = deps.api.addr_make("treasury");
+            let insurance = deps.api.addr_make("insurance");
             let new_fee_rate = Decimal::percent(20);
 
             execute_update_config(
@@ -310,8 +796,16 @@ mod tests {
                 info.clone(),
                 Some(new_owner.to_string()),
                 Some(new_hub_contract.to_string()),
-                Some(new_fee_addr.to_string()),
+                Some(vec![
+                    (treasury.to_string(), Decimal::percent(70)),
+                    (insurance.to_string(), Decimal::percent(30)),
+                ]),
                 Some(new_fee_rate),
+                None,
+                None,
+                None,
+                None,
+                None,
             )
             .unwrap();
 
@@ -319,7 +813,13 @@ mod tests {
             assert_eq!(config.owner, new_owner);
             assert_eq!(config.hub_contract, new_hub_contract);
             assert_eq!(config.reward_denom, denom.to_string());
-            assert_eq!(config.fee_addr, new_fee_addr);
+            assert_eq!(
+                config.fee_recipients,
+                vec![
+                    (treasury, Decimal::percent(70)),
+                    (insurance, Decimal::percent(30)),
+                ]
+            );
             assert_eq!(config.fee_rate, new_fee_rate);
         }
 
@@ -333,6 +833,11 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                None,
+                None,
+                None,
             )
             .unwrap_err();
 
@@ -352,11 +857,446 @@ mod tests {
                 None,
                 None,
                 Some(new_fee_rate),
+                None,
+                None,
+                None,
+                None,
+                None,
             )
             .unwrap_err();
 
             assert_eq!(err, ContractError::InvalidFeeRate {});
         }
+
+        // update invalid fee recipients
+        {
+            let info = message_info(&new_owner, &[]);
+            let treasury = deps.api.addr_make("treasury").to_string();
+            let insurance = deps.api.addr_make("insurance").to_string();
+
+            for recipients in [
+                vec![],
+                vec![(treasury.clone(), Decimal::percent(60))],
+                vec![
+                    (treasury.clone(), Decimal::one()),
+                    (insurance.clone(), Decimal::zero()),
+                ],
+                vec![
+                    (treasury.clone(), Decimal::percent(50)),
+                    (treasury.clone(), Decimal::percent(50)),
+                ],
+            ] {
+                let err = execute_update_config(
+                    deps.as_mut(),
+                    env.clone(),
+                    info.clone(),
+                    None,
+                    None,
+                    Some(recipients),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap_err();
+
+                assert_eq!(err, ContractError::InvalidFeeRecipients {});
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_denom_policy() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = deps.api.addr_make("owner");
+        let router = deps.api.addr_make("router");
+        let treasury = deps.api.addr_make("treasury");
+        let denom = "denom";
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate
+        {
+            let msg = InstantiateMsg {
+                hub_contract: deps.api.addr_make("hub_contract").to_string(),
+                reward_denom: denom.to_string(),
+                fee_recipients: vec![(deps.api.addr_make("fee_addr").to_string(), Decimal::one())],
+                fee_rate: Decimal::percent(10),
+            };
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // Router and treasury not set
+        {
+            let err = execute_set_denom_policy(
+                deps.as_mut(),
+                info.clone(),
+                "uatom".to_string(),
+                Some(DenomPolicy::Swap {
+                    min_price: Decimal::percent(99),
+                }),
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidDenomPolicy("router not set".to_string())
+            );
+
+            let err = execute_set_denom_policy(
+                deps.as_mut(),
+                info.clone(),
+                "uosmo".to_string(),
+                Some(DenomPolicy::Forward {}),
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidDenomPolicy("treasury not set".to_string())
+            );
+        }
+
+        execute_update_config(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            None,
+            None,
+            None,
+            None,
+            Some(router.to_string()),
+            Some(treasury.to_string()),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // Invalid policies
+        {
+            let err = execute_set_denom_policy(
+                deps.as_mut(),
+                info.clone(),
+                denom.to_string(),
+                Some(DenomPolicy::Hold {}),
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidDenomPolicy("reward denom is always restaked".to_string())
+            );
+
+            let err = execute_set_denom_policy(
+                deps.as_mut(),
+                info.clone(),
+                "uatom".to_string(),
+                Some(DenomPolicy::Swap {
+                    min_price: Decimal::zero(),
+                }),
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidDenomPolicy("min price must be above zero".to_string())
+            );
+        }
+
+        // Set, query and remove policies
+        {
+            let swap = DenomPolicy::Swap {
+                min_price: Decimal::percent(99),
+            };
+            let res = execute_set_denom_policy(
+                deps.as_mut(),
+                info.clone(),
+                "uatom".to_string(),
+                Some(swap.clone()),
+            )
+            .unwrap();
+            assert_eq!(
+                res.attributes,
+                vec![
+                    attr("action", "set_denom_policy"),
+                    attr("denom", "uatom"),
+                    attr("policy", "swap"),
+                ]
+            );
+
+            execute_set_denom_policy(
+                deps.as_mut(),
+                info.clone(),
+                "uosmo".to_string(),
+                Some(DenomPolicy::Forward {}),
+            )
+            .unwrap();
+
+            assert_eq!(
+                query_denom_policies(deps.as_ref()).unwrap().policies,
+                vec![
+                    ("uatom".to_string(), swap),
+                    ("uosmo".to_string(), DenomPolicy::Forward {}),
+                ]
+            );
+
+            execute_set_denom_policy(deps.as_mut(), info.clone(), "uatom".to_string(), None)
+                .unwrap();
+
+            assert_eq!(
+                query_denom_policies(deps.as_ref()).unwrap().policies,
+                vec![("uosmo".to_string(), DenomPolicy::Forward {})]
+            );
+        }
+
+        // Unauthorized error
+        {
+            let info = message_info(&router, &[]);
+            let err = execute_set_denom_policy(
+                deps.as_mut(),
+                info,
+                "uosmo".to_string(),
+                Some(DenomPolicy::Hold {}),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+    }
+
+    #[test]
+    fn test_reward_streaming() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let owner = deps.api.addr_make("owner");
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let denom = "denom";
+
+        let info = message_info(&owner, &[]);
+        let hub_info = message_info(&hub_contract, &[]);
+
+        // instantiate
+        {
+            let msg = InstantiateMsg {
+                hub_contract: hub_contract.to_string(),
+                reward_denom: denom.to_string(),
+                fee_recipients: vec![(deps.api.addr_make("fee_addr").to_string(), Decimal::one())],
+                fee_rate: Decimal::percent(10),
+            };
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart {
+                contract_addr: _,
+                msg,
+            } => {
+                let msg: HubQueryMsg = from_json(msg).unwrap();
+                match msg {
+                    HubQueryMsg::Parameters {} => SystemResult::Ok(ContractResult::Ok(
+                        to_json_binary(&Parameters::default()).unwrap(),
+                    )),
+                    HubQueryMsg::ExchangeRate {} => SystemResult::Ok(ContractResult::Ok(
+                        to_json_binary(&Decimal::one()).unwrap(),
+                    )),
+                    _ => panic!("unexpected query"),
+                }
+            }
+            _ => SystemResult::Err(SystemError::Unknown {}),
+        });
+
+        let stake_rewards_amount = |res: &Response| -> Option<u128> {
+            res.messages.iter().find_map(|sub_msg| match &sub_msg.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) => Some(funds[0].amount.u128()),
+                _ => None,
+            })
+        };
+        let vesting_rewards = |deps: Deps, env: Env| -> VestingRewardsResponse {
+            from_json(query(deps, env, QueryMsg::VestingRewards {}).unwrap()).unwrap()
+        };
+
+        // Window longer than the maximum
+        {
+            let err = execute_update_config(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(MAX_REWARD_STREAM_WINDOW + 1),
+                None,
+                None,
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidRewardStreamWindow {});
+        }
+
+        execute_update_config(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(100),
+            None,
+            None,
+        )
+        .unwrap();
+
+        // Rewards after the fee are held and nothing is restaked yet
+        {
+            deps.querier
+                .bank
+                .update_balance(env.contract.address.clone(), coins(1000, denom));
+            let res =
+                execute_dispatch_rewards(deps.as_mut(), env.clone(), hub_info.clone()).unwrap();
+            assert_eq!(stake_rewards_amount(&res), None);
+            assert!(res.attributes.contains(&attr("streamed", "900")));
+
+            let vesting = vesting_rewards(deps.as_ref(), env.clone());
+            assert_eq!(vesting.vesting, Uint128::new(900));
+            assert_eq!(vesting.releasable, Uint128::zero());
+            assert_eq!(vesting.end_time, env.block.time.seconds() + 100);
+        }
+
+        // Half the window later, half of the stream is released
+        {
+            env.block.time = env.block.time.plus_seconds(50);
+            deps.querier
+                .bank
+                .update_balance(env.contract.address.clone(), coins(900, denom));
+
+            let vesting = vesting_rewards(deps.as_ref(), env.clone());
+            assert_eq!(vesting.vesting, Uint128::new(450));
+            assert_eq!(vesting.releasable, Uint128::new(450));
+
+            let res =
+                execute_dispatch_rewards(deps.as_mut(), env.clone(), hub_info.clone()).unwrap();
+            assert_eq!(stake_rewards_amount(&res), Some(450));
+
+            // the rest keeps vesting on its own schedule
+            let vesting = vesting_rewards(deps.as_ref(), env.clone());
+            assert_eq!(vesting.vesting, Uint128::new(450));
+            assert_eq!(vesting.end_time, env.block.time.seconds() + 50);
+        }
+
+        // New rewards merge with the rest, ending at the amount-weighted average of both end times
+        {
+            env.block.time = env.block.time.plus_seconds(25);
+            deps.querier
+                .bank
+                .update_balance(env.contract.address.clone(), coins(950, denom));
+
+            let res =
+                execute_dispatch_rewards(deps.as_mut(), env.clone(), hub_info.clone()).unwrap();
+            assert_eq!(stake_rewards_amount(&res), Some(225));
+            assert!(res.attributes.contains(&attr("streamed", "450")));
+
+            // 225 left over 25 seconds and 450 over 100 seconds end in 75 seconds
+            let vesting = vesting_rewards(deps.as_ref(), env.clone());
+            assert_eq!(vesting.vesting, Uint128::new(675));
+            assert_eq!(vesting.end_time, env.block.time.seconds() + 75);
+        }
+
+        // Disabling the stream releases everything held with the next dispatch
+        {
+            execute_update_config(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(0),
+                None,
+                None,
+            )
+            .unwrap();
+
+            deps.querier
+                .bank
+                .update_balance(env.contract.address.clone(), coins(775, denom));
+            let res =
+                execute_dispatch_rewards(deps.as_mut(), env.clone(), hub_info.clone()).unwrap();
+            assert_eq!(stake_rewards_amount(&res), Some(765));
+
+            let vesting = vesting_rewards(deps.as_ref(), env.clone());
+            assert_eq!(vesting.vesting, Uint128::zero());
+            assert_eq!(vesting.releasable, Uint128::zero());
+        }
+    }
+
+    #[test]
+    fn test_split_fee() {
+        let deps = mock_dependencies();
+        let treasury = deps.api.addr_make("treasury");
+        let insurance = deps.api.addr_make("insurance");
+        let partner = deps.api.addr_make("partner");
+
+        let recipients = vec![
+            (treasury.clone(), Decimal::percent(50)),
+            (insurance.clone(), Decimal::percent(25)),
+            (partner.clone(), Decimal::percent(25)),
+        ];
+
+        // The rounding remainder goes to the first recipient
+        assert_eq!(
+            split_fee(Uint128::new(103), &recipients).unwrap(),
+            vec![
+                (treasury.clone(), Uint128::new(53)),
+                (insurance.clone(), Uint128::new(25)),
+                (partner.clone(), Uint128::new(25)),
+            ]
+        );
+
+        assert_eq!(
+            split_fee(Uint128::new(1), &recipients).unwrap(),
+            vec![
+                (treasury, Uint128::new(1)),
+                (insurance, Uint128::zero()),
+                (partner, Uint128::zero()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrate_legacy_config() {
+        let mut deps = mock_dependencies();
+        let owner = deps.api.addr_make("owner");
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let fee_addr = deps.api.addr_make("fee_addr");
+
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+        LEGACY_CONFIG
+            .save(
+                deps.as_mut().storage,
+                &LegacyConfig {
+                    owner,
+                    hub_contract,
+                    reward_denom: "denom".to_string(),
+                    fee_addr: fee_addr.clone(),
+                    fee_rate: Decimal::percent(10),
+                },
+            )
+            .unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!(config.fee_recipients, vec![(fee_addr, Decimal::one())]);
+        assert_eq!(config.fee_rate, Decimal::percent(10));
     }
 
     #[test]
@@ -377,7 +1317,7 @@ mod tests {
             let msg = InstantiateMsg {
                 hub_contract: hub_contract.to_string(),
                 reward_denom: denom.to_string(),
-                fee_addr: fee_addr.to_string(),
+                fee_recipients: vec![(fee_addr.to_string(), Decimal::one())],
                 fee_rate,
             };
             instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
@@ -395,6 +1335,9 @@ mod tests {
                         HubQueryMsg::Parameters {} => SystemResult::Ok(ContractResult::Ok(
                             to_json_binary(&Parameters::default()).unwrap(),
                         )),
+                        HubQueryMsg::ExchangeRate {} => SystemResult::Ok(ContractResult::Ok(
+                            to_json_binary(&Decimal::one()).unwrap(),
+                        )),
                         _ => panic!("unexpected query"),
                     }
                 }
@@ -415,11 +1358,15 @@ mod tests {
                         to_address: fee_addr.to_string(),
                         amount: coins(100, denom)
                     })),
-                    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                        contract_addr: hub_contract.to_string(),
-                        msg: to_json_binary(&HubExecuteMsg::StakeRewards {}).unwrap(),
-                        funds: coins(900, denom)
-                    }))
+                    SubMsg::reply_on_success(
+                        CosmosMsg::Wasm(WasmMsg::Execute {
+                            contract_addr: hub_contract.to_string(),
+                            msg: to_json_binary(&HubExecuteMsg::StakeRewards {}).unwrap(),
+                            funds: coins(900, denom)
+                        }),
+                        DISPATCH_REPLY_ID
+                    )
+                    .with_payload(0u64.to_be_bytes().to_vec())
                 ]
             );
 
@@ -429,8 +1376,174 @@ mod tests {
                     attr("action", "claim_rewards"),
                     attr("reward_amt", "900denom".to_string()),
                     attr("fee", "100denom".to_string()),
+                    attr("fee_recipient", format!("{}:100denom", fee_addr)),
+                ]
+            );
+        }
+
+        // split the fee across several recipients
+        {
+            let treasury = deps.api.addr_make("treasury");
+            let insurance = deps.api.addr_make("insurance");
+
+            execute_update_config(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                None,
+                None,
+                Some(vec![
+                    (treasury.to_string(), Decimal::percent(70)),
+                    (insurance.to_string(), Decimal::percent(30)),
+                ]),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+            deps.querier
+                .bank
+                .update_balance(env.clone().contract.address, coins(1005, denom));
+
+            let info = message_info(&hub_contract, &[]);
+            let response = execute_dispatch_rewards(deps.as_mut(), env.clone(), info).unwrap();
+
+            // fee is 101: 70% floors to 70, 30% floors to 30, the remainder goes to the treasury
+            assert_eq!(
+                response.messages,
+                vec![
+                    SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                        to_address: treasury.to_string(),
+                        amount: coins(71, denom)
+                    })),
+                    SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                        to_address: insurance.to_string(),
+                        amount: coins(30, denom)
+                    })),
+                    SubMsg::reply_on_success(
+                        CosmosMsg::Wasm(WasmMsg::Execute {
+                            contract_addr: hub_contract.to_string(),
+                            msg: to_json_binary(&HubExecuteMsg::StakeRewards {}).unwrap(),
+                            funds: coins(904, denom)
+                        }),
+                        DISPATCH_REPLY_ID
+                    )
+                    .with_payload(1u64.to_be_bytes().to_vec())
                 ]
             );
+
+            assert_eq!(
+                response.attributes,
+                vec![
+                    attr("action", "claim_rewards"),
+                    attr("reward_amt", "904denom".to_string()),
+                    attr("fee", "101denom".to_string()),
+                    attr("fee_recipient", format!("{}:71denom", treasury)),
+                    attr("fee_recipient", format!("{}:30denom", insurance)),
+                ]
+            );
+
+            let stats: RewardStats =
+                from_json(query(deps.as_ref(), env.clone(), QueryMsg::RewardStats {}).unwrap())
+                    .unwrap();
+            assert_eq!(
+                stats,
+                RewardStats {
+                    total_restaked: Uint128::new(1804),
+                    total_fees: Uint128::new(201),
+                    fees_by_recipient: vec![
+                        (fee_addr.clone(), Uint128::new(100)),
+                        (treasury, Uint128::new(71)),
+                        (insurance, Uint128::new(30)),
+                    ],
+                    dispatch_count: 2,
+                }
+            );
+
+            let history = query_dispatch_history(deps.as_ref(), Some(1), None)
+                .unwrap()
+                .history;
+            assert_eq!(
+                history,
+                vec![DispatchRecord {
+                    id: 1,
+                    height: env.block.height,
+                    gross: Uint128::new(1005),
+                    fee: Uint128::new(101),
+                    net: Uint128::new(904),
+                    exchange_rate: Decimal::one(),
+                }]
+            );
+        }
+
+        // the history keeps only the most recent dispatches
+        {
+            let info = message_info(&hub_contract, &[]);
+            for _ in 0..MAX_DISPATCH_HISTORY {
+                execute_dispatch_rewards(deps.as_mut(), env.clone(), info.clone()).unwrap();
+            }
+
+            let history = query_dispatch_history(deps.as_ref(), None, None)
+                .unwrap()
+                .history;
+            assert_eq!(history.len() as u64, MAX_DISPATCH_HISTORY);
+            assert_eq!(history[0].id, 2);
+
+            let stats: RewardStats =
+                from_json(query(deps.as_ref(), env.clone(), QueryMsg::RewardStats {}).unwrap())
+                    .unwrap();
+            assert_eq!(stats.dispatch_count, MAX_DISPATCH_HISTORY + 2);
+        }
+
+        // the fee is restaked and minted as LST to the recipients
+        {
+            let treasury = deps.api.addr_make("treasury");
+            let insurance = deps.api.addr_make("insurance");
+
+            execute_update_config(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(true),
+                None,
+            )
+            .unwrap();
+
+            deps.querier
+                .bank
+                .update_balance(env.clone().contract.address, coins(1000, denom));
+
+            let info = message_info(&hub_contract, &[]);
+            let response = execute_dispatch_rewards(deps.as_mut(), env.clone(), info).unwrap();
+            assert_eq!(
+                response.messages,
+                vec![SubMsg::reply_on_success(
+                    CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: hub_contract.to_string(),
+                        msg: to_json_binary(&HubExecuteMsg::StakeRewardsWithFeeShares {
+                            fee_shares: vec![
+                                (treasury.to_string(), Uint128::new(70)),
+                                (insurance.to_string(), Uint128::new(30)),
+                            ]
+                        })
+                        .unwrap(),
+                        funds: coins(1000, denom)
+                    }),
+                    DISPATCH_REPLY_ID
+                )
+                .with_payload((MAX_DISPATCH_HISTORY + 2).to_be_bytes().to_vec())]
+            );
         }
 
         // hub contract paused error
@@ -448,6 +1561,7 @@ mod tests {
                                 staking_coin_denom: "denom".to_string(),
                                 unstaking_period: 100,
                                 paused: true,
+                                batched_delegation: false,
                             })
                             .unwrap(),
                         )),
@@ -475,6 +1589,9 @@ mod tests {
                         HubQueryMsg::Parameters {} => SystemResult::Ok(ContractResult::Ok(
                             to_json_binary(&Parameters::default()).unwrap(),
                         )),
+                        HubQueryMsg::ExchangeRate {} => SystemResult::Ok(ContractResult::Ok(
+                            to_json_binary(&Decimal::one()).unwrap(),
+                        )),
                         _ => panic!("unexpected query"),
                     }
                 }
@@ -6,3 +6,9 @@ pub mod testing;
 
 // we'll use a raw decimal value that represents 30%
 pub const MAX_FEE_RATE: Decimal = Decimal::raw(300000000000000000);
+
+// number of dispatches kept in the history, older ones are pruned
+pub const MAX_DISPATCH_HISTORY: u64 = 100;
+
+// longest window over which rewards can be streamed to the hub, 7 days
+pub const MAX_REWARD_STREAM_WINDOW: u64 = 7 * 24 * 60 * 60;
@@ -1,4 +1,98 @@
-use cw_storage_plus::Item;
-use lst_common::rewards_msg::Config;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Decimal, Env, Storage, Uint128};
+use cw_storage_plus::{Item, Map};
+use lst_common::{
+    rewards_msg::{Config, DenomPolicy, DispatchRecord, RewardStats, RewardStream},
+    types::LstResult,
+    ContractError,
+};
+
+use crate::MAX_DISPATCH_HISTORY;
 
 pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Policies for non-staking denoms, keyed by denom
+pub const DENOM_POLICIES: Map<&str, DenomPolicy> = Map::new("denom_policies");
+
+/// Rewards being released to the hub when streaming is enabled
+pub const REWARD_STREAM: Item<RewardStream> = Item::new("reward_stream");
+
+/// Cumulative totals over all dispatches
+pub const REWARD_STATS: Item<RewardStats> = Item::new("reward_stats");
+
+/// Most recent dispatches keyed by id, bounded to `MAX_DISPATCH_HISTORY` entries
+pub const DISPATCH_HISTORY: Map<u64, DispatchRecord> = Map::new("dispatch_history");
+
+/// Adds a dispatch to the totals and the history, pruning the oldest record once the
+/// history is full. Returns the id of the new record.
+pub fn record_dispatch(
+    storage: &mut dyn Storage,
+    env: &Env,
+    gross: Uint128,
+    fee_shares: &[(Addr, Uint128)],
+    net: Uint128,
+    exchange_rate: Decimal,
+) -> LstResult<u64> {
+    let mut stats = REWARD_STATS.may_load(storage)?.unwrap_or_default();
+    let fee = gross
+        .checked_sub(net)
+        .map_err(|e| ContractError::Overflow(e.to_string()))?;
+
+    stats.total_restaked = stats
+        .total_restaked
+        .checked_add(net)
+        .map_err(|e| ContractError::Overflow(e.to_string()))?;
+    stats.total_fees = stats
+        .total_fees
+        .checked_add(fee)
+        .map_err(|e| ContractError::Overflow(e.to_string()))?;
+
+    for (recipient, amount) in fee_shares {
+        match stats
+            .fees_by_recipient
+            .iter_mut()
+            .find(|(addr, _)| addr == recipient)
+        {
+            Some((_, total)) => {
+                *total = total
+                    .checked_add(*amount)
+                    .map_err(|e| ContractError::Overflow(e.to_string()))?
+            }
+            None => stats.fees_by_recipient.push((recipient.clone(), *amount)),
+        }
+    }
+
+    let id = stats.dispatch_count;
+    stats.dispatch_count += 1;
+    REWARD_STATS.save(storage, &stats)?;
+
+    DISPATCH_HISTORY.save(
+        storage,
+        id,
+        &DispatchRecord {
+            id,
+            height: env.block.height,
+            gross,
+            fee,
+            net,
+            exchange_rate,
+        },
+    )?;
+    if id >= MAX_DISPATCH_HISTORY {
+        DISPATCH_HISTORY.remove(storage, id - MAX_DISPATCH_HISTORY);
+    }
+
+    Ok(id)
+}
+
+/// Config layout from before the fee was split across several recipients
+#[cw_serde]
+pub struct LegacyConfig {
+    pub owner: Addr,
+    pub hub_contract: Addr,
+    pub reward_denom: String,
+    pub fee_addr: Addr,
+    pub fee_rate: Decimal,
+}
+
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
@@ -1,6 +1,6 @@
 #![cfg(not(target_arch = "wasm32"))]
 
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, query, reply};
 use cosmwasm_std::{Addr, Decimal, Env, Uint128};
 use cw_multi_test::{Contract, ContractWrapper};
 use lst_common::babylon::{EpochingMsg, EpochingQuery};
@@ -16,14 +16,16 @@ pub struct RewardDispatcherContract {
 
 impl TestingContract<InstantiateMsg, ExecuteMsg, QueryMsg> for RewardDispatcherContract {
     fn wrapper() -> Box<dyn Contract<EpochingMsg, EpochingQuery>> {
-        Box::new(ContractWrapper::new_with_empty(execute, instantiate, query))
+        Box::new(
+            ContractWrapper::new_with_empty(execute, instantiate, query).with_reply_empty(reply),
+        )
     }
 
     fn default_init(app: &mut BabylonApp, _env: &Env) -> InstantiateMsg {
         InstantiateMsg {
             hub_contract: Self::get_contract_addr(app, "StakingHubContract").to_string(),
             reward_denom: "BABY".to_string(),
-            fee_addr: app.api().addr_make("fee_addr").to_string(),
+            fee_recipients: vec![(app.api().addr_make("fee_addr").to_string(), Decimal::one())],
             fee_rate: Decimal::new(Uint128::new(10)),
         }
     }
@@ -13,6 +13,7 @@ use lst_common::{
 use crate::constants::{MAX_EPOCH_LENGTH, MAX_UNSTAKING_PERIOD};
 use crate::state::{CONFIG, PARAMETERS};
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_update_config(
     deps: DepsMut,
     _env: Env,
@@ -21,6 +22,7 @@ pub fn execute_update_config(
     lst_token: Option<String>,
     validator_registry: Option<String>,
     reward_dispatcher: Option<String>,
+    insurance_fund: Option<String>,
 ) -> LstResult<Response<ResponseType>> {
     is_authorized_sender(deps.as_ref(), info.sender)?;
 
@@ -58,6 +60,10 @@ pub fn execute_update_config(
         ));
     }
 
+    if let Some(fund) = insurance_fund {
+        config.insurance_fund = Some(to_checked_address(deps.as_ref(), &fund)?);
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new()
@@ -83,6 +89,12 @@ pub fn execute_update_config(
                     .validators_registry_contract
                     .map_or(String::from("None"), |a| a.to_string()),
             ),
+            attr(
+                "insurance_fund",
+                config
+                    .insurance_fund
+                    .map_or(String::from("None"), |a| a.to_string()),
+            ),
         ]))
 }
 
@@ -93,6 +105,7 @@ pub fn execute_update_params(
     pause: Option<bool>,
     epoch_length: Option<u64>,
     unstaking_period: Option<u64>,
+    batched_delegation: Option<bool>,
 ) -> LstResult<Response<ResponseType>> {
     is_authorized_sender(deps.as_ref(), info.sender)?;
 
@@ -133,6 +146,7 @@ pub fn execute_update_params(
     params.paused = pause.unwrap_or(params.paused);
     params.epoch_length = epoch_length.unwrap_or(params.epoch_length);
     params.unstaking_period = unstaking_period.unwrap_or(params.unstaking_period);
+    params.batched_delegation = batched_delegation.unwrap_or(params.batched_delegation);
 
     PARAMETERS.save(deps.storage, &params)?;
 
@@ -142,6 +156,7 @@ pub fn execute_update_params(
         attr("staking_coin_denom", params.staking_coin_denom.clone()),
         attr("epoch_length", params.epoch_length.to_string()),
         attr("unstaking_period", params.unstaking_period.to_string()),
+        attr("batched_delegation", params.batched_delegation.to_string()),
     ]))
 }
 
@@ -197,6 +212,7 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
             )
             .unwrap();
 
@@ -209,7 +225,8 @@ mod tests {
                     attr("owner", owner.to_string()),
                     attr("lst_token", "None"),
                     attr("reward_dispatcher", "None"),
-                    attr("validator_registry", "None")
+                    attr("validator_registry", "None"),
+                    attr("insurance_fund", "None")
                 ]
             );
         }
@@ -220,6 +237,7 @@ mod tests {
             let lst_token = deps.api.addr_make("lst_token");
             let validator_registry = deps.api.addr_make("validator_registry");
             let reward_dispatcher = deps.api.addr_make("reward_dispatcher");
+            let insurance_fund = deps.api.addr_make("insurance_fund");
 
             let response = execute_update_config(
                 deps.as_mut(),
@@ -229,6 +247,7 @@ mod tests {
                 Some(lst_token.to_string()),
                 Some(validator_registry.to_string()),
                 Some(reward_dispatcher.to_string()),
+                Some(insurance_fund.to_string()),
             )
             .unwrap();
 
@@ -248,7 +267,8 @@ mod tests {
                     attr("owner", new_owner.to_string()),
                     attr("lst_token", lst_token.to_string()),
                     attr("reward_dispatcher", reward_dispatcher.to_string()),
-                    attr("validator_registry", validator_registry.to_string())
+                    attr("validator_registry", validator_registry.to_string()),
+                    attr("insurance_fund", insurance_fund.to_string())
                 ]
             );
         }
@@ -258,9 +278,17 @@ mod tests {
             let wrong_owner = deps.api.addr_make("wrong_owner");
             let info = message_info(&wrong_owner, &[]);
 
-            let err =
-                execute_update_config(deps.as_mut(), env.clone(), info, None, None, None, None)
-                    .unwrap_err();
+            let err = execute_update_config(
+                deps.as_mut(),
+                env.clone(),
+                info,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap_err();
 
             assert_eq!(err, ContractError::Unauthorized {});
         }
@@ -279,6 +307,7 @@ mod tests {
                 Some(new_lst_token.to_string()),
                 None,
                 None,
+                None,
             )
             .unwrap_err();
 
@@ -309,9 +338,16 @@ mod tests {
 
         // update None
         {
-            let response =
-                execute_update_params(deps.as_mut(), env.clone(), info.clone(), None, None, None)
-                    .unwrap();
+            let response = execute_update_params(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
 
             assert_eq!(
                 response.attributes,
@@ -320,7 +356,8 @@ mod tests {
                     attr("paused", false.to_string()),
                     attr("staking_coin_denom", denom.to_string()),
                     attr("epoch_length", "7200"),
-                    attr("unstaking_period", "10000")
+                    attr("unstaking_period", "10000"),
+                    attr("batched_delegation", "false"),
                 ]
             );
         }
@@ -334,6 +371,7 @@ mod tests {
                 Some(true),
                 Some(1000),
                 Some(8000),
+                Some(true),
             )
             .unwrap();
 
@@ -344,7 +382,8 @@ mod tests {
                     attr("paused", true.to_string()),
                     attr("staking_coin_denom", denom.to_string()),
                     attr("epoch_length", "1000"),
-                    attr("unstaking_period", "8000")
+                    attr("unstaking_period", "8000"),
+                    attr("batched_delegation", "true"),
                 ]
             );
         }
@@ -354,8 +393,9 @@ mod tests {
             let wrong_owner = deps.api.addr_make("wrong_owner");
             let info = message_info(&wrong_owner, &[]);
 
-            let err = execute_update_params(deps.as_mut(), env.clone(), info, None, None, None)
-                .unwrap_err();
+            let err =
+                execute_update_params(deps.as_mut(), env.clone(), info, None, None, None, None)
+                    .unwrap_err();
 
             assert_eq!(err, ContractError::Unauthorized {});
         }
@@ -369,6 +409,7 @@ mod tests {
                 Some(true),
                 Some(604801),
                 Some(8000),
+                None,
             )
             .unwrap_err();
 
@@ -384,6 +425,7 @@ mod tests {
                 Some(true),
                 Some(100),
                 Some(2419201),
+                None,
             )
             .unwrap_err();
 
@@ -399,6 +441,7 @@ mod tests {
                 Some(true),
                 Some(1000),
                 Some(100),
+                None,
             )
             .unwrap_err();
 
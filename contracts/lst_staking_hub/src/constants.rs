@@ -10,16 +10,27 @@ pub const UNSTAKE_HISTORY_KEY: &str = "unstake_history";
 pub const MAX_EPOCH_LENGTH: u64 = 7 * 24 * 60 * 60;
 // Maximum allowed unstaking period in seconds (4 weeks)
 pub const MAX_UNSTAKING_PERIOD: u64 = 4 * 7 * 24 * 60 * 60;
+// Largest shortfall of a delegation on chain taken as rounding rather than slashing
+pub const SLASHING_DUST_TOLERANCE: u128 = 100;
+// Reply id of the top-up requested from the insurance fund
+pub const INSURANCE_TOP_UP_REPLY_ID: u64 = 1;
 
 //Event names
 pub const LST_EXCHANGE_RATE_UPDATED: &str = "LstExchangeRateUpdated";
 pub const TOTAL_STAKED_AMOUNT_UPDATED: &str = "TotalStakedAmountUpdated";
+pub const HIGH_WATER_MARK_UPDATED: &str = "HighWaterMarkUpdated";
 pub const OLD_RATE: &str = "old_rate";
 pub const NEW_RATE: &str = "new_rate";
 pub const OLD_AMOUNT: &str = "old_amount";
 pub const NEW_AMOUNT: &str = "new_amount";
+pub const VALIDATOR_SLASHED: &str = "ValidatorSlashed";
+pub const VALIDATOR: &str = "validator";
+pub const SLASHED_AMOUNT: &str = "slashed_amount";
 
 pub const PENDING_DELEGATION_KEY: &str = "pending_delegation";
+pub const DELEGATION_LEDGER_KEY: &str = "delegation_ledger";
+pub const SLASHING_HISTORY_KEY: &str = "slashing_history";
+pub const SLASHING_EVENT_COUNT_KEY: &str = "slashing_event_count";
 
 // being generous on block time, to avoid staking epoch length being too short
 pub const AVERAGE_BLOCK_TIME: u64 = 20; // seconds
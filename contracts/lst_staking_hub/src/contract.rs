@@ -1,13 +1,14 @@
 use cosmos_sdk_proto::cosmos::staking::v1beta1::MsgBeginRedelegate;
 use cosmwasm_std::{
-    attr, entry_point, from_json, to_json_binary, Binary, CosmosMsg, Decimal, Deps, DepsMut,
-    DistributionMsg, Env, Event, MessageInfo, QueryRequest, Response, Uint128, WasmMsg, WasmQuery,
+    attr, entry_point, from_json, to_json_binary, Binary, CosmosMsg, Decimal, Delegation, Deps,
+    DepsMut, DistributionMsg, Env, Event, MessageInfo, QueryRequest, Reply, Response, StdError,
+    SubMsg, Uint128, WasmMsg, WasmQuery,
 };
 
 use cw2::set_contract_version;
 
 use cw20::Cw20ReceiveMsg;
-use lst_common::hub::PendingDelegation;
+use lst_common::hub::{PendingDelegation, ValidatorDelegation};
 use lst_common::types::{LstResult, ProtoCoin, ResponseType, StdCoin};
 use lst_common::{
     babylon_msg::{CosmosAny, MsgWrappedBeginRedelegate},
@@ -15,30 +16,37 @@ use lst_common::{
     hub::{
         Config, CurrentBatch, Cw20HookMsg, ExecuteMsg, InstantiateMsg, Parameters, QueryMsg, State,
     },
-    ContractError, MigrateMsg,
+    to_checked_address, ContractError, MigrateMsg,
 };
 
 use crate::config::{execute_update_config, execute_update_params};
 use crate::constants::{
-    AVERAGE_BLOCK_TIME, LST_EXCHANGE_RATE_UPDATED, MAX_EPOCH_LENGTH, MAX_UNSTAKING_PERIOD,
-    NEW_AMOUNT, NEW_RATE, OLD_AMOUNT, OLD_RATE, TOTAL_STAKED_AMOUNT_UPDATED,
+    AVERAGE_BLOCK_TIME, INSURANCE_TOP_UP_REPLY_ID, LST_EXCHANGE_RATE_UPDATED, MAX_EPOCH_LENGTH,
+    MAX_UNSTAKING_PERIOD, NEW_AMOUNT, NEW_RATE, OLD_AMOUNT, OLD_RATE, SLASHED_AMOUNT,
+    TOTAL_STAKED_AMOUNT_UPDATED, VALIDATOR, VALIDATOR_SLASHED,
 };
 use crate::query::{
-    query_config, query_current_batch, query_parameters, query_pending_delegation, query_state,
+    query_config, query_current_batch, query_delegations, query_high_water_mark, query_parameters,
+    query_pending_delegation, query_pending_rewards, query_slashing_history, query_state,
     query_unstake_requests, query_unstake_requests_limit, query_unstake_requests_limitation,
     query_withdrawable_unstaked,
 };
-use crate::stake::execute_stake;
+use crate::stake::{execute_delegate_pending, execute_stake};
 use crate::state::{
-    get_pending_delegation_amount, update_state, StakeType, UnstakeType, CONFIG, CURRENT_BATCH,
-    PARAMETERS, PENDING_DELEGATION, STATE,
+    get_pending_delegation_amount, read_delegation_ledger, reconcile_delegation_ledger,
+    record_delegation, record_slashing, record_undelegation, update_state, StakeType, UnstakeType,
+    CONFIG, CURRENT_BATCH, DELEGATION_LEDGER, PARAMETERS, PENDING_DELEGATION, STATE,
 };
 use crate::unstake::{
     execute_process_undelegations, execute_process_withdraw_requests, execute_unstake,
     execute_withdraw_unstaked, execute_withdraw_unstaked_for_batches,
 };
 use cw20_base::{msg::QueryMsg as Cw20QueryMsg, state::TokenInfo};
+use lst_common::insurance_msg::{
+    ExecuteMsg::RequestTopUp, PayoutResponse, QueryMsg::Payout as InsurancePayout,
+};
 use lst_common::rewards_msg::ExecuteMsg::DispatchRewards;
+use lst_common::validator::ExecuteMsg::ReportSlashing;
 
 const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -77,6 +85,7 @@ pub fn instantiate(
         lst_token: None,
         validators_registry_contract: None,
         reward_dispatcher_contract: None,
+        insurance_fund: None,
     };
     CONFIG.save(deps.storage, &data)?;
 
@@ -88,6 +97,7 @@ pub fn instantiate(
         unclaimed_unstaked_balance: Uint128::zero(),
         last_unbonded_time: env.block.time.seconds(),
         last_processed_batch: 0u64,
+        high_water_mark: Decimal::one(),
     };
     STATE.save(deps.storage, &state)?;
     let events: Vec<Event> = vec![
@@ -105,6 +115,7 @@ pub fn instantiate(
         staking_coin_denom: msg.staking_coin_denom,
         paused: false,
         unstaking_period: msg.unstaking_period,
+        batched_delegation: false,
     };
     PARAMETERS.save(deps.storage, &params)?;
 
@@ -120,6 +131,8 @@ pub fn instantiate(
         staking_epoch_start_block_height: msg.staking_epoch_start_block_height,
         pending_staking_amount: Uint128::zero(),
         pending_unstaking_amount: Uint128::zero(),
+        batched_staking_amount: Uint128::zero(),
+        last_batch_epoch_start_block_height: None,
         staking_epoch_length_blocks: msg.staking_epoch_length_blocks,
     };
     PENDING_DELEGATION.save(deps.storage, &pending_delegation)?;
@@ -138,9 +151,18 @@ pub fn execute(
         pause,
         epoch_length,
         unstaking_period,
+        batched_delegation,
     } = msg
     {
-        return execute_update_params(deps, env, info, pause, epoch_length, unstaking_period);
+        return execute_update_params(
+            deps,
+            env,
+            info,
+            pause,
+            epoch_length,
+            unstaking_period,
+            batched_delegation,
+        );
     }
 
     let params: Parameters = PARAMETERS.load(deps.storage)?;
@@ -152,6 +174,21 @@ pub fn execute(
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::Stake {} => execute_stake(deps, env, info, StakeType::LSTMint),
         ExecuteMsg::StakeRewards {} => execute_stake(deps, env, info, StakeType::StakeRewards),
+        ExecuteMsg::InsuranceTopUp {} => execute_stake(deps, env, info, StakeType::InsuranceTopUp),
+        ExecuteMsg::StakeRewardsWithFeeShares { fee_shares } => {
+            let fee_shares = fee_shares
+                .into_iter()
+                .map(|(recipient, amount)| {
+                    Ok((to_checked_address(deps.as_ref(), &recipient)?, amount))
+                })
+                .collect::<LstResult<Vec<_>>>()?;
+            execute_stake(
+                deps,
+                env,
+                info,
+                StakeType::StakeRewardsWithFeeShares(fee_shares),
+            )
+        }
         ExecuteMsg::Unstake { amount } => execute_unstake(
             deps,
             env,
@@ -168,12 +205,22 @@ pub fn execute(
             pause,
             epoch_length,
             unstaking_period,
-        } => execute_update_params(deps, env, info, pause, epoch_length, unstaking_period),
+            batched_delegation,
+        } => execute_update_params(
+            deps,
+            env,
+            info,
+            pause,
+            epoch_length,
+            unstaking_period,
+            batched_delegation,
+        ),
         ExecuteMsg::UpdateConfig {
             owner,
             lst_token,
             validator_registry,
             reward_dispatcher,
+            insurance_fund,
         } => execute_update_config(
             deps,
             env,
@@ -182,6 +229,7 @@ pub fn execute(
             lst_token,
             validator_registry,
             reward_dispatcher,
+            insurance_fund,
         ),
         ExecuteMsg::RedelegateProxy {
             src_validator,
@@ -190,6 +238,7 @@ pub fn execute(
         ExecuteMsg::UpdateGlobalIndex {} => execute_update_global_index(deps, env),
         ExecuteMsg::ProcessUndelegations {} => execute_process_undelegations(deps, env),
         ExecuteMsg::ProcessWithdrawRequests {} => execute_process_withdraw_requests(deps, env),
+        ExecuteMsg::DelegatePending {} => execute_delegate_pending(deps, env),
     }
 }
 
@@ -223,6 +272,16 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> LstResult<Binary> {
         QueryMsg::PendingDelegation {} => {
             Ok(to_json_binary(&query_pending_delegation(deps, &env)?)?)
         }
+        QueryMsg::Delegations {} => Ok(to_json_binary(&query_delegations(deps, &env)?)?),
+        QueryMsg::PendingRewards {} => Ok(to_json_binary(&query_pending_rewards(deps, &env)?)?),
+        QueryMsg::HighWaterMark {} => Ok(to_json_binary(&query_high_water_mark(deps, &env)?)?),
+        QueryMsg::SlashingHistory {
+            validator,
+            start_from,
+            limit,
+        } => Ok(to_json_binary(&query_slashing_history(
+            deps, validator, start_from, limit,
+        )?)?),
     }
 }
 
@@ -230,11 +289,14 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> LstResult<Binary> {
 pub fn execute_slashing(mut deps: DepsMut, env: Env) -> LstResult<Response<ResponseType>> {
     let mut state = STATE.load(deps.storage)?;
     // call slashing
-    let (events, state) = check_slashing(&mut deps, &env, &mut state)?;
-    Ok(Response::new().add_events(events).add_attributes(vec![
-        attr("action", "check_slashing"),
-        attr("new_lst_exchange_rate", state.lst_exchange_rate.to_string()),
-    ]))
+    let (events, messages, state) = check_slashing(&mut deps, &env, &mut state)?;
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_events(events)
+        .add_attributes(vec![
+            attr("action", "check_slashing"),
+            attr("new_lst_exchange_rate", state.lst_exchange_rate.to_string()),
+        ]))
 }
 
 // Check if slashing has happened and return the slashed amount
@@ -242,13 +304,125 @@ pub fn check_slashing<'a>(
     deps: &mut DepsMut,
     env: &Env,
     state: &'a mut State,
-) -> LstResult<(Vec<Event>, &'a State)> {
+) -> LstResult<(Vec<Event>, Vec<SubMsg>, &'a State)> {
     let old_state = state.clone();
 
-    query_actual_state(deps.as_ref(), env, state)?;
+    let delegations = deps
+        .querier
+        .query_all_delegations(env.contract.address.clone())?;
+    let staking_coin_denom = PARAMETERS.load(deps.storage)?.staking_coin_denom;
+    let slashed = reconcile_delegation_ledger(
+        deps.storage,
+        env,
+        &delegations
+            .iter()
+            .filter(|delegation| delegation.amount.denom == staking_coin_denom)
+            .map(|delegation| (delegation.validator.clone(), delegation.amount.amount))
+            .collect(),
+    )?;
+    apply_actual_delegations(deps.as_ref(), env, state, &delegations)?;
+
+    // every slashed validator is recorded and reported to the registry, which soft-removes it
+    let config = CONFIG.load(deps.storage)?;
+    let mut events: Vec<Event> = vec![];
+    let mut messages: Vec<SubMsg> = vec![];
+    let mut total_slashed = Uint128::zero();
+    for (validator, amount) in slashed {
+        total_slashed += amount;
+        record_slashing(deps.storage, env, validator.clone(), amount)?;
+        events.push(
+            Event::new(VALIDATOR_SLASHED)
+                .add_attribute(VALIDATOR, validator.clone())
+                .add_attribute(SLASHED_AMOUNT, amount),
+        );
+        if let Some(registry) = &config.validators_registry_contract {
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: registry.to_string(),
+                msg: to_json_binary(&ReportSlashing {
+                    validator,
+                    amount,
+                    height: env.block.height,
+                })?,
+                funds: vec![],
+            }));
+        }
+    }
+
+    // The insurance fund covers part of the loss. The covered amount is credited before anyone
+    // stakes at the slashed rate, the fund pays it through InsuranceTopUp and a failing claim is
+    // caught in `reply`, which takes the credit back
+    if let Some(insurance_fund) = &config.insurance_fund {
+        if !total_slashed.is_zero() {
+            let payout: Option<PayoutResponse> = deps
+                .querier
+                .query_wasm_smart(
+                    insurance_fund,
+                    &InsurancePayout {
+                        loss: total_slashed,
+                    },
+                )
+                .ok();
+            if let Some(PayoutResponse { payout }) = payout {
+                state.total_staked_amount += payout;
+                let lst_total_issued = query_total_lst_token_issued(deps.as_ref())?;
+                let current_batch = CURRENT_BATCH.load(deps.storage)?;
+                state.update_lst_exchange_rate(
+                    lst_total_issued,
+                    current_batch.requested_lst_token_amount,
+                );
+
+                messages.push(
+                    SubMsg::reply_on_error(
+                        WasmMsg::Execute {
+                            contract_addr: insurance_fund.to_string(),
+                            msg: to_json_binary(&RequestTopUp {
+                                loss: total_slashed,
+                            })?,
+                            funds: vec![],
+                        },
+                        INSURANCE_TOP_UP_REPLY_ID,
+                    )
+                    .with_payload(payout.u128().to_be_bytes().to_vec()),
+                );
+            }
+        }
+    }
+
+    events.extend(update_state(deps.storage, old_state, state.clone())?);
+    Ok((events, messages, state))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> LstResult<Response<ResponseType>> {
+    match msg.id {
+        // the top-up credited by the slashing check was not paid
+        INSURANCE_TOP_UP_REPLY_ID => {
+            let payout = Uint128::new(u128::from_be_bytes(
+                msg.payload
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| StdError::generic_err("Invalid top-up payout"))?,
+            ));
+
+            let mut state = STATE.load(deps.storage)?;
+            let old_state = state.clone();
+            state.total_staked_amount = state.total_staked_amount.saturating_sub(payout);
+            let lst_total_issued = query_total_lst_token_issued(deps.as_ref())?;
+            let current_batch = CURRENT_BATCH.load(deps.storage)?;
+            state.update_lst_exchange_rate(
+                lst_total_issued,
+                current_batch.requested_lst_token_amount,
+            );
+            let events = update_state(deps.storage, old_state, state)?;
 
-    let events = update_state(deps.storage, old_state, state.clone())?;
-    Ok((events, state))
+            Ok(Response::new().add_events(events).add_attributes(vec![
+                attr("action", "insurance_top_up_failed"),
+                attr("payout", payout),
+                attr("error", msg.result.into_result().err().unwrap_or_default()),
+            ]))
+        }
+        id => Err(StdError::generic_err(format!("Unknown reply id: {}", id)).into()),
+    }
 }
 
 pub(crate) fn query_total_lst_token_issued(deps: Deps) -> LstResult<Uint128> {
@@ -269,6 +443,16 @@ pub fn query_actual_state<'a>(deps: Deps, env: &Env, state: &'a mut State) -> Ls
     let delegations = deps
         .querier
         .query_all_delegations(env.contract.address.clone())?;
+    apply_actual_delegations(deps, env, state, &delegations)
+}
+
+// The chain delegations are only used to detect slashing, which lowers the total staked amount
+fn apply_actual_delegations<'a>(
+    deps: Deps,
+    env: &Env,
+    state: &'a mut State,
+    delegations: &[Delegation],
+) -> LstResult<&'a State> {
     if delegations.is_empty() {
         return Ok(state);
     }
@@ -279,7 +463,7 @@ pub fn query_actual_state<'a>(deps: Deps, env: &Env, state: &'a mut State) -> Ls
 
     // check the actual bonded amount
     let mut actual_total_staked = Uint128::zero();
-    for delegation in &delegations {
+    for delegation in delegations {
         if delegation.amount.denom == staking_coin_denom {
             actual_total_staked += delegation.amount.amount;
         }
@@ -288,17 +472,23 @@ pub fn query_actual_state<'a>(deps: Deps, env: &Env, state: &'a mut State) -> Ls
     // get the pending delegation amount
     let (pending_staked_amount, pending_unstaked_amount) =
         get_pending_delegation_amount(deps, env)?;
+    // stake held by the hub in batched delegation mode is not delegated yet
+    let batched_staking_amount = PENDING_DELEGATION
+        .load(deps.storage)?
+        .batched_staking_amount;
 
     // Check the amount that contract thinks is staked, pending amount should not be included as we don't get that in the delegation query
     let state_total_staked =
-        state.total_staked_amount - pending_staked_amount + pending_unstaked_amount;
+        state.total_staked_amount - pending_staked_amount - batched_staking_amount
+            + pending_unstaked_amount;
     if state_total_staked.is_zero() {
         return Ok(state);
     }
 
     if state_total_staked.u128() > actual_total_staked.u128() {
         state.total_staked_amount =
-            actual_total_staked + pending_staked_amount - pending_unstaked_amount;
+            actual_total_staked + pending_staked_amount + batched_staking_amount
+                - pending_unstaked_amount;
     }
 
     // Need total issued for updating the exchange rate
@@ -328,18 +518,18 @@ pub fn execute_redelegate_proxy(
         return Err(ContractError::Unauthorized {});
     }
 
-    let messages: Vec<CosmosMsg<ResponseType>> = redelegations
-        .into_iter()
-        .map(|(dst_validator, amount)| {
-            prepare_wrapped_begin_redelegate_msg(
-                amount.denom,
-                amount.amount.to_string(),
-                env.contract.address.to_string(),
-                src_validator.clone(),
-                dst_validator,
-            )
-        })
-        .collect();
+    let mut messages: Vec<CosmosMsg<ResponseType>> = vec![];
+    for (dst_validator, amount) in redelegations {
+        record_undelegation(deps.storage, &env, &src_validator, amount.amount)?;
+        record_delegation(deps.storage, &env, &dst_validator, amount.amount)?;
+        messages.push(prepare_wrapped_begin_redelegate_msg(
+            amount.denom,
+            amount.amount.to_string(),
+            env.contract.address.to_string(),
+            src_validator.clone(),
+            dst_validator,
+        ));
+    }
 
     let res = Response::new().add_messages(messages);
     Ok(res)
@@ -384,7 +574,7 @@ pub fn execute_update_global_index(deps: DepsMut, env: Env) -> LstResult<Respons
         .ok_or(HubError::RewardDispatcherNotSet)?;
 
     // Send withdraw message
-    let mut withdraw_msgs = withdraw_all_rewards(&deps, env.contract.address.to_string())?;
+    let mut withdraw_msgs = withdraw_all_rewards(&deps, &env)?;
     messages.append(&mut withdraw_msgs);
 
     messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -405,22 +595,17 @@ pub fn execute_update_global_index(deps: DepsMut, env: Env) -> LstResult<Respons
     Ok(res)
 }
 
-fn withdraw_all_rewards(
-    deps: &DepsMut,
-    delegator: String,
-) -> LstResult<Vec<CosmosMsg<ResponseType>>> {
+// Rewards are only accrued by the bonded delegations, the ones queued for the current epoch are skipped
+fn withdraw_all_rewards(deps: &DepsMut, env: &Env) -> LstResult<Vec<CosmosMsg<ResponseType>>> {
     let mut messages: Vec<CosmosMsg<ResponseType>> = vec![];
 
-    let delegations = deps.querier.query_all_delegations(delegator)?;
-
-    if !delegations.is_empty() {
-        for delegation in delegations {
-            let msg: CosmosMsg<ResponseType> =
-                CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward {
-                    validator: delegation.validator,
-                });
-            messages.push(msg);
+    for (validator, delegation) in read_delegation_ledger(deps.storage, env)? {
+        if delegation.bonded.is_zero() {
+            continue;
         }
+        let msg: CosmosMsg<ResponseType> =
+            CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward { validator });
+        messages.push(msg);
     }
 
     Ok(messages)
@@ -429,8 +614,42 @@ fn withdraw_all_rewards(
 /// This can only be called by the contract ADMIN, enforced by `wasmd` separate from cosmwasm.
 /// See https://github.com/CosmWasm/cosmwasm/issues/926#issuecomment-851259818
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     cw2::ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // Deployments from before the performance fee have no high-water mark, it starts from the current rate
+    let mut state = STATE.load(deps.storage)?;
+    if state.high_water_mark.is_zero() {
+        state.high_water_mark = state.lst_exchange_rate.max(Decimal::one());
+        STATE.save(deps.storage, &state)?;
+    }
+
+    // Seed the delegation ledger from the bonded delegations on chain. Delegations queued for the current epoch are not
+    // visible on chain yet, the slashing checks add them to the ledger once they are bonded
+    if DELEGATION_LEDGER.is_empty(deps.storage) {
+        let staking_coin_denom = PARAMETERS.load(deps.storage)?.staking_coin_denom;
+        let current_epoch_start = PENDING_DELEGATION
+            .load(deps.storage)?
+            .current_epoch_start(env.block.height);
+        for delegation in deps
+            .querier
+            .query_all_delegations(env.contract.address.clone())?
+        {
+            if delegation.amount.denom != staking_coin_denom {
+                continue;
+            }
+            DELEGATION_LEDGER.save(
+                deps.storage,
+                &delegation.validator,
+                &ValidatorDelegation {
+                    bonded: delegation.amount.amount,
+                    epoch_start_block_height: current_epoch_start,
+                    ..Default::default()
+                },
+            )?;
+        }
+    }
+
     Ok(Response::default())
 }
 
@@ -462,7 +681,8 @@ mod tests {
             LST_EXCHANGE_RATE_UPDATED, NEW_AMOUNT, NEW_RATE, OLD_AMOUNT, OLD_RATE,
             TOTAL_STAKED_AMOUNT_UPDATED,
         },
-        contract::{execute_redelegate_proxy, execute_update_global_index, instantiate},
+        contract::{execute_redelegate_proxy, execute_update_global_index, instantiate, migrate},
+        state::{record_delegation, STATE},
     };
     use cosmos_sdk_proto::{cosmos::staking::v1beta1::MsgBeginRedelegate, traits::MessageExt};
     use cosmwasm_std::{
@@ -479,6 +699,7 @@ mod tests {
         hub::{Cw20HookMsg, InstantiateMsg},
         rewards_msg::ExecuteMsg::DispatchRewards,
         types::ProtoCoin,
+        MigrateMsg,
     };
 
     use super::{execute_slashing, receive_cw20};
@@ -579,6 +800,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_migrate() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let owner = deps.api.addr_make("owner");
+        let msg = InstantiateMsg {
+            epoch_length: 7200,
+            staking_coin_denom: "denom".to_string(),
+            unstaking_period: 10000,
+            staking_epoch_start_block_height: 100,
+            staking_epoch_length_blocks: 360,
+        };
+        instantiate(deps.as_mut(), env.clone(), message_info(&owner, &[]), msg).unwrap();
+
+        // the high-water mark of an older deployment starts from the current rate, and never below one
+        for (rate, high_water_mark) in [
+            (Decimal::percent(120), Decimal::percent(120)),
+            (Decimal::percent(90), Decimal::one()),
+        ] {
+            let mut state = STATE.load(&deps.storage).unwrap();
+            state.lst_exchange_rate = rate;
+            state.high_water_mark = Decimal::zero();
+            STATE.save(deps.as_mut().storage, &state).unwrap();
+
+            migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+            assert_eq!(
+                STATE.load(&deps.storage).unwrap().high_water_mark,
+                high_water_mark
+            );
+        }
+
+        // a high-water mark already set is kept
+        let mut state = STATE.load(&deps.storage).unwrap();
+        state.lst_exchange_rate = Decimal::percent(150);
+        STATE.save(deps.as_mut().storage, &state).unwrap();
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            STATE.load(&deps.storage).unwrap().high_water_mark,
+            Decimal::one()
+        );
+    }
+
     #[test]
     fn test_execute_slashing() {
         let mut deps = mock_dependencies();
@@ -740,6 +1004,7 @@ mod tests {
             None,
             Some(validator_registry.to_string()),
             None,
+            None,
         )
         .unwrap();
 
@@ -842,6 +1107,7 @@ mod tests {
                 Some(lst_token.to_string()),
                 None,
                 None,
+                None,
             )
             .unwrap();
 
@@ -897,6 +1163,7 @@ mod tests {
             None,
             None,
             Some(reward_dispatcher.to_string()),
+            None,
         )
         .unwrap();
 
@@ -920,27 +1187,27 @@ mod tests {
             );
         }
 
-        // update global index successfully with delegations
+        // update global index successfully with delegations, the ones queued for the current epoch are skipped
         {
             let validator1 = deps.api.addr_make("validator1");
-            let validator1_info = Validator::create(
-                validator1.to_string(),
-                Decimal::percent(5),
-                Decimal::percent(10),
-                Decimal::percent(1),
-            );
-
-            let validator1_full_delegation = FullDelegation::create(
-                env.contract.address.clone(),
-                validator1.to_string(),
-                coin(100, denom),
-                coin(120, denom),
-                coins(1000, denom),
-            );
+            let validator2 = deps.api.addr_make("validator2");
+            record_delegation(
+                deps.as_mut().storage,
+                &env,
+                validator1.as_str(),
+                Uint128::new(100),
+            )
+            .unwrap();
 
-            deps.querier
-                .staking
-                .update(denom, &[validator1_info], &[validator1_full_delegation]);
+            let mut env = env.clone();
+            env.block.height += 360;
+            record_delegation(
+                deps.as_mut().storage,
+                &env,
+                validator2.as_str(),
+                Uint128::new(100),
+            )
+            .unwrap();
 
             let response = execute_update_global_index(deps.as_mut(), env.clone()).unwrap();
 
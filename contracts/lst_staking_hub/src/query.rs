@@ -1,10 +1,16 @@
 use cosmwasm_std::{Addr, Deps, Env, Storage, Uint128};
 use cw_storage_plus::Bound;
 use lst_common::{
+    errors::HubError,
     hub::{
-        AllHistoryResponse, Config, ConfigResponse, CurrentBatch, Parameters, PendingDelegation,
-        State, UnstakeHistory, UnstakeRequestsResponses, UserUnstakeRequestsResponse,
-        WithdrawableUnstakedResponse,
+        AllHistoryResponse, Config, ConfigResponse, CurrentBatch, HighWaterMarkResponse,
+        Parameters, PendingDelegation, PendingRewardsResponse, SlashingHistoryResponse, State,
+        UnstakeHistory, UnstakeRequestsResponses, UserUnstakeRequestsResponse,
+        ValidatorDelegationResponse, WithdrawableUnstakedResponse,
+    },
+    rewards_msg::{
+        compute_fee, Config as DispatcherConfig, QueryMsg as DispatcherQueryMsg,
+        VestingRewardsResponse,
     },
     to_checked_address,
     types::LstResult,
@@ -12,11 +18,11 @@ use lst_common::{
 };
 
 use crate::{
-    contract::query_actual_state,
-    math::decimal_multiplication,
+    contract::{query_actual_state, query_total_lst_token_issued},
+    math::{decimal_division, decimal_multiplication},
     state::{
-        read_unstake_history, CONFIG, CURRENT_BATCH, PARAMETERS, PENDING_DELEGATION, STATE,
-        UNSTAKE_HISTORY, UNSTAKE_WAIT_LIST,
+        read_delegation_ledger, read_unstake_history, CONFIG, CURRENT_BATCH, PARAMETERS,
+        PENDING_DELEGATION, SLASHING_HISTORY, STATE, UNSTAKE_HISTORY, UNSTAKE_WAIT_LIST,
     },
 };
 
@@ -26,6 +32,7 @@ pub fn query_config(deps: Deps) -> LstResult<ConfigResponse> {
         reward_dispatcher_contract,
         validators_registry_contract,
         lst_token,
+        insurance_fund,
     } = CONFIG.load(deps.storage)?;
 
     Ok(ConfigResponse {
@@ -33,6 +40,7 @@ pub fn query_config(deps: Deps) -> LstResult<ConfigResponse> {
         reward_dispatcher_contract: reward_dispatcher_contract.map(|addr| addr.to_string()),
         validators_registry_contract: validators_registry_contract.map(|addr| addr.to_string()),
         lst_token: lst_token.map(|addr| addr.to_string()),
+        insurance_fund: insurance_fund.map(|addr| addr.to_string()),
     })
 }
 
@@ -51,6 +59,18 @@ pub fn query_pending_delegation(deps: Deps, env: &Env) -> LstResult<PendingDeleg
     Ok(pending_delegation)
 }
 
+pub fn query_delegations(deps: Deps, env: &Env) -> LstResult<Vec<ValidatorDelegationResponse>> {
+    Ok(read_delegation_ledger(deps.storage, env)?
+        .into_iter()
+        .filter(|(_, delegation)| !delegation.delegated().is_zero() || !delegation.bonded.is_zero())
+        .map(|(validator, delegation)| ValidatorDelegationResponse {
+            validator,
+            delegated: delegation.delegated(),
+            bonded: delegation.bonded,
+        })
+        .collect())
+}
+
 pub fn query_state(deps: Deps, env: &Env) -> LstResult<State> {
     let mut state = STATE.load(deps.storage)?;
     query_actual_state(deps, env, &mut state)?;
@@ -119,6 +139,7 @@ pub fn query_unstake_requests_limitation(
                 lst_applied_exchange_rate: request.lst_applied_exchange_rate,
                 lst_withdraw_rate: request.lst_withdraw_rate,
                 released: request.released,
+                netted_amount: request.netted_amount,
             })
             .collect(),
     })
@@ -142,7 +163,7 @@ fn query_get_finished_amount(
         .into_iter()
         .fold(Uint128::zero(), |acc, (batch_id, lst_amount)| {
             if let Ok(history) = read_unstake_history(storage, batch_id) {
-                if history.time < unstake_cutoff_time {
+                if history.released || history.time < unstake_cutoff_time {
                     return acc
                         + decimal_multiplication(lst_amount, history.lst_applied_exchange_rate);
                 }
@@ -187,6 +208,136 @@ pub fn get_unstake_requests(
         .collect()
 }
 
+pub fn query_slashing_history(
+    deps: Deps,
+    validator: Option<String>,
+    start_from: Option<u64>,
+    limit: Option<u32>,
+) -> LstResult<SlashingHistoryResponse> {
+    let history = SLASHING_HISTORY
+        .range(
+            deps.storage,
+            start_from.map(Bound::inclusive),
+            None,
+            cosmwasm_std::Order::Ascending,
+        )
+        .filter(|item| match (item, &validator) {
+            (Ok((_, event)), Some(validator)) => event.validator == *validator,
+            _ => true,
+        })
+        .take(limit.unwrap_or(u32::MAX) as usize)
+        .map(|item| Ok(item?.1))
+        .collect::<LstResult<Vec<_>>>()?;
+
+    Ok(SlashingHistoryResponse { history })
+}
+
+// Rewards accrued by the bonded delegations, which the next UpdateGlobalIndex withdraws, previewed
+// together with the rewards the reward dispatcher already holds the way the dispatch handles them
+pub fn query_pending_rewards(deps: Deps, env: &Env) -> LstResult<PendingRewardsResponse> {
+    let reward_dispatcher = CONFIG
+        .load(deps.storage)?
+        .reward_dispatcher_contract
+        .ok_or(HubError::RewardDispatcherNotSet)?;
+    let staking_coin_denom = PARAMETERS.load(deps.storage)?.staking_coin_denom;
+
+    let mut rewards = vec![];
+    let mut total = Uint128::zero();
+    for (validator, delegation) in read_delegation_ledger(deps.storage, env)? {
+        if delegation.bonded.is_zero() {
+            continue;
+        }
+
+        let accrued: Uint128 = deps
+            .querier
+            .query_delegation(&env.contract.address, &validator)?
+            .map(|full_delegation| {
+                full_delegation
+                    .accumulated_rewards
+                    .iter()
+                    .filter(|coin| coin.denom == staking_coin_denom)
+                    .map(|coin| coin.amount)
+                    .sum()
+            })
+            .unwrap_or_default();
+
+        total += accrued;
+        rewards.push((validator, accrued));
+    }
+
+    let dispatcher_config: DispatcherConfig = deps
+        .querier
+        .query_wasm_smart(&reward_dispatcher, &DispatcherQueryMsg::Config {})?;
+    let stream: VestingRewardsResponse = deps
+        .querier
+        .query_wasm_smart(&reward_dispatcher, &DispatcherQueryMsg::VestingRewards {})?;
+    let streamed = stream.vesting + stream.releasable;
+
+    // the balance of the dispatcher includes the streamed rewards, the rest is dispatched with the accrued rewards
+    let held = deps
+        .querier
+        .query_balance(&reward_dispatcher, &staking_coin_denom)?
+        .amount
+        .saturating_sub(streamed);
+    let gross = total + held;
+
+    let chargeable = if dispatcher_config.performance_fee {
+        let recovery_amount = query_high_water_mark(deps, env)?
+            .recovery_amount
+            .saturating_sub(streamed);
+        gross.saturating_sub(recovery_amount)
+    } else {
+        gross
+    };
+    let fee = compute_fee(chargeable, dispatcher_config.fee_rate);
+    let net = gross
+        .checked_sub(fee)
+        .map_err(|e| ContractError::Overflow(e.to_string()))?;
+
+    // with a stream only the vested rewards are restaked, the new ones join the stream
+    let restaked = if dispatcher_config.reward_stream_window == 0 {
+        net + streamed
+    } else {
+        stream.releasable
+    };
+
+    let mut state = query_state(deps, env)?;
+    let requested_lst_token_amount = CURRENT_BATCH.load(deps.storage)?.requested_lst_token_amount;
+    let mut total_supply = query_total_lst_token_issued(deps)?;
+    state.total_staked_amount += restaked;
+    state.update_lst_exchange_rate(total_supply, requested_lst_token_amount);
+
+    // the fee is restaked against shares minted at the new rate, as the hub does when staking the rewards
+    if dispatcher_config.fee_as_shares && !fee.is_zero() {
+        total_supply += decimal_division(fee, state.lst_exchange_rate);
+        state.total_staked_amount += fee;
+        state.update_lst_exchange_rate(total_supply, requested_lst_token_amount);
+    }
+
+    Ok(PendingRewardsResponse {
+        rewards,
+        total,
+        held,
+        fee,
+        net,
+        projected_exchange_rate: state.lst_exchange_rate,
+    })
+}
+
+pub fn query_high_water_mark(deps: Deps, env: &Env) -> LstResult<HighWaterMarkResponse> {
+    let state = query_state(deps, env)?;
+    let total_supply = query_total_lst_token_issued(deps)?
+        + CURRENT_BATCH.load(deps.storage)?.requested_lst_token_amount;
+
+    Ok(HighWaterMarkResponse {
+        high_water_mark: state.high_water_mark,
+        exchange_rate: state.lst_exchange_rate,
+        recovery_amount: total_supply
+            .mul_floor(state.high_water_mark)
+            .saturating_sub(state.total_staked_amount),
+    })
+}
+
 fn all_unstake_history(
     storage: &dyn Storage,
     start: Option<u64>,
@@ -235,6 +386,7 @@ mod tests {
             lst_applied_exchange_rate: Decimal::from_ratio(2u128, 1u128),
             lst_withdraw_rate: Decimal::from_ratio(2u128, 1u128),
             released: false,
+            netted_amount: Uint128::zero(),
         };
         UNSTAKE_HISTORY
             .save(deps.as_mut().storage, batch_id, &history)
@@ -286,6 +438,7 @@ mod tests {
             lst_applied_exchange_rate: Decimal::from_ratio(2u128, 1u128),
             lst_withdraw_rate: Decimal::from_ratio(2u128, 1u128),
             released: false,
+            netted_amount: Uint128::zero(),
         };
         UNSTAKE_HISTORY
             .save(deps.as_mut().storage, batch_id1, &history)
@@ -321,6 +474,7 @@ mod tests {
                 lst_applied_exchange_rate: Decimal::from_ratio(2u128, 1u128),
                 lst_withdraw_rate: Decimal::from_ratio(2u128, 1u128),
                 released: false,
+                netted_amount: Uint128::zero(),
             };
             UNSTAKE_HISTORY
                 .save(deps.as_mut().storage, batch_id, &history)
@@ -357,6 +511,7 @@ mod tests {
                 lst_applied_exchange_rate: Decimal::from_ratio(2u128, 1u128),
                 lst_withdraw_rate: Decimal::from_ratio(2u128, 1u128),
                 released: false,
+                netted_amount: Uint128::zero(),
             };
             UNSTAKE_HISTORY
                 .save(deps.as_mut().storage, batch_id, &history)
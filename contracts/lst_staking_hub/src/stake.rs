@@ -7,19 +7,19 @@ use cw20_base::msg::ExecuteMsg as Cw20ExecuteMsg;
 
 use lst_common::{
     babylon_msg::{CosmosAny, MsgWrappedDelegate},
-    calculate_delegations,
+    delegation::{bounded_delegations, capped_delegations},
     errors::HubError,
     types::{LstResult, ProtoCoin, ResponseType},
-    validator::{QueryMsg::ValidatorsDelegation, ValidatorResponse},
+    validator::{QueryMsg::ValidatorsDelegation, ValidatorsDelegationResponse},
     ContractError, ValidatorError,
 };
 
 use crate::{
     contract::{check_slashing, query_total_lst_token_issued},
-    math::decimal_division,
+    math::{decimal_division, decimal_multiplication},
     state::{
-        update_pending_delegation_amount, update_state, StakeType, CONFIG, CURRENT_BATCH,
-        PARAMETERS, STATE,
+        record_delegation, update_pending_delegation_amount, update_state, StakeType, CONFIG,
+        CURRENT_BATCH, PARAMETERS, PENDING_DELEGATION, STATE,
     },
 };
 
@@ -42,9 +42,19 @@ pub fn execute_stake(
         .reward_dispatcher_contract
         .ok_or(HubError::RewardDispatcherNotSet)?;
 
-    //If stake type is StakeRewards, we need to check if the sender is the reward dispatcher contract
-    if stake_type == StakeType::StakeRewards && sender != reward_dispatcher_address {
-        return Err(ContractError::Unauthorized {});
+    //Rewards can only be staked by the reward dispatcher and top-ups by the insurance fund
+    match stake_type {
+        StakeType::LSTMint => {}
+        StakeType::InsuranceTopUp => {
+            if config.insurance_fund.as_ref() != Some(&sender) {
+                return Err(ContractError::Unauthorized {});
+            }
+        }
+        StakeType::StakeRewards | StakeType::StakeRewardsWithFeeShares(_) => {
+            if sender != reward_dispatcher_address {
+                return Err(ContractError::Unauthorized {});
+            }
+        }
     }
 
     let current_batch = CURRENT_BATCH.load(deps.storage)?;
@@ -61,21 +71,32 @@ pub fn execute_stake(
         .ok_or(HubError::InvalidAmount)?;
 
     let mut events: Vec<Event> = vec![];
-    // check slashing and get the latest exchange rate
-    let (slashing_events, _) = check_slashing(&mut deps, &env, &mut state)?;
-    events.extend(slashing_events);
+    // check slashing and get the latest exchange rate. A top-up is paid in the slashing check that
+    // credited it, so the check is skipped until the top-up is delegated
+    let slashing_msgs = if stake_type == StakeType::InsuranceTopUp {
+        vec![]
+    } else {
+        let (slashing_events, slashing_msgs, _) = check_slashing(&mut deps, &env, &mut state)?;
+        events.extend(slashing_events);
+        slashing_msgs
+    };
 
     let mut total_supply = query_total_lst_token_issued(deps.as_ref()).unwrap();
 
     let mint_amount = match stake_type {
         StakeType::LSTMint => decimal_division(payment.amount, state.lst_exchange_rate),
-        StakeType::StakeRewards => Uint128::zero(),
+        StakeType::StakeRewards
+        | StakeType::StakeRewardsWithFeeShares(_)
+        | StakeType::InsuranceTopUp => Uint128::zero(),
     };
 
     total_supply += mint_amount;
 
+    // fee shares minted to the recipients, in the same order as the fee amounts
+    let mut fee_share_mints: Vec<(String, Uint128)> = vec![];
+
     // state update
-    match stake_type {
+    match &stake_type {
         StakeType::LSTMint => {
             state.total_staked_amount += payment.amount;
             state.update_lst_exchange_rate(total_supply, requested_withdrawal_amount);
@@ -84,65 +105,117 @@ pub fn execute_stake(
             state.total_staked_amount += payment.amount;
             state.update_lst_exchange_rate(total_supply, requested_withdrawal_amount);
         }
-    }
-    update_pending_delegation_amount(&mut deps, &env, Some(payment.amount), None)?;
-    let state_events = update_state(deps.storage, old_state, state)?;
-    events.extend(state_events);
-
-    //validators management
-    let validators_registry_contract = config
-        .validators_registry_contract
-        .ok_or(HubError::ValidatorRegistryNotSet)?;
-
-    let validators: Vec<ValidatorResponse> =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: validators_registry_contract.to_string(),
-            msg: to_json_binary(&ValidatorsDelegation {})?,
-        }))?;
-
-    if validators.is_empty() {
-        return Err(ValidatorError::EmptyValidatorSet.into());
-    }
+        // already counted in the total staked amount by the slashing check
+        StakeType::InsuranceTopUp => {}
+        StakeType::StakeRewardsWithFeeShares(fee_shares) => {
+            let fee: Uint128 = fee_shares.iter().map(|(_, amount)| amount).sum();
+            let rewards = payment
+                .amount
+                .checked_sub(fee)
+                .map_err(|_| HubError::InvalidAmount)?;
+
+            // Restake the rewards first so the fee is minted at the new rate, then add the fee
+            // together with its shares, which leaves the rate unchanged
+            state.total_staked_amount += rewards;
+            state.update_lst_exchange_rate(total_supply, requested_withdrawal_amount);
 
-    let delegations = calculate_delegations(payment.amount, validators.as_slice())?;
+            for (recipient, amount) in fee_shares {
+                let shares = decimal_division(*amount, state.lst_exchange_rate);
+                total_supply += shares;
+                fee_share_mints.push((recipient.to_string(), shares));
+            }
 
-    let mut external_call_msgs: Vec<CosmosMsg> = vec![];
-    for i in 0..delegations.len() {
-        if delegations[i].is_zero() {
-            continue;
+            state.total_staked_amount += fee;
+            state.update_lst_exchange_rate(total_supply, requested_withdrawal_amount);
         }
-
-        let msg = prepare_wrapped_delegate_msg(
-            payment.denom.to_string(),
-            delegations[i].to_string(),
-            env.contract.address.to_string(),
-            validators[i].address.to_string(),
-        );
-
-        external_call_msgs.push(msg);
     }
+    let mut external_call_msgs: Vec<CosmosMsg> = if params.batched_delegation {
+        // the stake is held by the hub until the next DelegatePending call
+        PENDING_DELEGATION.update(deps.storage, |mut pending_delegation| -> LstResult<_> {
+            pending_delegation.batched_staking_amount += payment.amount;
+            Ok(pending_delegation)
+        })?;
+        vec![]
+    } else {
+        // stake arriving while unstake requests wait for their batch is held back to pay the batch out,
+        // only the rest is delegated right away
+        let mut pending_delegation = PENDING_DELEGATION.load(deps.storage)?;
+        let held_amount =
+            decimal_multiplication(requested_withdrawal_amount, state.lst_exchange_rate)
+                .saturating_sub(pending_delegation.batched_staking_amount)
+                .min(payment.amount);
+        pending_delegation.batched_staking_amount += held_amount;
+        PENDING_DELEGATION.save(deps.storage, &pending_delegation)?;
+
+        let delegated_amount = payment.amount - held_amount;
+        if delegated_amount.is_zero() {
+            vec![]
+        } else {
+            update_pending_delegation_amount(&mut deps, &env, Some(delegated_amount), None)?;
+            delegate_stake(&mut deps, &env, &payment.denom, delegated_amount)?
+        }
+    };
+    let state_events = update_state(deps.storage, old_state, state)?;
+    events.extend(state_events);
 
-    //Skip minting of lst token in case of staking rewards
-    if stake_type == StakeType::StakeRewards {
+    //Skip minting of lst token in case of staking rewards or insurance top-ups
+    if stake_type == StakeType::StakeRewards || stake_type == StakeType::InsuranceTopUp {
+        let action = if stake_type == StakeType::InsuranceTopUp {
+            "insurance_top_up"
+        } else {
+            "stake_rewards"
+        };
         let res = Response::new()
             .add_messages(external_call_msgs)
+            .add_submessages(slashing_msgs)
             .add_events(events)
             .add_attributes(vec![
-                attr("action", "stake_rewards"),
+                attr("action", action),
                 attr("from", sender.clone()),
                 attr("amount", payment.amount.to_string()),
             ]);
         return Ok(res);
     }
 
+    let token_address = config.lst_token.ok_or(HubError::LstTokenNotSet)?;
+
+    if let StakeType::StakeRewardsWithFeeShares(_) = stake_type {
+        let mut attrs = vec![
+            attr("action", "stake_rewards"),
+            attr("from", sender.clone()),
+            attr("amount", payment.amount.to_string()),
+        ];
+
+        for (recipient, shares) in fee_share_mints {
+            if shares.is_zero() {
+                continue;
+            }
+
+            attrs.push(attr("fee_shares", format!("{}:{}", recipient, shares)));
+            external_call_msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: token_address.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+                    recipient,
+                    amount: shares,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        let res = Response::new()
+            .add_messages(external_call_msgs)
+            .add_submessages(slashing_msgs)
+            .add_events(events)
+            .add_attributes(attrs);
+        return Ok(res);
+    }
+
     // Create mint message
     let mint_msg = Cw20ExecuteMsg::Mint {
         recipient: sender.to_string(),
         amount: mint_amount,
     };
 
-    let token_address = config.lst_token.ok_or(HubError::LstTokenNotSet)?;
-
     external_call_msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: token_address.to_string(),
         msg: to_json_binary(&mint_msg)?,
@@ -151,6 +224,7 @@ pub fn execute_stake(
 
     let res = Response::new()
         .add_messages(external_call_msgs)
+        .add_submessages(slashing_msgs)
         .add_events(events)
         .add_attributes(vec![
             attr("action", "mint"),
@@ -162,6 +236,104 @@ pub fn execute_stake(
     Ok(res)
 }
 
+// Delegate the stake held by the hub in batched delegation mode, at most once per staking epoch.
+// Anyone can call this method
+pub fn execute_delegate_pending(mut deps: DepsMut, env: Env) -> LstResult<Response<ResponseType>> {
+    let params = PARAMETERS.load(deps.storage)?;
+    let mut pending_delegation = PENDING_DELEGATION.load(deps.storage)?;
+
+    let amount = pending_delegation.batched_staking_amount;
+    if amount.is_zero() {
+        return Err(HubError::NoPendingStake.into());
+    }
+
+    let current_epoch_start = pending_delegation.current_epoch_start(env.block.height);
+    if pending_delegation.last_batch_epoch_start_block_height == Some(current_epoch_start) {
+        return Err(HubError::PendingStakeAlreadyDelegated.into());
+    }
+
+    pending_delegation.batched_staking_amount = Uint128::zero();
+    pending_delegation.last_batch_epoch_start_block_height = Some(current_epoch_start);
+    PENDING_DELEGATION.save(deps.storage, &pending_delegation)?;
+
+    update_pending_delegation_amount(&mut deps, &env, Some(amount), None)?;
+    let messages = delegate_stake(&mut deps, &env, &params.staking_coin_denom, amount)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "delegate_pending"),
+        attr("amount", amount),
+    ]))
+}
+
+// Split the amount across the validators of the registry and prepare the wrapped delegate messages
+fn delegate_stake(
+    deps: &mut DepsMut,
+    env: &Env,
+    denom: &str,
+    amount: Uint128,
+) -> LstResult<Vec<CosmosMsg>> {
+    let validators_registry_contract = CONFIG
+        .load(deps.storage)?
+        .validators_registry_contract
+        .ok_or(HubError::ValidatorRegistryNotSet)?;
+
+    let ValidatorsDelegationResponse {
+        strategy,
+        validators,
+        draining,
+        max_validator_share,
+        max_validators_per_delegation,
+        min_delegation_chunk,
+    } = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: validators_registry_contract.to_string(),
+        msg: to_json_binary(&ValidatorsDelegation {})?,
+    }))?;
+
+    if validators.is_empty() {
+        return Err(ValidatorError::EmptyValidatorSet.into());
+    }
+
+    let total_stake: Uint128 = validators
+        .iter()
+        .chain(draining.iter())
+        .map(|validator| validator.total_delegated)
+        .sum();
+    let delegations = capped_delegations(
+        &strategy,
+        amount,
+        validators.as_slice(),
+        total_stake,
+        max_validator_share,
+    )?;
+    let delegations = bounded_delegations(
+        delegations,
+        validators.as_slice(),
+        total_stake,
+        max_validator_share,
+        max_validators_per_delegation,
+        min_delegation_chunk,
+    );
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for i in 0..delegations.len() {
+        if delegations[i].is_zero() {
+            continue;
+        }
+
+        let msg = prepare_wrapped_delegate_msg(
+            denom.to_string(),
+            delegations[i].to_string(),
+            env.contract.address.to_string(),
+            validators[i].address.to_string(),
+        );
+        record_delegation(deps.storage, env, &validators[i].address, delegations[i])?;
+
+        messages.push(msg);
+    }
+
+    Ok(messages)
+}
+
 fn prepare_wrapped_delegate_msg(
     denom: String,
     amount: String,
@@ -188,19 +360,20 @@ mod tests {
     use cosmwasm_std::{
         attr, coin, from_json,
         testing::{message_info, mock_dependencies, mock_env},
-        to_json_binary, AnyMsg, Binary, ContractResult, CosmosMsg, SubMsg, SystemResult, Uint128,
-        WasmMsg, WasmQuery,
+        to_json_binary, AnyMsg, Binary, ContractResult, CosmosMsg, Decimal, SubMsg, SystemResult,
+        Uint128, WasmMsg, WasmQuery,
     };
     use cw20::{Cw20ExecuteMsg, Cw20QueryMsg};
     use cw20_base::state::TokenInfo;
     use lst_common::{
         babylon_msg::MsgWrappedDelegate,
+        delegation::DelegationStrategyKind,
         errors::HubError,
         hub::InstantiateMsg,
         types::ProtoCoin,
         validator::{
             QueryMsg::{self as ValidatorQueryMsg, ValidatorsDelegation},
-            ValidatorResponse,
+            ValidatorResponse, ValidatorsDelegationResponse,
         },
         ContractError,
     };
@@ -210,6 +383,7 @@ mod tests {
     use super::execute_stake;
 
     #[test]
+    #[allow(clippy::cmp_owned)]
     fn test_execute_stake() {
         let mut deps = mock_dependencies();
         let env = mock_env();
@@ -249,6 +423,7 @@ mod tests {
                 None,
                 None,
                 Some(reward_dispatcher.to_string()),
+                None,
             )
             .unwrap();
 
@@ -273,6 +448,7 @@ mod tests {
                 None,
                 None,
                 Some(reward_dispatcher.to_string()),
+                None,
             )
             .unwrap();
 
@@ -298,6 +474,7 @@ mod tests {
                 None,
                 None,
                 Some(reward_dispatcher.to_string()),
+                None,
             )
             .unwrap();
 
@@ -317,6 +494,7 @@ mod tests {
                 None,
                 None,
                 Some(reward_dispatcher.to_string()),
+                None,
             )
             .unwrap();
 
@@ -355,10 +533,18 @@ mod tests {
                         let msg: ValidatorQueryMsg = from_json(msg).unwrap();
                         match msg {
                             ValidatorsDelegation {} => SystemResult::Ok(ContractResult::Ok(
-                                to_json_binary(&vec![ValidatorResponse {
-                                    total_delegated: Uint128::new(100),
-                                    address: "validator1".to_string(),
-                                }])
+                                to_json_binary(&ValidatorsDelegationResponse {
+                                    strategy: DelegationStrategyKind::EqualSplit,
+                                    validators: vec![ValidatorResponse {
+                                        total_delegated: Uint128::new(100),
+                                        address: "validator1".to_string(),
+                                        weight: 1,
+                                    }],
+                                    draining: vec![],
+                                    max_validator_share: Decimal::one(),
+                                    max_validators_per_delegation: 0,
+                                    min_delegation_chunk: Uint128::zero(),
+                                })
                                 .unwrap(),
                             )),
                             _ => panic!("unexpected query"),
@@ -378,6 +564,7 @@ mod tests {
                 Some(lst_token.to_string()),
                 Some(validator_registry.to_string()),
                 Some(reward_dispatcher.to_string()),
+                None,
             )
             .unwrap();
 
@@ -1,9 +1,14 @@
+use std::collections::HashMap;
+
 use crate::{constants::*, math::decimal_multiplication};
 use cosmwasm_std::{Addr, Deps, DepsMut, Env, Event, Storage, Uint128};
 use cw_storage_plus::{Item, Map};
 use lst_common::{
     errors::HubError,
-    hub::{Config, CurrentBatch, Parameters, PendingDelegation, State, UnstakeHistory},
+    hub::{
+        Config, CurrentBatch, Parameters, PendingDelegation, SlashingEvent, State, UnstakeHistory,
+        ValidatorDelegation,
+    },
     types::LstResult,
 };
 
@@ -18,10 +23,21 @@ pub const UNSTAKE_HISTORY: Map<u64, UnstakeHistory> = Map::new(UNSTAKE_HISTORY_K
 
 pub const PENDING_DELEGATION: Item<PendingDelegation> = Item::new(PENDING_DELEGATION_KEY);
 
+/// HashMap<validator address, delegation of the hub>, updated whenever the hub delegates, undelegates or redelegates
+pub const DELEGATION_LEDGER: Map<&str, ValidatorDelegation> = Map::new(DELEGATION_LEDGER_KEY);
+
+/// HashMap<id, slashing detected on a validator>
+pub const SLASHING_HISTORY: Map<u64, SlashingEvent> = Map::new(SLASHING_HISTORY_KEY);
+pub const SLASHING_EVENT_COUNT: Item<u64> = Item::new(SLASHING_EVENT_COUNT_KEY);
+
 #[derive(PartialEq)]
 pub enum StakeType {
     LSTMint,
     StakeRewards,
+    /// Rewards with the fee portion minted as LST to the fee recipients
+    StakeRewardsWithFeeShares(Vec<(Addr, Uint128)>),
+    /// Top-up from the insurance fund covering a slashing loss
+    InsuranceTopUp,
 }
 
 #[derive(PartialEq)]
@@ -139,6 +155,13 @@ pub fn update_state(
             .add_attribute(OLD_AMOUNT, old_state.total_staked_amount.to_string())
             .add_attribute(NEW_AMOUNT, new_state.total_staked_amount.to_string()),
     );
+    if new_state.high_water_mark != old_state.high_water_mark {
+        events.push(
+            Event::new(HIGH_WATER_MARK_UPDATED)
+                .add_attribute(OLD_RATE, old_state.high_water_mark.to_string())
+                .add_attribute(NEW_RATE, new_state.high_water_mark.to_string()),
+        );
+    }
     Ok(events)
 }
 
@@ -189,6 +212,174 @@ pub fn update_pending_delegation_amount(
     Ok(())
 }
 
+// Return the delegations of the hub per validator, with the pending changes of the ended epochs applied
+pub fn read_delegation_ledger(
+    storage: &dyn Storage,
+    env: &Env,
+) -> LstResult<Vec<(String, ValidatorDelegation)>> {
+    let current_epoch_start = PENDING_DELEGATION
+        .load(storage)?
+        .current_epoch_start(env.block.height);
+
+    DELEGATION_LEDGER
+        .range(storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| {
+            let (validator, mut delegation) = item?;
+            delegation.settle(current_epoch_start);
+            Ok((validator, delegation))
+        })
+        .collect()
+}
+
+// Slashing lowers the delegations on chain, the bonded amounts of the ledger are lowered to match them.
+// The chain delegations don't include the delegations queued for the current epoch, so only the bonded amounts are compared.
+// The ledger settles an ended epoch at its estimated end, while the chain may apply it some blocks later. Until the chain
+// holds the settled amount, a chain delegation at the amount before the settlement is neither untracked nor slashed.
+// Chain delegations above everything the ledger knows of were queued before the ledger existed, they are added as bonded
+pub fn reconcile_delegation_ledger(
+    storage: &mut dyn Storage,
+    env: &Env,
+    chain_delegations: &HashMap<String, Uint128>,
+) -> LstResult<Vec<(String, Uint128)>> {
+    let current_epoch_start = PENDING_DELEGATION
+        .load(storage)?
+        .current_epoch_start(env.block.height);
+    let ledger = read_delegation_ledger(storage, env)?;
+    for (validator, chain_amount) in chain_delegations {
+        if !ledger
+            .iter()
+            .any(|(ledger_validator, _)| ledger_validator == validator)
+        {
+            DELEGATION_LEDGER.save(
+                storage,
+                validator,
+                &ValidatorDelegation {
+                    bonded: *chain_amount,
+                    epoch_start_block_height: current_epoch_start,
+                    ..Default::default()
+                },
+            )?;
+        }
+    }
+
+    let mut slashed = vec![];
+    for (validator, mut delegation) in ledger {
+        let chain_amount = chain_delegations
+            .get(&validator)
+            .copied()
+            .unwrap_or_default();
+        if !delegation.settled_delegation.is_zero() || !delegation.settled_undelegation.is_zero() {
+            if chain_amount.abs_diff(delegation.unsettled_bonded())
+                <= Uint128::new(SLASHING_DUST_TOLERANCE)
+            {
+                continue;
+            }
+            // the chain has applied the settled changes
+            delegation.settled_delegation = Uint128::zero();
+            delegation.settled_undelegation = Uint128::zero();
+            DELEGATION_LEDGER.save(storage, &validator, &delegation)?;
+        }
+        let untracked =
+            chain_amount.saturating_sub(delegation.bonded + delegation.pending_delegation);
+        if !untracked.is_zero() {
+            delegation.bonded += untracked;
+            DELEGATION_LEDGER.save(storage, &validator, &delegation)?;
+            continue;
+        }
+        if delegation.bonded <= chain_amount {
+            continue;
+        }
+        // share conversions on chain round delegations down, such dust is not reported as slashing
+        let shortfall = delegation.bonded - chain_amount;
+        if shortfall > Uint128::new(SLASHING_DUST_TOLERANCE) {
+            slashed.push((validator.clone(), shortfall));
+        }
+        delegation.bonded = chain_amount;
+        if delegation.bonded.is_zero() && delegation.delegated().is_zero() {
+            DELEGATION_LEDGER.remove(storage, &validator);
+        } else {
+            DELEGATION_LEDGER.save(storage, &validator, &delegation)?;
+        }
+    }
+    Ok(slashed)
+}
+
+// Append a slashing of a validator to the slashing history
+pub fn record_slashing(
+    storage: &mut dyn Storage,
+    env: &Env,
+    validator: String,
+    amount: Uint128,
+) -> LstResult<()> {
+    let id = SLASHING_EVENT_COUNT.may_load(storage)?.unwrap_or_default();
+    SLASHING_HISTORY.save(
+        storage,
+        id,
+        &SlashingEvent {
+            id,
+            validator,
+            amount,
+            height: env.block.height,
+            time: env.block.time.seconds(),
+        },
+    )?;
+    SLASHING_EVENT_COUNT.save(storage, &(id + 1))?;
+    Ok(())
+}
+
+// Record a delegation queued for the current epoch
+pub fn record_delegation(
+    storage: &mut dyn Storage,
+    env: &Env,
+    validator: &str,
+    amount: Uint128,
+) -> LstResult<()> {
+    update_delegation_ledger(storage, env, validator, |delegation| {
+        delegation.pending_delegation += amount;
+    })
+}
+
+// Record an undelegation queued for the current epoch, redelegations are recorded as an undelegation from the source
+// and a delegation to the destination
+pub fn record_undelegation(
+    storage: &mut dyn Storage,
+    env: &Env,
+    validator: &str,
+    amount: Uint128,
+) -> LstResult<()> {
+    update_delegation_ledger(storage, env, validator, |delegation| {
+        delegation.pending_undelegation += amount;
+    })
+}
+
+fn update_delegation_ledger(
+    storage: &mut dyn Storage,
+    env: &Env,
+    validator: &str,
+    action: impl FnOnce(&mut ValidatorDelegation),
+) -> LstResult<()> {
+    let current_epoch_start = PENDING_DELEGATION
+        .load(storage)?
+        .current_epoch_start(env.block.height);
+
+    let mut delegation =
+        DELEGATION_LEDGER
+            .may_load(storage, validator)?
+            .unwrap_or(ValidatorDelegation {
+                epoch_start_block_height: current_epoch_start,
+                ..Default::default()
+            });
+    delegation.settle(current_epoch_start);
+    action(&mut delegation);
+
+    if delegation.bonded.is_zero() && delegation.delegated().is_zero() {
+        DELEGATION_LEDGER.remove(storage, validator);
+    } else {
+        DELEGATION_LEDGER.save(storage, validator, &delegation)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,6 +403,8 @@ mod tests {
             staking_epoch_start_block_height: start_height,
             pending_staking_amount: staking_amount.unwrap_or(Uint128::zero()),
             pending_unstaking_amount: unstaking_amount.unwrap_or(Uint128::zero()),
+            batched_staking_amount: Uint128::zero(),
+            last_batch_epoch_start_block_height: None,
             staking_epoch_length_blocks: epoch_length,
         };
         PENDING_DELEGATION
@@ -330,4 +523,160 @@ mod tests {
         );
         assert_eq!(pending_delegation.staking_epoch_start_block_height, 666001);
     }
+
+    #[test]
+    fn test_delegation_ledger() {
+        let (mut deps, mut env) = setup_test_env();
+        setup_pending_delegation(deps.as_mut().storage, 664561, None, None, 360);
+        env.block.height = 664600;
+
+        // delegations and undelegations of the epoch stay pending
+        record_delegation(
+            deps.as_mut().storage,
+            &env,
+            "validator1",
+            Uint128::new(1000),
+        )
+        .unwrap();
+        record_delegation(deps.as_mut().storage, &env, "validator2", Uint128::new(500)).unwrap();
+        record_undelegation(deps.as_mut().storage, &env, "validator1", Uint128::new(200)).unwrap();
+
+        let ledger = read_delegation_ledger(deps.as_ref().storage, &env).unwrap();
+        assert_eq!(ledger[0].0, "validator1");
+        assert_eq!(ledger[0].1.delegated(), Uint128::new(800));
+        assert_eq!(ledger[0].1.bonded, Uint128::zero());
+
+        // pending amounts are bonded once the epoch has ended
+        env.block.height = 664921;
+        let ledger = read_delegation_ledger(deps.as_ref().storage, &env).unwrap();
+        assert_eq!(ledger[0].1.bonded, Uint128::new(800));
+        assert_eq!(ledger[1].1.bonded, Uint128::new(500));
+        assert_eq!(ledger[1].1.epoch_start_block_height, 664921);
+
+        // rounding dust lowers the bonded amount without being reported as slashing
+        let slashed = reconcile_delegation_ledger(
+            deps.as_mut().storage,
+            &env,
+            &HashMap::from([
+                ("validator1".to_string(), Uint128::new(799)),
+                ("validator2".to_string(), Uint128::new(500)),
+            ]),
+        )
+        .unwrap();
+        assert!(slashed.is_empty());
+        let ledger = read_delegation_ledger(deps.as_ref().storage, &env).unwrap();
+        assert_eq!(ledger[0].1.bonded, Uint128::new(799));
+
+        // slashing lowers the bonded amount to the chain amount, missing delegations are fully slashed
+        let slashed = reconcile_delegation_ledger(
+            deps.as_mut().storage,
+            &env,
+            &HashMap::from([("validator1".to_string(), Uint128::new(560))]),
+        )
+        .unwrap();
+        assert_eq!(
+            slashed,
+            vec![
+                ("validator1".to_string(), Uint128::new(239)),
+                ("validator2".to_string(), Uint128::new(500)),
+            ]
+        );
+        let ledger = read_delegation_ledger(deps.as_ref().storage, &env).unwrap();
+        assert_eq!(ledger.len(), 1);
+        assert_eq!(ledger[0].1.bonded, Uint128::new(560));
+
+        // delegations bonded on chain without a ledger entry are added, pending delegations are not counted twice
+        record_delegation(deps.as_mut().storage, &env, "validator1", Uint128::new(100)).unwrap();
+        let slashed = reconcile_delegation_ledger(
+            deps.as_mut().storage,
+            &env,
+            &HashMap::from([
+                ("validator1".to_string(), Uint128::new(660)),
+                ("validator3".to_string(), Uint128::new(300)),
+            ]),
+        )
+        .unwrap();
+        assert!(slashed.is_empty());
+        let ledger = read_delegation_ledger(deps.as_ref().storage, &env).unwrap();
+        assert_eq!(ledger[0].1.bonded, Uint128::new(560));
+        assert_eq!(ledger[0].1.delegated(), Uint128::new(660));
+        assert_eq!(ledger[1].0, "validator3");
+        assert_eq!(ledger[1].1.bonded, Uint128::new(300));
+
+        let slashed = reconcile_delegation_ledger(
+            deps.as_mut().storage,
+            &env,
+            &HashMap::from([
+                ("validator1".to_string(), Uint128::new(700)),
+                ("validator3".to_string(), Uint128::new(300)),
+            ]),
+        )
+        .unwrap();
+        assert!(slashed.is_empty());
+        let ledger = read_delegation_ledger(deps.as_ref().storage, &env).unwrap();
+        assert_eq!(ledger[0].1.bonded, Uint128::new(600));
+
+        // fully undelegated validators are removed once the epoch has ended
+        record_undelegation(deps.as_mut().storage, &env, "validator1", Uint128::new(700)).unwrap();
+        record_undelegation(deps.as_mut().storage, &env, "validator3", Uint128::new(300)).unwrap();
+        assert!(DELEGATION_LEDGER.has(deps.as_ref().storage, "validator1"));
+        env.block.height = 665281;
+        record_delegation(deps.as_mut().storage, &env, "validator1", Uint128::zero()).unwrap();
+        record_delegation(deps.as_mut().storage, &env, "validator3", Uint128::zero()).unwrap();
+        assert!(DELEGATION_LEDGER.is_empty(deps.as_ref().storage));
+    }
+
+    #[test]
+    fn test_delegation_ledger_settled_before_chain() {
+        let (mut deps, mut env) = setup_test_env();
+        setup_pending_delegation(deps.as_mut().storage, 664561, None, None, 360);
+        env.block.height = 664600;
+        record_delegation(
+            deps.as_mut().storage,
+            &env,
+            "validator1",
+            Uint128::new(1000),
+        )
+        .unwrap();
+        env.block.height = 664921;
+        let chain =
+            |amount: u128| HashMap::from([("validator1".to_string(), Uint128::new(amount))]);
+        assert!(
+            reconcile_delegation_ledger(deps.as_mut().storage, &env, &chain(1000))
+                .unwrap()
+                .is_empty()
+        );
+
+        // the ledger settles the undelegation at the epoch end, the chain applies it a block later
+        record_undelegation(deps.as_mut().storage, &env, "validator1", Uint128::new(300)).unwrap();
+        env.block.height = 665281;
+        let slashed =
+            reconcile_delegation_ledger(deps.as_mut().storage, &env, &chain(1000)).unwrap();
+        assert!(slashed.is_empty());
+        let ledger = read_delegation_ledger(deps.as_ref().storage, &env).unwrap();
+        assert_eq!(ledger[0].1.bonded, Uint128::new(700));
+
+        env.block.height = 665282;
+        let slashed =
+            reconcile_delegation_ledger(deps.as_mut().storage, &env, &chain(700)).unwrap();
+        assert!(slashed.is_empty());
+        let ledger = read_delegation_ledger(deps.as_ref().storage, &env).unwrap();
+        assert_eq!(ledger[0].1.bonded, Uint128::new(700));
+
+        // same for a delegation
+        record_delegation(deps.as_mut().storage, &env, "validator1", Uint128::new(200)).unwrap();
+        env.block.height = 665641;
+        let slashed =
+            reconcile_delegation_ledger(deps.as_mut().storage, &env, &chain(700)).unwrap();
+        assert!(slashed.is_empty());
+        env.block.height = 665642;
+        let slashed =
+            reconcile_delegation_ledger(deps.as_mut().storage, &env, &chain(900)).unwrap();
+        assert!(slashed.is_empty());
+
+        // slashing is still reported once the chain has applied the settled changes
+        let slashed =
+            reconcile_delegation_ledger(deps.as_mut().storage, &env, &chain(600)).unwrap();
+        assert_eq!(slashed, vec![("validator1".to_string(), Uint128::new(300))]);
+    }
 }
@@ -1,6 +1,6 @@
 #![cfg(not(target_arch = "wasm32"))]
 
-use crate::{execute, instantiate, query};
+use crate::{execute, instantiate, query, reply};
 use cosmwasm_std::{Addr, Env};
 use cw_multi_test::{Contract, ContractWrapper};
 use lst_common::babylon::{
@@ -19,7 +19,9 @@ pub struct StakingHubContract {
 
 impl TestingContract<InstantiateMsg, ExecuteMsg, QueryMsg> for StakingHubContract {
     fn wrapper() -> Box<dyn Contract<EpochingMsg, EpochingQuery>> {
-        Box::new(ContractWrapper::new_with_empty(execute, instantiate, query))
+        Box::new(
+            ContractWrapper::new_with_empty(execute, instantiate, query).with_reply_empty(reply),
+        )
     }
 
     fn default_init(_app: &mut BabylonApp, _env: &Env) -> InstantiateMsg {
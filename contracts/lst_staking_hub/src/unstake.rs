@@ -1,19 +1,19 @@
 use cosmos_sdk_proto::cosmos::staking::v1beta1::MsgUndelegate;
 use cosmwasm_std::{
     attr, coins, to_json_binary, BankMsg, CosmosMsg, DepsMut, Env, Event, MessageInfo,
-    QueryRequest, Response, Storage, Uint128, WasmMsg, WasmQuery,
+    QueryRequest, Response, Storage, SubMsg, Uint128, WasmMsg, WasmQuery,
 };
 use cw20::{AllowanceResponse, BalanceResponse, Cw20QueryMsg};
 use cw20_base::msg::ExecuteMsg as Cw20ExecuteMsg;
 
 use lst_common::{
     babylon_msg::{CosmosAny, MsgWrappedUndelegate},
-    delegation::calculate_undelegations,
+    delegation::DelegationStrategy,
     errors::HubError,
     hub::{CurrentBatch, State, UnstakeHistory},
     to_checked_address,
     types::{LstResult, ProtoCoin, ResponseType},
-    validator::{QueryMsg::ValidatorsDelegation, ValidatorResponse},
+    validator::{QueryMsg::ValidatorsDelegation, ValidatorsDelegationResponse},
     ContractError,
 };
 
@@ -22,9 +22,9 @@ use crate::{
     math::decimal_multiplication,
     state::{
         get_finished_amount, get_finished_amount_for_batches, get_pending_delegation_amount,
-        read_unstake_history, remove_unstake_wait_list, update_pending_delegation_amount,
-        update_state, UnstakeType, CONFIG, CURRENT_BATCH, PARAMETERS, STATE, UNSTAKE_HISTORY,
-        UNSTAKE_WAIT_LIST,
+        read_unstake_history, record_undelegation, remove_unstake_wait_list,
+        update_pending_delegation_amount, update_state, UnstakeType, CONFIG, CURRENT_BATCH,
+        PARAMETERS, PENDING_DELEGATION, STATE, UNSTAKE_HISTORY, UNSTAKE_WAIT_LIST,
     },
 };
 
@@ -84,14 +84,14 @@ pub(crate) fn execute_unstake(
         }
     };
 
-    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+    messages.push(SubMsg::new(WasmMsg::Execute {
         contract_addr: lst_token_addr.to_string(),
         msg: to_json_binary(&burn_msg)?,
         funds: vec![],
     }));
 
     let res = Response::new()
-        .add_messages(messages)
+        .add_submessages(messages)
         .add_events(events)
         .add_attributes(vec![
             attr("action", "burn"),
@@ -109,7 +109,7 @@ fn check_for_unstake_batch_epoch_completion(
     deps: &mut DepsMut,
     env: &Env,
     current_batch: &mut CurrentBatch,
-) -> LstResult<(Vec<CosmosMsg>, Vec<Event>)> {
+) -> LstResult<(Vec<SubMsg>, Vec<Event>)> {
     // read parameters
     let params = PARAMETERS.load(deps.storage)?;
     let epoch_period = params.epoch_length;
@@ -119,19 +119,17 @@ fn check_for_unstake_batch_epoch_completion(
     let old_state = state.clone();
 
     // check if slashing has occurred and update the exchange rate
-    let (slashing_events, _) = check_slashing(deps, env, &mut state)?;
+    let (slashing_events, mut messages, _) = check_slashing(deps, env, &mut state)?;
     events.extend(slashing_events);
 
     let current_time = env.block.time.seconds();
     let passed_time = current_time - state.last_unbonded_time;
 
-    let mut messages: Vec<CosmosMsg> = vec![];
-
     // if the epoch period is passed, the undelegate message would be sent
     if passed_time > epoch_period {
-        let mut undelegate_msgs =
+        let undelegate_msgs =
             process_undelegations_for_batch(deps, env.clone(), current_batch, &mut state)?;
-        messages.append(&mut undelegate_msgs);
+        messages.extend(undelegate_msgs.into_iter().map(SubMsg::new));
     }
 
     // Store the new requested id in the batch
@@ -158,7 +156,7 @@ pub fn execute_process_undelegations(mut deps: DepsMut, env: Env) -> LstResult<R
         check_for_unstake_batch_epoch_completion(&mut deps, &env, &mut current_batch)?;
 
     let res = Response::new()
-        .add_messages(messages)
+        .add_submessages(messages)
         .add_events(events)
         .add_attributes(vec![attr(
             "process undelegations",
@@ -215,15 +213,33 @@ fn process_undelegations_for_batch(
         state.lst_exchange_rate,
     );
 
+    // net the batch against the stake waiting to be delegated, only the rest is undelegated
+    let mut pending_delegation = PENDING_DELEGATION.load(deps.storage)?;
+    let netted_amount = pending_delegation
+        .batched_staking_amount
+        .min(unstaked_amount_in_batch);
+    pending_delegation.batched_staking_amount -= netted_amount;
+    PENDING_DELEGATION.save(deps.storage, &pending_delegation)?;
+    let undelegated_amount = unstaked_amount_in_batch - netted_amount;
+
     // send undelegate requests to possibly more than one validators
-    let undelegate_msgs =
-        pick_validator_for_undelegation(deps, env.clone(), unstaked_amount_in_batch)?;
+    let undelegate_msgs = if undelegated_amount.is_zero() {
+        vec![]
+    } else {
+        pick_validator_for_undelegation(deps, env.clone(), undelegated_amount)?
+    };
 
     state.total_staked_amount = state
         .total_staked_amount
         .checked_sub(unstaked_amount_in_batch)
         .map_err(|e| ContractError::Overflow(e.to_string()))?;
-    update_pending_delegation_amount(deps, &env, None, Some(unstaked_amount_in_batch))?;
+    update_pending_delegation_amount(deps, &env, None, Some(undelegated_amount))?;
+
+    // a batch fully paid out of pending stake has nothing to wait for and is released right away
+    let released = undelegated_amount.is_zero();
+    if released {
+        state.unclaimed_unstaked_balance += unstaked_amount_in_batch;
+    }
 
     // Store history for withdraw unstaked
     let history = UnstakeHistory {
@@ -232,7 +248,8 @@ fn process_undelegations_for_batch(
         lst_token_amount: current_batch.requested_lst_token_amount,
         lst_applied_exchange_rate: state.lst_exchange_rate,
         lst_withdraw_rate: state.lst_exchange_rate,
-        released: false,
+        released,
+        netted_amount,
     };
 
     UNSTAKE_HISTORY.save(deps.storage, current_batch.id, &history)?;
@@ -261,19 +278,48 @@ fn pick_validator_for_undelegation(
 
     let mut messages: Vec<CosmosMsg> = vec![];
 
-    let delegator_address = env.contract.address;
+    let delegator_address = env.contract.address.clone();
 
     let validators_registry_contract = config
         .validators_registry_contract
         .ok_or(HubError::ValidatorRegistryNotSet)?;
 
-    let validators: Vec<ValidatorResponse> =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: validators_registry_contract.to_string(),
-            msg: to_json_binary(&ValidatorsDelegation {})?,
-        }))?;
+    let ValidatorsDelegationResponse {
+        strategy,
+        validators,
+        draining,
+        ..
+    } = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: validators_registry_contract.to_string(),
+        msg: to_json_binary(&ValidatorsDelegation {})?,
+    }))?;
+
+    // soft-removed validators are drained before touching the active validators
+    let mut remaining = claim;
+    for validator in draining.iter() {
+        if remaining.is_zero() {
+            break;
+        }
 
-    let undelegations = calculate_undelegations(claim, validators.clone())?;
+        let undelegated_amount = validator.total_delegated.min(remaining);
+        remaining -= undelegated_amount;
+
+        let msg = prepare_wrapped_undelegate_msg(
+            staking_coin_denom.clone(),
+            undelegated_amount.to_string(),
+            delegator_address.to_string(),
+            validator.address.to_string(),
+        );
+        record_undelegation(deps.storage, &env, &validator.address, undelegated_amount)?;
+
+        messages.push(msg);
+    }
+
+    if remaining.is_zero() {
+        return Ok(messages);
+    }
+
+    let undelegations = strategy.undelegations(remaining, validators.as_slice())?;
 
     for (index, undelegated_amount) in undelegations.iter().enumerate() {
         if undelegated_amount.is_zero() {
@@ -286,6 +332,12 @@ fn pick_validator_for_undelegation(
             delegator_address.to_string(),
             validators[index].address.to_string(),
         );
+        record_undelegation(
+            deps.storage,
+            &env,
+            &validators[index].address,
+            *undelegated_amount,
+        )?;
 
         messages.push(msg);
     }
@@ -305,8 +357,12 @@ pub fn execute_process_withdraw_requests(mut deps: DepsMut, env: Env) -> LstResu
         .query_balance(&env.contract.address, &*params.staking_coin_denom)?
         .amount;
     let (pending_staking_amount, _) = get_pending_delegation_amount(deps.as_ref(), &env)?;
+    // stake held by the hub in batched delegation mode is not available for withdrawals
+    let batched_staking_amount = PENDING_DELEGATION
+        .load(deps.storage)?
+        .batched_staking_amount;
     let actual_free_balance = hub_balance
-        .checked_sub(pending_staking_amount)
+        .checked_sub(pending_staking_amount + batched_staking_amount)
         .map_err(|e| ContractError::Overflow(e.to_string()))?;
 
     let (events, left_over_unstaked_amount) =
@@ -369,6 +425,7 @@ fn process_withdraw_rate(
 
 // Helper function to get unprocessed histories
 // Only return the histories for which the unstaking cutoff time has passed, haven't been released yet and exists in storage
+// Batches already released by netting are skipped
 // Also checks if contract has sufficient balance to release the funds
 fn get_unprocessed_histories(
     storage: &dyn Storage,
@@ -399,8 +456,6 @@ fn get_unprocessed_histories(
                         // If we don't have enough balance, stop processing
                         break;
                     }
-                } else {
-                    break;
                 }
             }
             Err(_) => break,
@@ -452,8 +507,12 @@ fn execute_withdraw_unstaked_impl(
         .amount;
 
     let (pending_staking_amount, _) = get_pending_delegation_amount(deps.as_ref(), &env)?;
+    // stake held by the hub in batched delegation mode is not available for withdrawals
+    let batched_staking_amount = PENDING_DELEGATION
+        .load(deps.storage)?
+        .batched_staking_amount;
     let actual_free_balance = hub_balance
-        .checked_sub(pending_staking_amount)
+        .checked_sub(pending_staking_amount + batched_staking_amount)
         .map_err(|e| ContractError::Overflow(e.to_string()))?;
 
     // Process withdrawal rate first (MUST be before get_finished_amount)
@@ -597,6 +656,7 @@ mod tests {
                 Some(lst_token.to_string()),
                 Some(validator_registry.to_string()),
                 Some(reward_dispatcher.to_string()),
+                None,
             )
             .unwrap();
         }
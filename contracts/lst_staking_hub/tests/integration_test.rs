@@ -1,22 +1,49 @@
 #![cfg(any(test, feature = "testing"))]
+// the older tests predate the clippy gate on test targets
+#![allow(
+    unused_variables,
+    unused_must_use,
+    clippy::needless_range_loop,
+    clippy::bool_assert_comparison
+)]
 
 use cosmwasm_std::testing::mock_env;
 use cosmwasm_std::{coin, coins, Addr, Coin, Decimal, Uint128, Validator};
 use cw20::BalanceResponse;
 use cw20::Cw20ExecuteMsg::IncreaseAllowance;
-use cw_multi_test::{Executor, StakingInfo};
+use cw_multi_test::{AppResponse, BankSudo, Executor, StakingInfo, StakingSudo, SudoMsg};
 use lst_common::address::VALIDATOR_ADDR_PREFIX;
 use lst_common::babylon::{
     DENOM, EPOCH_LENGTH, STAKING_EPOCH_LENGTH_BLOCKS, STAKING_EPOCH_START_BLOCK_HEIGHT,
     UNSTAKING_PERIOD,
 };
 use lst_common::hub::CurrentBatch as CurrentBatchRes;
-use lst_common::hub::ExecuteMsg::{Stake, Unstake, UpdateConfig};
+use lst_common::hub::ExecuteMsg::{
+    DelegatePending, InsuranceTopUp, Stake, Unstake, UpdateConfig, UpdateGlobalIndex, UpdateParams,
+};
 use lst_common::hub::PendingDelegation as PendingDelegationRes;
-use lst_common::hub::QueryMsg::{CurrentBatch, ExchangeRate, PendingDelegation};
+use lst_common::hub::QueryMsg::{
+    CurrentBatch, Delegations, ExchangeRate, HighWaterMark, PendingDelegation, PendingRewards,
+    SlashingHistory,
+};
+use lst_common::hub::{
+    HighWaterMarkResponse, PendingRewardsResponse, SlashingHistoryResponse,
+    ValidatorDelegationResponse,
+};
+use lst_common::insurance_msg::{
+    ClaimsResponse, FundStateResponse, InstantiateMsg as InsuranceInstantiateMsg,
+    QueryMsg as InsuranceQueryMsg,
+};
+use lst_common::mock_router::{ExecuteMsg as MockRouterExecuteMsg, MockRouterContract};
+use lst_common::rewards_msg::QueryMsg::DispatchHistory;
+use lst_common::rewards_msg::{
+    DenomPolicy, DispatchHistoryResponse, ExecuteMsg as DispatcherExecuteMsg,
+    QueryMsg as DispatcherQueryMsg, RewardStats,
+};
 use lst_common::testing::{BabylonApp, TestingContract};
-use lst_common::validator::ExecuteMsg::AddValidator;
+use lst_common::validator::ExecuteMsg::{AddValidator, SoftRemoveValidator};
 use lst_common::validator::Validator as LSTValidator;
+use lst_insurance_fund::testing::InsuranceFundContract;
 use lst_reward_dispatcher::testing::RewardDispatcherContract;
 use lst_staking_hub::testing::StakingHubContract;
 use lst_token::testing::TokenContract;
@@ -32,13 +59,19 @@ struct TestContracts {
 const UNBONDING_TIME: u64 = 180000; // time between unbonding and receiving tokens back (in seconds) - 50hours
 
 fn instantiate() -> (BabylonApp, TestContracts, Vec<(Addr, Validator)>) {
+    instantiate_with_validators(10)
+}
+
+fn instantiate_with_validators(
+    count: usize,
+) -> (BabylonApp, TestContracts, Vec<(Addr, Validator)>) {
     let block = mock_env().block;
     let mut validators: Vec<(Addr, Validator)> = vec![];
 
     let mut app = BabylonApp::new(|router, api, storage| {
         let owner = api.addr_make("owner");
 
-        for i in 1..=10 {
+        for i in 1..=count {
             let validator_addr = api
                 .with_prefix(VALIDATOR_ADDR_PREFIX)
                 .addr_make(format!("validator{}", i).as_str());
@@ -105,6 +138,7 @@ fn instantiate() -> (BabylonApp, TestContracts, Vec<(Addr, Validator)>) {
                 lst_token: Some(lst_token.addr().to_string()),
                 validator_registry: Some(validator_registry.addr().to_string()),
                 reward_dispatcher: Some(reward_dispatcher.addr().to_string()),
+                insurance_fund: None,
             },
         )
         .unwrap();
@@ -119,6 +153,7 @@ fn instantiate() -> (BabylonApp, TestContracts, Vec<(Addr, Validator)>) {
                     validator: LSTValidator {
                         address: validator.address,
                     },
+                    reason: None,
                 },
             )
             .expect("Failed to add validator");
@@ -271,6 +306,8 @@ fn test_unbonding() {
             staking_epoch_start_block_height: 360, // next epoch
             pending_staking_amount: Uint128::zero(),
             pending_unstaking_amount: Uint128::zero(),
+            batched_staking_amount: Uint128::zero(),
+            last_batch_epoch_start_block_height: None,
         }
     );
 
@@ -389,6 +426,8 @@ fn test_stake_unstake_within_same_epoch() {
             staking_epoch_start_block_height: 0,
             pending_staking_amount: Uint128::new(1_500_000), // should be 1_300_000 ??
             pending_unstaking_amount: Uint128::zero(),
+            batched_staking_amount: Uint128::zero(),
+            last_batch_epoch_start_block_height: None,
         }
     );
 
@@ -403,6 +442,8 @@ fn test_stake_unstake_within_same_epoch() {
             staking_epoch_start_block_height: 360, // next epoch
             pending_staking_amount: Uint128::zero(),
             pending_unstaking_amount: Uint128::zero(),
+            batched_staking_amount: Uint128::zero(),
+            last_batch_epoch_start_block_height: None,
         }
     );
 
@@ -459,6 +500,8 @@ fn test_multi_unstaker_single_epoch() {
             staking_epoch_start_block_height: 0,
             pending_staking_amount: Uint128::new(100_000_000),
             pending_unstaking_amount: Uint128::zero(),
+            batched_staking_amount: Uint128::zero(),
+            last_batch_epoch_start_block_height: None,
         }
     );
 
@@ -496,12 +539,14 @@ fn test_multi_unstaker_single_epoch() {
             staking_epoch_start_block_height: 360,
             pending_staking_amount: Uint128::new(100_000_000),
             pending_unstaking_amount: Uint128::zero(),
+            batched_staking_amount: Uint128::zero(),
+            last_batch_epoch_start_block_height: None,
         }
     );
 
     let _res = app.next_epoch();
 
-    let validators: Vec<lst_common::validator::ValidatorResponse> = tc
+    let response: lst_common::validator::ValidatorsDelegationResponse = tc
         .validator_registry
         .query(
             &app,
@@ -509,7 +554,7 @@ fn test_multi_unstaker_single_epoch() {
         )
         .unwrap();
 
-    for validator in validators {
+    for validator in response.validators {
         // 200 stakers, each staking 1_000_000 BABY, total 200_000_000 BABY
         // delegated equally to 10 validators, each validator should have 2_000_000 BABY delegated
         let delegated_amnt = validator.total_delegated;
@@ -529,6 +574,8 @@ fn test_multi_unstaker_single_epoch() {
             staking_epoch_start_block_height: 720,
             pending_staking_amount: Uint128::zero(),
             pending_unstaking_amount: Uint128::zero(),
+            batched_staking_amount: Uint128::zero(),
+            last_batch_epoch_start_block_height: None,
         }
     );
 
@@ -597,7 +644,9 @@ fn test_multi_unstaker_single_epoch() {
             staking_epoch_start_block_height: 2520,
             pending_staking_amount: Uint128::zero(),
             pending_unstaking_amount: Uint128::new(199_000_000), // <- the 1st unstaker gets
-                                                                 // batched with batch id 1,
+            batched_staking_amount: Uint128::zero(),
+            last_batch_epoch_start_block_height: None,
+            // batched with batch id 1,
         }
     );
 
@@ -727,6 +776,8 @@ fn test_multi_unstaker_multi_epoch() {
             staking_epoch_start_block_height: 0,
             pending_staking_amount: Uint128::new(200_000_000),
             pending_unstaking_amount: Uint128::zero(),
+            batched_staking_amount: Uint128::zero(),
+            last_batch_epoch_start_block_height: None,
         }
     );
 
@@ -741,10 +792,12 @@ fn test_multi_unstaker_multi_epoch() {
             staking_epoch_start_block_height: 360,
             pending_staking_amount: Uint128::zero(),
             pending_unstaking_amount: Uint128::zero(),
+            batched_staking_amount: Uint128::zero(),
+            last_batch_epoch_start_block_height: None,
         }
     );
 
-    let validators: Vec<lst_common::validator::ValidatorResponse> = tc
+    let response: lst_common::validator::ValidatorsDelegationResponse = tc
         .validator_registry
         .query(
             &app,
@@ -752,7 +805,7 @@ fn test_multi_unstaker_multi_epoch() {
         )
         .unwrap();
 
-    for validator in validators {
+    for validator in response.validators {
         // 200 stakers, each staking 1_000_000 BABY, total 200_000_000 BABY
         // delegated equally to 10 validators, each validator should have 2_000_000 BABY delegated
         let delegated_amnt = validator.total_delegated;
@@ -868,5 +921,1555 @@ fn test_multi_unstaker_multi_epoch() {
     }
 }
 
+#[test]
+fn test_unstake_drains_soft_removed_validator() {
+    let (mut app, tc, validators) = instantiate();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+
+    // staker stake 10_000_000 BABY, 1_000_000 BABY delegated to each validator
+    app.send_tokens(owner.clone(), staker.clone(), &coins(10_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(10_000_000, DENOM))
+        .unwrap();
+    let _res = app.next_epoch();
+
+    // soft remove validator1
+    let (soft_removed, _) = validators[0].clone();
+    tc.validator_registry
+        .execute(
+            &mut app,
+            &owner,
+            &SoftRemoveValidator {
+                address: soft_removed.to_string(),
+                reason: None,
+            },
+        )
+        .unwrap();
+
+    // staker unstake 1_500_000 LST
+    tc.lst_token
+        .execute(
+            &mut app,
+            &staker,
+            &IncreaseAllowance {
+                spender: tc.staking_hub.addr().to_string(),
+                amount: Uint128::new(1_500_000),
+                expires: None,
+            },
+        )
+        .unwrap();
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &staker,
+            &Unstake {
+                amount: Uint128::new(1_500_000),
+            },
+        )
+        .unwrap();
+
+    let _res = app.next_many_epochs(3);
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::ProcessUndelegations {},
+        )
+        .unwrap();
+    let _res = app.next_epoch();
+
+    // the soft-removed validator is drained first, the rest is undelegated from the active validators
+    let delegations = app
+        .wrap()
+        .query_all_delegations(tc.staking_hub.addr())
+        .unwrap();
+    assert!(delegations
+        .iter()
+        .all(|delegation| delegation.validator != soft_removed.to_string()));
+
+    let total_delegated: u128 = delegations
+        .iter()
+        .map(|delegation| delegation.amount.amount.u128())
+        .sum();
+    assert_eq!(total_delegated, 8_500_000);
+
+    // the delegation ledger of the hub matches the chain once the epoch has ended
+    let ledger: Vec<ValidatorDelegationResponse> =
+        tc.staking_hub.query(&app, &Delegations {}).unwrap();
+    assert_eq!(ledger.len(), delegations.len());
+    for delegation in delegations {
+        let entry = ledger
+            .iter()
+            .find(|entry| entry.validator == delegation.validator)
+            .unwrap();
+        assert_eq!(entry.delegated, delegation.amount.amount);
+        assert_eq!(entry.bonded, delegation.amount.amount);
+    }
+}
+
+#[test]
+fn test_batched_delegation() {
+    let (mut app, tc, _validators) = instantiate();
+
+    let owner = app.api().addr_make("owner");
+    let staker1 = app.api().addr_make("staker1");
+    let staker2 = app.api().addr_make("staker2");
+    let keeper = app.api().addr_make("keeper");
+
+    // enable batched delegation
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &UpdateParams {
+                pause: None,
+                epoch_length: None,
+                unstaking_period: None,
+                batched_delegation: Some(true),
+            },
+        )
+        .unwrap();
+
+    app.send_tokens(owner.clone(), staker1.clone(), &coins(1_000_000, DENOM))
+        .unwrap();
+    app.send_tokens(owner.clone(), staker2.clone(), &coins(600_000, DENOM))
+        .unwrap();
+
+    // both stakers stake, the stake is held in the hub until DelegatePending
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker1, &Stake {}, coins(1_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker2, &Stake {}, coins(500_000, DENOM))
+        .unwrap();
+
+    let balance: BalanceResponse = tc
+        .lst_token
+        .query(
+            &app,
+            &cw20_base::msg::QueryMsg::Balance {
+                address: staker2.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance, Uint128::new(500_000));
+
+    let delegations = app
+        .wrap()
+        .query_all_delegations(tc.staking_hub.addr())
+        .unwrap();
+    assert!(delegations.is_empty());
+
+    let pending: PendingDelegationRes = tc.staking_hub.query(&app, &PendingDelegation {}).unwrap();
+    assert_eq!(pending.batched_staking_amount, Uint128::new(1_500_000));
+    assert_eq!(pending.pending_staking_amount, Uint128::zero());
+
+    // anyone can delegate the batched stake once per staking epoch
+    tc.staking_hub
+        .execute(&mut app, &keeper, &DelegatePending {})
+        .unwrap();
+
+    // the delegations are recorded in the ledger and settle on chain at the end of the epoch
+    let ledger: Vec<ValidatorDelegationResponse> =
+        tc.staking_hub.query(&app, &Delegations {}).unwrap();
+    assert_eq!(ledger.len(), 10);
+    let total_delegated: u128 = ledger.iter().map(|entry| entry.delegated.u128()).sum();
+    assert_eq!(total_delegated, 1_500_000);
+
+    let pending: PendingDelegationRes = tc.staking_hub.query(&app, &PendingDelegation {}).unwrap();
+    assert_eq!(pending.batched_staking_amount, Uint128::zero());
+    assert_eq!(pending.pending_staking_amount, Uint128::new(1_500_000));
+
+    // nothing left to delegate
+    let err = tc
+        .staking_hub
+        .execute(&mut app, &keeper, &DelegatePending {})
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "No pending stake to delegate");
+
+    // new stake within the same staking epoch waits for the next one
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker2, &Stake {}, coins(100_000, DENOM))
+        .unwrap();
+    let err = tc
+        .staking_hub
+        .execute(&mut app, &keeper, &DelegatePending {})
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Pending stake has already been delegated in this staking epoch"
+    );
+
+    let _res = app.next_epoch();
+    let delegations = app
+        .wrap()
+        .query_all_delegations(tc.staking_hub.addr())
+        .unwrap();
+    assert_eq!(delegations.len(), 10);
+
+    tc.staking_hub
+        .execute(&mut app, &keeper, &DelegatePending {})
+        .unwrap();
+    let _res = app.next_epoch();
+
+    let total_delegated: u128 = app
+        .wrap()
+        .query_all_delegations(tc.staking_hub.addr())
+        .unwrap()
+        .iter()
+        .map(|delegation| delegation.amount.amount.u128())
+        .sum();
+    assert_eq!(total_delegated, 1_600_000);
+
+    // holding stake in the hub does not move the exchange rate
+    let exchange_rate: Decimal = tc.staking_hub.query(&app, &ExchangeRate {}).unwrap();
+    assert_eq!(exchange_rate, Decimal::one());
+}
+
+#[test]
+fn test_unstake_netted_against_pending_stake() {
+    let (mut app, tc, _validators) = instantiate();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+    let unstaker = app.api().addr_make("unstaker");
+
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &UpdateParams {
+                pause: None,
+                epoch_length: None,
+                unstaking_period: None,
+                batched_delegation: Some(true),
+            },
+        )
+        .unwrap();
+
+    // unstaker stakes 1_000_000 BABY which is delegated in the first epoch
+    app.send_tokens(owner.clone(), unstaker.clone(), &coins(1_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &unstaker, &Stake {}, coins(1_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute(&mut app, &owner, &DelegatePending {})
+        .unwrap();
+    let _res = app.next_epoch();
+
+    tc.lst_token
+        .execute(
+            &mut app,
+            &unstaker,
+            &IncreaseAllowance {
+                spender: tc.staking_hub.addr().to_string(),
+                amount: Uint128::new(700_000),
+                expires: None,
+            },
+        )
+        .unwrap();
+
+    // new stake of 300_000 BABY waits to be delegated
+    app.send_tokens(owner.clone(), staker.clone(), &coins(300_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(300_000, DENOM))
+        .unwrap();
+
+    // the first batch is fully paid out of the pending stake
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &unstaker,
+            &Unstake {
+                amount: Uint128::new(200_000),
+            },
+        )
+        .unwrap();
+    let _res = app.next_many_epochs(3);
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::ProcessUndelegations {},
+        )
+        .unwrap();
+
+    let all_history: lst_common::hub::AllHistoryResponse = tc
+        .staking_hub
+        .query(
+            &app,
+            &lst_common::hub::QueryMsg::AllHistory {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(all_history.history.len(), 1);
+    assert!(all_history.history[0].released);
+    assert_eq!(all_history.history[0].netted_amount, Uint128::new(200_000));
+
+    let pending: PendingDelegationRes = tc.staking_hub.query(&app, &PendingDelegation {}).unwrap();
+    assert_eq!(pending.batched_staking_amount, Uint128::new(100_000));
+    assert_eq!(pending.pending_unstaking_amount, Uint128::zero());
+
+    // nothing was undelegated, the unstaker withdraws without waiting for the unbonding
+    let _res = app.next_epoch();
+    let total_delegated: u128 = app
+        .wrap()
+        .query_all_delegations(tc.staking_hub.addr())
+        .unwrap()
+        .iter()
+        .map(|delegation| delegation.amount.amount.u128())
+        .sum();
+    assert_eq!(total_delegated, 1_000_000);
+
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &unstaker,
+            &lst_common::hub::ExecuteMsg::WithdrawUnstaked {},
+        )
+        .unwrap();
+    let balance = app.wrap().query_balance(&unstaker, DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::new(200_000));
+
+    // the second batch is partially netted, the rest is undelegated
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &unstaker,
+            &Unstake {
+                amount: Uint128::new(500_000),
+            },
+        )
+        .unwrap();
+    let _res = app.next_many_epochs(3);
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::ProcessUndelegations {},
+        )
+        .unwrap();
+
+    let all_history: lst_common::hub::AllHistoryResponse = tc
+        .staking_hub
+        .query(
+            &app,
+            &lst_common::hub::QueryMsg::AllHistory {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(all_history.history.len(), 2);
+    assert!(!all_history.history[1].released);
+    assert_eq!(all_history.history[1].netted_amount, Uint128::new(100_000));
+
+    let pending: PendingDelegationRes = tc.staking_hub.query(&app, &PendingDelegation {}).unwrap();
+    assert_eq!(pending.batched_staking_amount, Uint128::zero());
+    assert_eq!(pending.pending_unstaking_amount, Uint128::new(400_000));
+
+    let _res = app.next_epoch();
+    let total_delegated: u128 = app
+        .wrap()
+        .query_all_delegations(tc.staking_hub.addr())
+        .unwrap()
+        .iter()
+        .map(|delegation| delegation.amount.amount.u128())
+        .sum();
+    assert_eq!(total_delegated, 600_000);
+
+    // the unstaker withdraws the rest once the undelegation has unbonded
+    let _res = app.next_many_epochs(25);
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &unstaker,
+            &lst_common::hub::ExecuteMsg::WithdrawUnstaked {},
+        )
+        .unwrap();
+    let balance = app.wrap().query_balance(&unstaker, DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::new(700_000));
+}
+
+#[test]
+fn test_bounded_delegation_fan_out() {
+    let (mut app, tc, _validators) = instantiate();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+
+    // a stake is delegated to at most 3 validators, with at least 200_000 BABY each
+    tc.validator_registry
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::validator::ExecuteMsg::UpdateConfig {
+                owner: None,
+                hub_contract: None,
+                rebalance_cooldown: None,
+                delegation_strategy: None,
+                soft_removal_grace_period: None,
+                max_validator_share: None,
+                max_commission: None,
+                max_validators_per_delegation: Some(3),
+                min_delegation_chunk: Some(Uint128::new(200_000)),
+            },
+        )
+        .unwrap();
+
+    app.send_tokens(owner.clone(), staker.clone(), &coins(1_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(600_000, DENOM))
+        .unwrap();
+    let _res = app.next_epoch();
+
+    let delegations = app
+        .wrap()
+        .query_all_delegations(tc.staking_hub.addr())
+        .unwrap();
+    assert_eq!(delegations.len(), 3);
+    assert!(delegations
+        .iter()
+        .all(|delegation| delegation.amount.amount == Uint128::new(200_000)));
+
+    // a smaller stake goes to a single validator, to the ones without any stake first
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(300_000, DENOM))
+        .unwrap();
+    let _res = app.next_epoch();
+
+    let delegations = app
+        .wrap()
+        .query_all_delegations(tc.staking_hub.addr())
+        .unwrap();
+    assert_eq!(delegations.len(), 4);
+    let total_delegated: u128 = delegations
+        .iter()
+        .map(|delegation| delegation.amount.amount.u128())
+        .sum();
+    assert_eq!(total_delegated, 900_000);
+}
+
+#[test]
+fn test_slashing_attribution() {
+    let (mut app, tc, validators) = instantiate();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+
+    // staker stake 10_000_000 BABY, 1_000_000 BABY delegated to each validator
+    app.send_tokens(owner.clone(), staker.clone(), &coins(10_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(10_000_000, DENOM))
+        .unwrap();
+    let _res = app.next_epoch();
+
+    // validator1 is slashed by 10%
+    let (slashed, _) = validators[0].clone();
+    app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+        validator: slashed.to_string(),
+        percentage: Decimal::percent(10),
+    }))
+    .unwrap();
+
+    let res = tc
+        .staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::CheckSlashing {},
+        )
+        .unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|event| event.ty == "wasm-ValidatorSlashed"
+            && event
+                .attributes
+                .iter()
+                .any(|attr| attr.key == "slashed_amount" && attr.value == "100000")));
+
+    let history: SlashingHistoryResponse = tc
+        .staking_hub
+        .query(
+            &app,
+            &SlashingHistory {
+                validator: None,
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(history.history.len(), 1);
+    assert_eq!(history.history[0].id, 0);
+    assert_eq!(history.history[0].validator, slashed.to_string());
+    assert_eq!(history.history[0].amount, Uint128::new(100_000));
+    assert_eq!(history.history[0].height, app.block_info().height);
+
+    // the slashing is only recorded once
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::CheckSlashing {},
+        )
+        .unwrap();
+    let history: SlashingHistoryResponse = tc
+        .staking_hub
+        .query(
+            &app,
+            &SlashingHistory {
+                validator: Some(validators[1].0.to_string()),
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(history.history.is_empty());
+
+    // the registry soft-removed the slashed validator
+    let soft_removed: Vec<lst_common::validator::SoftRemovedValidator> = tc
+        .validator_registry
+        .query(&app, &lst_common::validator::QueryMsg::SoftRemovedList {})
+        .unwrap();
+    assert_eq!(soft_removed.len(), 1);
+    assert_eq!(soft_removed[0].address, slashed.to_string());
+
+    // the loss is spread over the LST holders
+    let exchange_rate: Decimal = tc.staking_hub.query(&app, &ExchangeRate {}).unwrap();
+    assert_eq!(
+        exchange_rate,
+        Decimal::from_ratio(9_900_000u128, 10_000_000u128)
+    );
+}
+
+// cw-multi-test does not meter gas, the number of staking messages shows how the delegation paths scale
+#[test]
+fn test_staking_messages_scale_with_validator_set() {
+    let count_events =
+        |res: &AppResponse, ty: &str| res.events.iter().filter(|event| event.ty == ty).count();
+
+    for count in [10, 50, 150] {
+        let (mut app, tc, _validators) = instantiate_with_validators(count);
+
+        let owner = app.api().addr_make("owner");
+        let staker = app.api().addr_make("staker");
+        app.send_tokens(owner.clone(), staker.clone(), &coins(100_000_000, DENOM))
+            .unwrap();
+
+        // one delegation per validator, whatever the size of the set
+        let res = tc
+            .staking_hub
+            .execute_with_funds(&mut app, &staker, &Stake {}, coins(90_000_000, DENOM))
+            .unwrap();
+        assert_eq!(count_events(&res, "delegate"), count);
+        let _res = app.next_epoch();
+
+        let delegations = app
+            .wrap()
+            .query_all_delegations(tc.staking_hub.addr())
+            .unwrap();
+        assert_eq!(delegations.len(), count);
+
+        // an uneven stake leaves the validators unbalanced before the undelegation
+        let res = tc
+            .staking_hub
+            .execute_with_funds(&mut app, &staker, &Stake {}, coins(1_234_567, DENOM))
+            .unwrap();
+        assert_eq!(count_events(&res, "delegate"), count);
+        let _res = app.next_epoch();
+
+        tc.lst_token
+            .execute(
+                &mut app,
+                &staker,
+                &IncreaseAllowance {
+                    spender: tc.staking_hub.addr().to_string(),
+                    amount: Uint128::new(45_000_000),
+                    expires: None,
+                },
+            )
+            .unwrap();
+
+        // the first batch is undelegated by the unstake itself, one undelegation per validator
+        let res = tc
+            .staking_hub
+            .execute(
+                &mut app,
+                &staker,
+                &Unstake {
+                    amount: Uint128::new(45_000_000),
+                },
+            )
+            .unwrap();
+        assert_eq!(count_events(&res, "undelegate"), count);
+        let _res = app.next_many_epochs(3);
+
+        let res = tc
+            .staking_hub
+            .execute(
+                &mut app,
+                &owner,
+                &lst_common::hub::ExecuteMsg::ProcessUndelegations {},
+            )
+            .unwrap();
+        assert_eq!(count_events(&res, "undelegate"), 0);
+        let _res = app.next_epoch();
+
+        let total_delegated: u128 = app
+            .wrap()
+            .query_all_delegations(tc.staking_hub.addr())
+            .unwrap()
+            .iter()
+            .map(|delegation| delegation.amount.amount.u128())
+            .sum();
+        assert_eq!(total_delegated, 91_234_567 - 45_000_000);
+    }
+}
+
+#[test]
+fn test_non_staking_denom_rewards() {
+    let (mut app, tc, _validators) = instantiate();
+    let env = mock_env();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+    let treasury = app.api().addr_make("treasury");
+    let dispatcher = tc.reward_dispatcher.addr().clone();
+
+    app.send_tokens(owner.clone(), staker.clone(), &coins(10_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(10_000_000, DENOM))
+        .unwrap();
+    // delegations settle, then accrue rewards over an epoch
+    let _res = app.next_many_epochs(2);
+
+    // router swaps 1 uatom for 2 BABY
+    let router = MockRouterContract::new(&mut app, &env, None);
+    router
+        .execute(
+            &mut app,
+            &owner,
+            &MockRouterExecuteMsg::SetRate {
+                offer_denom: "uatom".to_string(),
+                ask_denom: DENOM.to_string(),
+                rate: Decimal::from_ratio(2u128, 1u128),
+            },
+        )
+        .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: router.addr().to_string(),
+        amount: coins(1_000_000, DENOM),
+    }))
+    .unwrap();
+
+    tc.reward_dispatcher
+        .execute(
+            &mut app,
+            &owner,
+            &DispatcherExecuteMsg::UpdateConfig {
+                owner: None,
+                hub_contract: None,
+                fee_recipients: None,
+                fee_rate: None,
+                router: Some(router.addr().to_string()),
+                treasury: Some(treasury.to_string()),
+                reward_stream_window: None,
+                fee_as_shares: None,
+                performance_fee: None,
+            },
+        )
+        .unwrap();
+
+    for (denom, policy) in [
+        (
+            "uatom",
+            DenomPolicy::Swap {
+                min_price: Decimal::from_ratio(198u128, 100u128),
+            },
+        ),
+        ("uairdrop", DenomPolicy::Forward {}),
+        ("uhold", DenomPolicy::Hold {}),
+    ] {
+        tc.reward_dispatcher
+            .execute(
+                &mut app,
+                &owner,
+                &DispatcherExecuteMsg::SetDenomPolicy {
+                    denom: denom.to_string(),
+                    policy: Some(policy),
+                },
+            )
+            .unwrap();
+    }
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: dispatcher.to_string(),
+        amount: vec![
+            coin(1_000, "uairdrop"),
+            coin(1_000, "uatom"),
+            coin(1_000, "uhold"),
+        ],
+    }))
+    .unwrap();
+
+    // swapped BABY comes back to the dispatcher, airdrop goes to treasury, held denom stays
+    tc.staking_hub
+        .execute(&mut app, &owner, &UpdateGlobalIndex {})
+        .unwrap();
+
+    let balance = |app: &BabylonApp, addr: &Addr, denom: &str| {
+        app.wrap().query_balance(addr, denom).unwrap().amount.u128()
+    };
+    assert_eq!(balance(&app, &dispatcher, DENOM), 2_000);
+    assert_eq!(balance(&app, &dispatcher, "uatom"), 0);
+    assert_eq!(balance(&app, router.addr(), "uatom"), 1_000);
+    assert_eq!(balance(&app, &treasury, "uairdrop"), 1_000);
+    assert_eq!(balance(&app, &dispatcher, "uhold"), 1_000);
+
+    // swap proceeds are restaked with the next dispatch
+    let _res = app.next_epoch();
+    tc.staking_hub
+        .execute(&mut app, &owner, &UpdateGlobalIndex {})
+        .unwrap();
+    assert_eq!(balance(&app, &dispatcher, DENOM), 0);
+
+    // a swap below the minimum price fails without blocking the dispatch, even when the router quotes it
+    router
+        .execute(
+            &mut app,
+            &owner,
+            &MockRouterExecuteMsg::SetRate {
+                offer_denom: "uatom".to_string(),
+                ask_denom: DENOM.to_string(),
+                rate: Decimal::from_ratio(3u128, 2u128),
+            },
+        )
+        .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: dispatcher.to_string(),
+        amount: coins(1_000, "uatom"),
+    }))
+    .unwrap();
+    let _res = app.next_epoch();
+
+    let res = tc
+        .staking_hub
+        .execute(&mut app, &owner, &UpdateGlobalIndex {})
+        .unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "action" && attr.value == "swap_failed")));
+    assert_eq!(balance(&app, &dispatcher, "uatom"), 1_000);
+    assert_eq!(balance(&app, &dispatcher, DENOM), 0);
+}
+
+#[test]
+fn test_dispatch_history() {
+    let (mut app, tc, _validators) = instantiate();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+
+    app.send_tokens(owner.clone(), staker.clone(), &coins(10_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(10_000_000, DENOM))
+        .unwrap();
+    let _res = app.next_many_epochs(2);
+
+    tc.staking_hub
+        .execute(&mut app, &owner, &UpdateGlobalIndex {})
+        .unwrap();
+
+    let history: DispatchHistoryResponse = tc
+        .reward_dispatcher
+        .query(
+            &app,
+            &DispatchHistory {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(history.history.len(), 1);
+
+    // the record holds the exchange rate after the rewards were restaked
+    let record = history.history[0].clone();
+    let exchange_rate: Decimal = tc.staking_hub.query(&app, &ExchangeRate {}).unwrap();
+    assert!(exchange_rate > Decimal::one());
+    assert_eq!(record.exchange_rate, exchange_rate);
+    assert_eq!(record.height, app.block_info().height);
+    assert_eq!(record.gross, record.fee + record.net);
+
+    let stats: RewardStats = tc
+        .reward_dispatcher
+        .query(&app, &DispatcherQueryMsg::RewardStats {})
+        .unwrap();
+    assert_eq!(stats.dispatch_count, 1);
+    assert_eq!(stats.total_restaked, record.net);
+    assert_eq!(stats.total_fees, record.fee);
+}
+
+#[test]
+fn test_fee_as_lst_shares() {
+    let (mut app, tc, _validators) = instantiate();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+    let fee_addr = app.api().addr_make("fee_addr");
+
+    tc.reward_dispatcher
+        .execute(
+            &mut app,
+            &owner,
+            &DispatcherExecuteMsg::UpdateConfig {
+                owner: None,
+                hub_contract: None,
+                fee_recipients: None,
+                fee_rate: Some(Decimal::percent(10)),
+                router: None,
+                treasury: None,
+                reward_stream_window: None,
+                fee_as_shares: Some(true),
+                performance_fee: None,
+            },
+        )
+        .unwrap();
+
+    app.send_tokens(owner.clone(), staker.clone(), &coins(10_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(10_000_000, DENOM))
+        .unwrap();
+    let _res = app.next_many_epochs(2);
+
+    tc.staking_hub
+        .execute(&mut app, &owner, &UpdateGlobalIndex {})
+        .unwrap();
+
+    let history: DispatchHistoryResponse = tc
+        .reward_dispatcher
+        .query(
+            &app,
+            &DispatchHistory {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let record = history.history[0].clone();
+    assert!(!record.fee.is_zero());
+
+    // the whole reward is restaked and no fee is paid out in BABY
+    let state: lst_common::hub::State = tc
+        .staking_hub
+        .query(&app, &lst_common::hub::QueryMsg::State {})
+        .unwrap();
+    assert_eq!(
+        state.total_staked_amount,
+        Uint128::new(10_000_000) + record.gross
+    );
+    assert!(app
+        .wrap()
+        .query_balance(&fee_addr, DENOM)
+        .unwrap()
+        .amount
+        .is_zero());
+
+    // the exchange rate accounts for the minted fee shares
+    let token_info: cw20::TokenInfoResponse = tc
+        .lst_token
+        .query(&app, &cw20_base::msg::QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(
+        state.lst_exchange_rate,
+        Decimal::from_ratio(state.total_staked_amount, token_info.total_supply)
+    );
+    assert!(state.lst_exchange_rate > Decimal::one());
+
+    // the fee recipient holds shares worth the fee
+    let BalanceResponse { balance } = tc
+        .lst_token
+        .query(
+            &app,
+            &cw20_base::msg::QueryMsg::Balance {
+                address: fee_addr.to_string(),
+            },
+        )
+        .unwrap();
+    let value = balance.mul_floor(state.lst_exchange_rate);
+    assert!(record.fee - value <= Uint128::one());
+}
+
+#[test]
+fn test_pending_rewards() {
+    let (mut app, tc, _validators) = instantiate();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+
+    tc.reward_dispatcher
+        .execute(
+            &mut app,
+            &owner,
+            &DispatcherExecuteMsg::UpdateConfig {
+                owner: None,
+                hub_contract: None,
+                fee_recipients: None,
+                fee_rate: Some(Decimal::percent(10)),
+                router: None,
+                treasury: None,
+                reward_stream_window: None,
+                fee_as_shares: None,
+                performance_fee: None,
+            },
+        )
+        .unwrap();
+
+    app.send_tokens(owner.clone(), staker.clone(), &coins(10_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(10_000_000, DENOM))
+        .unwrap();
+    let _res = app.next_many_epochs(2);
+
+    let pending: PendingRewardsResponse = tc.staking_hub.query(&app, &PendingRewards {}).unwrap();
+    assert_eq!(pending.rewards.len(), 10);
+    assert_eq!(
+        pending.total,
+        pending
+            .rewards
+            .iter()
+            .map(|(_, amount)| amount)
+            .sum::<Uint128>()
+    );
+    assert!(!pending.total.is_zero());
+    assert_eq!(pending.fee, pending.total.mul_ceil(Decimal::percent(10)));
+    assert_eq!(pending.net, pending.total - pending.fee);
+
+    // the preview matches the dispatch
+    tc.staking_hub
+        .execute(&mut app, &owner, &UpdateGlobalIndex {})
+        .unwrap();
+
+    let history: DispatchHistoryResponse = tc
+        .reward_dispatcher
+        .query(
+            &app,
+            &DispatchHistory {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let record = history.history[0].clone();
+    assert_eq!(record.gross, pending.total);
+    assert_eq!(record.fee, pending.fee);
+    assert_eq!(record.exchange_rate, pending.projected_exchange_rate);
+
+    // nothing accrues within the same block
+    let pending: PendingRewardsResponse = tc.staking_hub.query(&app, &PendingRewards {}).unwrap();
+    assert!(pending.total.is_zero());
+
+    // rewards held by the dispatcher, streams and fee shares are previewed like the dispatch handles them
+    tc.reward_dispatcher
+        .execute(
+            &mut app,
+            &owner,
+            &DispatcherExecuteMsg::UpdateConfig {
+                owner: None,
+                hub_contract: None,
+                fee_recipients: None,
+                fee_rate: None,
+                router: None,
+                treasury: None,
+                reward_stream_window: Some(100),
+                fee_as_shares: Some(true),
+                performance_fee: None,
+            },
+        )
+        .unwrap();
+    app.send_tokens(
+        owner.clone(),
+        tc.reward_dispatcher.addr().clone(),
+        &coins(1_000, DENOM),
+    )
+    .unwrap();
+
+    for dispatch_id in [1, 2] {
+        let _res = app.next_epoch();
+        let pending: PendingRewardsResponse =
+            tc.staking_hub.query(&app, &PendingRewards {}).unwrap();
+        if dispatch_id == 1 {
+            assert_eq!(pending.held, Uint128::new(1_000));
+        }
+        assert_eq!(
+            pending.fee,
+            (pending.total + pending.held).mul_ceil(Decimal::percent(10))
+        );
+
+        tc.staking_hub
+            .execute(&mut app, &owner, &UpdateGlobalIndex {})
+            .unwrap();
+
+        let history: DispatchHistoryResponse = tc
+            .reward_dispatcher
+            .query(
+                &app,
+                &DispatchHistory {
+                    start_from: Some(dispatch_id),
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let record = history.history[0].clone();
+        assert_eq!(record.gross, pending.total + pending.held);
+        assert_eq!(record.fee, pending.fee);
+        assert_eq!(record.exchange_rate, pending.projected_exchange_rate);
+    }
+}
+
+#[test]
+fn test_performance_fee_high_water_mark() {
+    let (mut app, tc, validators) = instantiate();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+
+    tc.reward_dispatcher
+        .execute(
+            &mut app,
+            &owner,
+            &DispatcherExecuteMsg::UpdateConfig {
+                owner: None,
+                hub_contract: None,
+                fee_recipients: None,
+                fee_rate: Some(Decimal::percent(10)),
+                router: None,
+                treasury: None,
+                reward_stream_window: None,
+                fee_as_shares: None,
+                performance_fee: Some(true),
+            },
+        )
+        .unwrap();
+
+    app.send_tokens(owner.clone(), staker.clone(), &coins(10_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(10_000_000, DENOM))
+        .unwrap();
+    let _res = app.next_many_epochs(2);
+
+    // above the high-water mark the full fee is charged and the mark follows the rate
+    let res = tc
+        .staking_hub
+        .execute(&mut app, &owner, &UpdateGlobalIndex {})
+        .unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|event| event.ty == "wasm-HighWaterMarkUpdated"));
+
+    let history: DispatchHistoryResponse = tc
+        .reward_dispatcher
+        .query(
+            &app,
+            &DispatchHistory {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let record = history.history[0].clone();
+    assert_eq!(record.fee, record.gross.mul_ceil(Decimal::percent(10)));
+
+    let high_water_mark: HighWaterMarkResponse =
+        tc.staking_hub.query(&app, &HighWaterMark {}).unwrap();
+    assert_eq!(high_water_mark.high_water_mark, record.exchange_rate);
+    assert!(high_water_mark.recovery_amount.is_zero());
+
+    // slashing lowers the rate but not the high-water mark
+    let (slashed, _) = validators[0].clone();
+    app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+        validator: slashed.to_string(),
+        percentage: Decimal::percent(10),
+    }))
+    .unwrap();
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::CheckSlashing {},
+        )
+        .unwrap();
+
+    let high_water_mark: HighWaterMarkResponse =
+        tc.staking_hub.query(&app, &HighWaterMark {}).unwrap();
+    assert_eq!(high_water_mark.high_water_mark, record.exchange_rate);
+    assert!(high_water_mark.exchange_rate < high_water_mark.high_water_mark);
+    assert!(high_water_mark.recovery_amount >= Uint128::new(100_000));
+
+    // no fee is charged while the rate recovers
+    let _res = app.next_epoch();
+    let pending: PendingRewardsResponse = tc.staking_hub.query(&app, &PendingRewards {}).unwrap();
+    assert!(!pending.total.is_zero());
+    assert!(pending.fee.is_zero());
+
+    let res = tc
+        .staking_hub
+        .execute(&mut app, &owner, &UpdateGlobalIndex {})
+        .unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "performance_fee" && attr.value == "0")));
+
+    let history: DispatchHistoryResponse = tc
+        .reward_dispatcher
+        .query(
+            &app,
+            &DispatchHistory {
+                start_from: Some(1),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(history.history[0].fee, Uint128::zero());
+    assert_eq!(history.history[0].net, pending.total);
+}
+
+#[test]
+fn test_insurance_fund_top_up() {
+    let (mut app, tc, validators) = instantiate();
+    let env = mock_env();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+    let fee_addr = app.api().addr_make("fee_addr");
+
+    // covers half of a loss, at most 40_000 BABY per claim and once a day
+    let insurance_fund = InsuranceFundContract::new(
+        &mut app,
+        &env,
+        Some(InsuranceInstantiateMsg {
+            hub_contract: tc.staking_hub.addr().to_string(),
+            denom: DENOM.to_string(),
+            coverage_rate: Decimal::percent(50),
+            max_payout: Uint128::new(40_000),
+            cooldown: 24 * 60 * 60,
+        }),
+    );
+
+    // the fund receives half of the protocol fee
+    tc.reward_dispatcher
+        .execute(
+            &mut app,
+            &owner,
+            &DispatcherExecuteMsg::UpdateConfig {
+                owner: None,
+                hub_contract: None,
+                fee_recipients: Some(vec![
+                    (fee_addr.to_string(), Decimal::percent(50)),
+                    (insurance_fund.addr().to_string(), Decimal::percent(50)),
+                ]),
+                fee_rate: Some(Decimal::percent(10)),
+                router: None,
+                treasury: None,
+                reward_stream_window: None,
+                fee_as_shares: None,
+                performance_fee: None,
+            },
+        )
+        .unwrap();
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &UpdateConfig {
+                owner: None,
+                lst_token: None,
+                validator_registry: None,
+                reward_dispatcher: None,
+                insurance_fund: Some(insurance_fund.addr().to_string()),
+            },
+        )
+        .unwrap();
+
+    app.send_tokens(owner.clone(), staker.clone(), &coins(10_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(10_000_000, DENOM))
+        .unwrap();
+    let _res = app.next_many_epochs(2);
+
+    tc.staking_hub
+        .execute(&mut app, &owner, &UpdateGlobalIndex {})
+        .unwrap();
+    let fee_share = app
+        .wrap()
+        .query_balance(insurance_fund.addr(), DENOM)
+        .unwrap()
+        .amount;
+    assert!(!fee_share.is_zero());
+
+    // only the insurance fund can top up the hub
+    let err = tc
+        .staking_hub
+        .execute_with_funds(&mut app, &owner, &InsuranceTopUp {}, coins(1_000, DENOM))
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: insurance_fund.addr().to_string(),
+        amount: coins(100_000, DENOM),
+    }))
+    .unwrap();
+
+    let state: lst_common::hub::State = tc
+        .staking_hub
+        .query(&app, &lst_common::hub::QueryMsg::State {})
+        .unwrap();
+    let staked_before = state.total_staked_amount;
+
+    // a 100_000 BABY loss is covered up to the payout cap
+    app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+        validator: validators[0].0.to_string(),
+        percentage: Decimal::percent(10),
+    }))
+    .unwrap();
+    let res = tc
+        .staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::CheckSlashing {},
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|event| event.ty == "wasm"
+        && event
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "insurance_top_up")));
+
+    let state: lst_common::hub::State = tc
+        .staking_hub
+        .query(&app, &lst_common::hub::QueryMsg::State {})
+        .unwrap();
+    assert_eq!(
+        state.total_staked_amount,
+        staked_before - Uint128::new(100_000) + Uint128::new(40_000)
+    );
+
+    // a second slash within the cooldown is recorded without a payout
+    let staked_before = state.total_staked_amount;
+    app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+        validator: validators[1].0.to_string(),
+        percentage: Decimal::percent(10),
+    }))
+    .unwrap();
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::CheckSlashing {},
+        )
+        .unwrap();
+
+    let state: lst_common::hub::State = tc
+        .staking_hub
+        .query(&app, &lst_common::hub::QueryMsg::State {})
+        .unwrap();
+    assert!(state.total_staked_amount < staked_before);
+
+    let claims: ClaimsResponse = insurance_fund
+        .query(
+            &app,
+            &InsuranceQueryMsg::Claims {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(claims.claims.len(), 2);
+    assert_eq!(claims.claims[0].loss, Uint128::new(100_000));
+    assert_eq!(claims.claims[0].payout, Uint128::new(40_000));
+    assert_eq!(
+        claims.claims[1].loss,
+        staked_before - state.total_staked_amount
+    );
+    assert!(claims.claims[1].payout.is_zero());
+
+    // once the cooldown is over the fund pays out again
+    app.update_block(|block| block.time = block.time.plus_seconds(24 * 60 * 60));
+    app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+        validator: validators[2].0.to_string(),
+        percentage: Decimal::percent(10),
+    }))
+    .unwrap();
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::CheckSlashing {},
+        )
+        .unwrap();
+
+    let fund_state: FundStateResponse = insurance_fund
+        .query(&app, &InsuranceQueryMsg::FundState {})
+        .unwrap();
+    assert_eq!(fund_state.claim_count, 3);
+    assert_eq!(fund_state.total_paid_out, Uint128::new(80_000));
+    assert_eq!(
+        fund_state.balance,
+        fee_share + Uint128::new(100_000) - Uint128::new(80_000)
+    );
+    assert_eq!(
+        fund_state.cooldown_end,
+        Some(app.block_info().time.seconds() + 24 * 60 * 60)
+    );
+}
+
+#[test]
+fn test_insurance_top_up_credited_before_minting() {
+    let (mut app, tc, validators) = instantiate();
+    let env = mock_env();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+
+    // covers the whole loss without a cooldown
+    let insurance_fund = InsuranceFundContract::new(
+        &mut app,
+        &env,
+        Some(InsuranceInstantiateMsg {
+            hub_contract: tc.staking_hub.addr().to_string(),
+            denom: DENOM.to_string(),
+            coverage_rate: Decimal::one(),
+            max_payout: Uint128::new(1_000_000),
+            cooldown: 0,
+        }),
+    );
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &UpdateConfig {
+                owner: None,
+                lst_token: None,
+                validator_registry: None,
+                reward_dispatcher: None,
+                insurance_fund: Some(insurance_fund.addr().to_string()),
+            },
+        )
+        .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: insurance_fund.addr().to_string(),
+        amount: coins(1_000_000, DENOM),
+    }))
+    .unwrap();
+
+    app.send_tokens(owner.clone(), staker.clone(), &coins(12_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(10_000_000, DENOM))
+        .unwrap();
+    let _res = app.next_many_epochs(2);
+
+    // the slash is detected by a stake, which mints at the covered rate
+    app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+        validator: validators[0].0.to_string(),
+        percentage: Decimal::percent(10),
+    }))
+    .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(1_000_000, DENOM))
+        .unwrap();
+
+    let BalanceResponse { balance } = tc
+        .lst_token
+        .query(
+            &app,
+            &cw20_base::msg::QueryMsg::Balance {
+                address: staker.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance, Uint128::new(11_000_000));
+
+    let exchange_rate: Decimal = tc.staking_hub.query(&app, &ExchangeRate {}).unwrap();
+    assert_eq!(exchange_rate, Decimal::one());
+    let claims: ClaimsResponse = insurance_fund
+        .query(
+            &app,
+            &InsuranceQueryMsg::Claims {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(claims.claims.len(), 1);
+    assert_eq!(claims.claims[0].payout, claims.claims[0].loss);
+
+    // the paid top-up is not taken for another slash
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::CheckSlashing {},
+        )
+        .unwrap();
+    let exchange_rate: Decimal = tc.staking_hub.query(&app, &ExchangeRate {}).unwrap();
+    assert_eq!(exchange_rate, Decimal::one());
+
+    // a fund that fails the claim does not fail the stake, the credit is taken back instead
+    insurance_fund
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::insurance_msg::ExecuteMsg::UpdateConfig {
+                owner: None,
+                hub_contract: Some(owner.to_string()),
+                coverage_rate: None,
+                max_payout: None,
+                cooldown: None,
+            },
+        )
+        .unwrap();
+    app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+        validator: validators[1].0.to_string(),
+        percentage: Decimal::percent(10),
+    }))
+    .unwrap();
+    let res = tc
+        .staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(1_000_000, DENOM))
+        .unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "action" && attr.value == "insurance_top_up_failed")));
+
+    let exchange_rate: Decimal = tc.staking_hub.query(&app, &ExchangeRate {}).unwrap();
+    assert!(exchange_rate < Decimal::one());
+    let claims: ClaimsResponse = insurance_fund
+        .query(
+            &app,
+            &InsuranceQueryMsg::Claims {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(claims.claims.len(), 1);
+}
+
+#[test]
+fn test_unstake_netted_against_stake_without_batched_delegation() {
+    let (mut app, tc, _validators) = instantiate();
+
+    let owner = app.api().addr_make("owner");
+    let staker = app.api().addr_make("staker");
+    let unstaker = app.api().addr_make("unstaker");
+
+    // unstaker stakes 1_000_000 BABY which is delegated right away
+    app.send_tokens(owner.clone(), unstaker.clone(), &coins(1_000_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &unstaker, &Stake {}, coins(1_000_000, DENOM))
+        .unwrap();
+    let _res = app.next_epoch();
+
+    tc.lst_token
+        .execute(
+            &mut app,
+            &unstaker,
+            &IncreaseAllowance {
+                spender: tc.staking_hub.addr().to_string(),
+                amount: Uint128::new(200_000),
+                expires: None,
+            },
+        )
+        .unwrap();
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &unstaker,
+            &Unstake {
+                amount: Uint128::new(200_000),
+            },
+        )
+        .unwrap();
+
+    // new stake covering the open batch is held back, only the rest is delegated
+    app.send_tokens(owner.clone(), staker.clone(), &coins(300_000, DENOM))
+        .unwrap();
+    tc.staking_hub
+        .execute_with_funds(&mut app, &staker, &Stake {}, coins(300_000, DENOM))
+        .unwrap();
+    let pending: PendingDelegationRes = tc.staking_hub.query(&app, &PendingDelegation {}).unwrap();
+    assert_eq!(pending.batched_staking_amount, Uint128::new(200_000));
+    assert_eq!(pending.pending_staking_amount, Uint128::new(100_000));
+
+    // the batch is fully paid out of the held stake
+    let _res = app.next_many_epochs(3);
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &owner,
+            &lst_common::hub::ExecuteMsg::ProcessUndelegations {},
+        )
+        .unwrap();
+
+    let all_history: lst_common::hub::AllHistoryResponse = tc
+        .staking_hub
+        .query(
+            &app,
+            &lst_common::hub::QueryMsg::AllHistory {
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(all_history.history[0].released);
+    assert_eq!(all_history.history[0].netted_amount, Uint128::new(200_000));
+
+    let pending: PendingDelegationRes = tc.staking_hub.query(&app, &PendingDelegation {}).unwrap();
+    assert_eq!(pending.batched_staking_amount, Uint128::zero());
+    assert_eq!(pending.pending_unstaking_amount, Uint128::zero());
+
+    // nothing was undelegated, the unstaker withdraws without waiting for the unbonding
+    let total_delegated: u128 = app
+        .wrap()
+        .query_all_delegations(tc.staking_hub.addr())
+        .unwrap()
+        .iter()
+        .map(|delegation| delegation.amount.amount.u128())
+        .sum();
+    assert_eq!(total_delegated, 1_100_000);
+
+    tc.staking_hub
+        .execute(
+            &mut app,
+            &unstaker,
+            &lst_common::hub::ExecuteMsg::WithdrawUnstaked {},
+        )
+        .unwrap();
+    let balance = app.wrap().query_balance(&unstaker, DENOM).unwrap();
+    assert_eq!(balance.amount, Uint128::new(200_000));
+}
+
 #[test]
 fn test_multi_unstaker_multi_epoch_undelegation_throttle() {}
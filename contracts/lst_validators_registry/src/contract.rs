@@ -1,32 +1,62 @@
 use std::collections::HashMap;
 
 use cosmwasm_std::{
-    to_json_binary, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, Uint128,
-    WasmMsg,
+    to_json_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    QuerierWrapper, Response, StdResult, Storage, Uint128, Validator as StdValidator, WasmMsg,
 };
 use cw2::set_contract_version;
+use cw_storage_plus::Bound;
 
 use crate::{
-    helper::fetch_validator_info,
-    state::{CONFIG, VALIDATOR_EXCLUDE_LIST, VALIDATOR_REGISTRY},
+    curation::{curate_validators, query_curation_config, update_curation_config},
+    gauge::{
+        query_gauge_config, query_gauge_results, query_gauge_votes, tally_votes,
+        update_gauge_config, vote,
+    },
+    helper::{
+        fetch_hub_delegations, fetch_unbonding_period, fetch_validator_info,
+        fetch_validator_status, ValidatorStatus,
+    },
+    state::{
+        is_receiving_redelegation, prune_matured_redelegations, record_validator_set_change,
+        save_pending_redelegation, CONFIG, LAST_REBALANCE_TIME, LAST_SNAPSHOT_TIME,
+        PENDING_REDELEGATIONS, REDELEGATION_CURSOR, SOFT_REMOVED_VALIDATORS,
+        VALIDATOR_APPLICATIONS, VALIDATOR_EXCLUDE_LIST, VALIDATOR_METADATA, VALIDATOR_REGISTRY,
+        VALIDATOR_SET_CHANGES, VALIDATOR_SNAPSHOTS, VALIDATOR_WEIGHTS,
+    },
 };
 use lst_common::address::{convert_addr_by_prefix, VALIDATOR_ADDR_PREFIX};
 use lst_common::{
-    calculate_delegations,
+    delegation::{
+        calculate_rebalance_moves, capped_delegations, capped_targets, concentration_cap,
+        DelegationStrategy, DelegationStrategyKind,
+    },
     hub::ExecuteMsg::RedelegateProxy,
     to_checked_address,
     types::{LstResult, StdCoin},
-    validator::{Config, ExecuteMsg, InstantiateMsg, QueryMsg, Validator, ValidatorResponse},
-    ContractError, MigrateMsg,
+    validator::{
+        default_max_commission, default_max_validator_share, default_validator_weight, Config,
+        DelegationDeviationResponse, ExecuteMsg, InstantiateMsg, OverCapValidatorResponse,
+        PendingRedelegation, QueryMsg, SoftRemovedValidator, Validator, ValidatorApplication,
+        ValidatorInfoResponse, ValidatorMetadata, ValidatorResponse, ValidatorSetAction,
+        ValidatorSetChange, ValidatorSnapshot, ValidatorsDelegationResponse,
+        DEFAULT_REBALANCE_COOLDOWN, DEFAULT_SOFT_REMOVAL_GRACE_PERIOD,
+        MAX_APPLICATION_DETAILS_LENGTH, MAX_REASON_LENGTH, SNAPSHOT_INTERVAL,
+    },
+    ContractError, MigrateMsg, SignedInt, ValidatorError,
 };
 
 const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Number of excluded validators handled by a single process redelegations call
+const DEFAULT_REDELEGATION_LIMIT: u32 = 10;
+const MAX_REDELEGATION_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> LstResult<Response> {
@@ -38,8 +68,15 @@ pub fn instantiate(
     CONFIG.save(
         deps.storage,
         &Config {
-            owner: info.sender,
+            owner: info.sender.clone(),
             hub_contract,
+            rebalance_cooldown: DEFAULT_REBALANCE_COOLDOWN,
+            delegation_strategy: DelegationStrategyKind::default(),
+            soft_removal_grace_period: DEFAULT_SOFT_REMOVAL_GRACE_PERIOD,
+            max_validator_share: default_max_validator_share(),
+            max_commission: default_max_commission(),
+            max_validators_per_delegation: 0,
+            min_delegation_chunk: Uint128::zero(),
         },
     )?;
 
@@ -51,16 +88,33 @@ pub fn instantiate(
             fetch_validator_info(&deps.querier, validator_addr)
                 .ok()
                 .flatten()
-                .map(|info| {
+                .map(|validator_info| {
                     VALIDATOR_REGISTRY
                         .save(
                             deps.storage,
-                            info.address.as_bytes(),
+                            validator_info.address.as_bytes(),
                             &Validator {
-                                address: info.address.clone(),
+                                address: validator_info.address.clone(),
                             },
                         )
-                        .ok()
+                        .ok();
+                    save_validator_metadata(
+                        deps.storage,
+                        &deps.querier,
+                        &validator_info,
+                        &info.sender,
+                        env.block.time.seconds(),
+                    )
+                    .ok();
+                    record_validator_set_change(
+                        deps.storage,
+                        &env,
+                        ValidatorSetAction::Add,
+                        validator_info.address,
+                        info.sender.clone(),
+                        None,
+                    )
+                    .ok()
                 })
         })
         .count();
@@ -71,13 +125,82 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> LstResult<Response> {
     match msg {
-        ExecuteMsg::AddValidator { validator } => add_validator(deps, env, info, validator),
-        ExecuteMsg::RemoveValidator { address } => remove_validator(deps, info, address),
+        ExecuteMsg::AddValidator { validator, reason } => {
+            add_validator(deps, env, info, validator, reason)
+        }
+        ExecuteMsg::RemoveValidator { address, reason } => {
+            remove_validator(deps, env, info, address, reason)
+        }
+        ExecuteMsg::SoftRemoveValidator { address, reason } => {
+            soft_remove_validator(deps, env, info, address, reason)
+        }
         ExecuteMsg::UpdateConfig {
             owner,
             hub_contract,
-        } => update_config(deps, env, info, owner, hub_contract),
-        ExecuteMsg::ProcessRedelegations {} => process_redelegations(deps),
+            rebalance_cooldown,
+            delegation_strategy,
+            soft_removal_grace_period,
+            max_validator_share,
+            max_commission,
+            max_validators_per_delegation,
+            min_delegation_chunk,
+        } => update_config(
+            deps,
+            info,
+            owner,
+            hub_contract,
+            rebalance_cooldown,
+            delegation_strategy,
+            soft_removal_grace_period,
+            max_validator_share,
+            max_commission,
+            max_validators_per_delegation,
+            min_delegation_chunk,
+        ),
+        ExecuteMsg::ProcessRedelegations { limit } => process_redelegations(deps, env, limit),
+        ExecuteMsg::Rebalance { max_moves } => rebalance(deps, env, max_moves),
+        ExecuteMsg::RecordSnapshots {} => record_snapshots(deps, env),
+        ExecuteMsg::UpdateValidatorWeight { address, weight } => {
+            update_validator_weight(deps, info, address, weight)
+        }
+        ExecuteMsg::ApplyValidator {
+            address,
+            details,
+            contact,
+        } => apply_validator(deps, env, info, address, details, contact),
+        ExecuteMsg::ApproveApplication { address } => approve_application(deps, env, info, address),
+        ExecuteMsg::RejectApplication { address, reason } => {
+            reject_application(deps, info, address, reason)
+        }
+        ExecuteMsg::WithdrawApplication { address } => withdraw_application(deps, info, address),
+        ExecuteMsg::Vote { votes } => vote(deps, info, votes),
+        ExecuteMsg::TallyVotes {} => tally_votes(deps, env),
+        ExecuteMsg::UpdateGaugeConfig {
+            period,
+            min_weight,
+            max_weight,
+        } => update_gauge_config(deps, info, period, min_weight, max_weight),
+        ExecuteMsg::CurateValidators {} => curate_validators(deps, env),
+        ExecuteMsg::ReportSlashing {
+            validator,
+            amount,
+            height,
+        } => report_slashing(deps, env, info, validator, amount, height),
+        ExecuteMsg::UpdateCurationConfig {
+            enabled,
+            set_size,
+            skip_top_share,
+            max_commission,
+            period,
+        } => update_curation_config(
+            deps,
+            info,
+            enabled,
+            set_size,
+            skip_top_share,
+            max_commission,
+            period,
+        ),
     }
 }
 
@@ -91,21 +214,22 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
 
 fn add_validator(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     validator: Validator,
+    reason: Option<String>,
 ) -> LstResult<Response> {
     let Config { owner, .. } = CONFIG.load(deps.storage)?;
 
     if info.sender != owner {
         return Err(ContractError::Unauthorized {});
     }
+    validate_reason(&reason)?;
 
     let validator_addr = convert_addr_by_prefix(validator.address.as_str(), VALIDATOR_ADDR_PREFIX);
     let validator_info = fetch_validator_info(&deps.querier, validator_addr)?;
-    if let Some(info) = validator_info {
-        VALIDATOR_REGISTRY.save(deps.storage, info.address.as_bytes(), &validator)?;
-        VALIDATOR_EXCLUDE_LIST.remove(deps.storage, info.address);
+    if let Some(validator_info) = validator_info {
+        register_validator(deps, &env, &validator, &validator_info, info.sender, reason)?;
     }
 
     Ok(Response::default()
@@ -113,115 +237,830 @@ fn add_validator(
         .add_attribute("validator", validator.address.to_string()))
 }
 
+// Add a validator known to the chain to the registry, clearing any pending removal
+pub(crate) fn register_validator(
+    deps: DepsMut,
+    env: &Env,
+    validator: &Validator,
+    validator_info: &StdValidator,
+    added_by: Addr,
+    reason: Option<String>,
+) -> LstResult<()> {
+    VALIDATOR_REGISTRY.save(deps.storage, validator_info.address.as_bytes(), validator)?;
+    save_validator_metadata(
+        deps.storage,
+        &deps.querier,
+        validator_info,
+        &added_by,
+        env.block.time.seconds(),
+    )?;
+    SOFT_REMOVED_VALIDATORS.remove(deps.storage, validator_info.address.clone());
+    VALIDATOR_EXCLUDE_LIST.remove(deps.storage, validator_info.address.clone());
+    record_validator_set_change(
+        deps.storage,
+        env,
+        ValidatorSetAction::Add,
+        validator_info.address.clone(),
+        added_by,
+        reason,
+    )?;
+    Ok(())
+}
+
+// An applicant must be bonded, not jailed and within the commission limit of the registry
+fn check_validator_eligibility(
+    querier: &QuerierWrapper,
+    max_commission: Decimal,
+    validator_addr: String,
+) -> LstResult<(StdValidator, ValidatorStatus)> {
+    let validator_info = fetch_validator_info(querier, validator_addr.clone())?
+        .ok_or(ValidatorError::ValidatorNotBonded)?;
+    let status = fetch_validator_status(querier, validator_addr)?
+        .ok_or(ValidatorError::ValidatorNotFound)?;
+
+    if !status.bonded {
+        return Err(ValidatorError::ValidatorNotBonded.into());
+    }
+    if status.jailed {
+        return Err(ValidatorError::ValidatorJailed.into());
+    }
+    if validator_info.commission > max_commission {
+        return Err(ValidatorError::CommissionTooHigh.into());
+    }
+    Ok((validator_info, status))
+}
+
+// Only the operator of the validator can apply, from the account matching its operator address
+fn apply_validator(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    details: String,
+    contact: Option<String>,
+) -> LstResult<Response> {
+    let Config { max_commission, .. } = CONFIG.load(deps.storage)?;
+
+    let validator_addr = convert_addr_by_prefix(address.as_str(), VALIDATOR_ADDR_PREFIX);
+    if convert_addr_by_prefix(info.sender.as_str(), VALIDATOR_ADDR_PREFIX) != validator_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+    if details.len() > MAX_APPLICATION_DETAILS_LENGTH
+        || contact
+            .as_ref()
+            .is_some_and(|contact| contact.len() > MAX_APPLICATION_DETAILS_LENGTH)
+    {
+        return Err(ValidatorError::ApplicationDetailsTooLong.into());
+    }
+    if VALIDATOR_REGISTRY.has(deps.storage, validator_addr.as_bytes()) {
+        return Err(ValidatorError::ValidatorAlreadyRegistered.into());
+    }
+    if VALIDATOR_APPLICATIONS.has(deps.storage, &validator_addr) {
+        return Err(ValidatorError::ApplicationAlreadyExists.into());
+    }
+
+    let (validator_info, status) =
+        check_validator_eligibility(&deps.querier, max_commission, validator_addr.clone())?;
+
+    VALIDATOR_APPLICATIONS.save(
+        deps.storage,
+        &validator_addr,
+        &ValidatorApplication {
+            validator: validator_addr.clone(),
+            applicant: info.sender,
+            moniker: status.moniker,
+            details,
+            contact,
+            commission: validator_info.commission,
+            submitted_at: env.block.time.seconds(),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "apply_validator")
+        .add_attribute("validator", validator_addr))
+}
+
+// The validator is checked again, as it may have been jailed or raised its commission since it applied
+fn approve_application(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+) -> LstResult<Response> {
+    let Config {
+        owner,
+        max_commission,
+        ..
+    } = CONFIG.load(deps.storage)?;
+
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let validator_addr = convert_addr_by_prefix(address.as_str(), VALIDATOR_ADDR_PREFIX);
+    if !VALIDATOR_APPLICATIONS.has(deps.storage, &validator_addr) {
+        return Err(ValidatorError::ApplicationNotFound.into());
+    }
+    let (validator_info, _) =
+        check_validator_eligibility(&deps.querier, max_commission, validator_addr.clone())?;
+
+    VALIDATOR_APPLICATIONS.remove(deps.storage, &validator_addr);
+    register_validator(
+        deps,
+        &env,
+        &Validator {
+            address: validator_addr.clone(),
+        },
+        &validator_info,
+        info.sender,
+        Some("application approved".to_string()),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "approve_application")
+        .add_attribute("validator", validator_addr))
+}
+
+fn reject_application(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    reason: Option<String>,
+) -> LstResult<Response> {
+    let Config { owner, .. } = CONFIG.load(deps.storage)?;
+
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    validate_reason(&reason)?;
+
+    let validator_addr = convert_addr_by_prefix(address.as_str(), VALIDATOR_ADDR_PREFIX);
+    if !VALIDATOR_APPLICATIONS.has(deps.storage, &validator_addr) {
+        return Err(ValidatorError::ApplicationNotFound.into());
+    }
+    VALIDATOR_APPLICATIONS.remove(deps.storage, &validator_addr);
+
+    let mut res = Response::new()
+        .add_attribute("action", "reject_application")
+        .add_attribute("validator", validator_addr);
+    if let Some(reason) = reason {
+        res = res.add_attribute("reason", reason);
+    }
+    Ok(res)
+}
+
+fn withdraw_application(deps: DepsMut, info: MessageInfo, address: String) -> LstResult<Response> {
+    let validator_addr = convert_addr_by_prefix(address.as_str(), VALIDATOR_ADDR_PREFIX);
+    let application = VALIDATOR_APPLICATIONS
+        .may_load(deps.storage, &validator_addr)?
+        .ok_or(ValidatorError::ApplicationNotFound)?;
+
+    if info.sender != application.applicant {
+        return Err(ContractError::Unauthorized {});
+    }
+    VALIDATOR_APPLICATIONS.remove(deps.storage, &validator_addr);
+
+    Ok(Response::new()
+        .add_attribute("action", "withdraw_application")
+        .add_attribute("validator", validator_addr))
+}
+
+// Record the metadata of a validator added to the registry.
+// The moniker is informative only, so it is left empty when the chain doesn't expose it
+fn save_validator_metadata(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    validator_info: &StdValidator,
+    added_by: &Addr,
+    admission_time: u64,
+) -> LstResult<()> {
+    let moniker = fetch_validator_status(querier, validator_info.address.clone())
+        .ok()
+        .flatten()
+        .map(|status| status.moniker)
+        .unwrap_or_default();
+
+    VALIDATOR_METADATA.save(
+        storage,
+        &validator_info.address,
+        &ValidatorMetadata {
+            moniker,
+            commission_at_admission: validator_info.commission,
+            admission_time,
+            added_by: added_by.clone(),
+        },
+    )?;
+    Ok(())
+}
+
+// The reason is kept in the validator set change log, so its size is bounded
+fn validate_reason(reason: &Option<String>) -> LstResult<()> {
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.len() > MAX_REASON_LENGTH)
+    {
+        return Err(ValidatorError::ReasonTooLong.into());
+    }
+    Ok(())
+}
+
 fn remove_validator(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     validator_addr: String,
+    reason: Option<String>,
 ) -> LstResult<Response> {
     let Config { owner, .. } = CONFIG.load(deps.storage)?;
 
     if info.sender != owner {
         return Err(ContractError::Unauthorized {});
     }
+    validate_reason(&reason)?;
 
     let validator_operator_addr =
         convert_addr_by_prefix(validator_addr.as_str(), VALIDATOR_ADDR_PREFIX);
-
-    VALIDATOR_REGISTRY.remove(deps.storage, validator_operator_addr.as_bytes());
-    VALIDATOR_EXCLUDE_LIST.save(deps.storage, validator_operator_addr, &true)?;
+    deregister_validator(
+        deps.storage,
+        &env,
+        validator_operator_addr,
+        info.sender,
+        reason,
+    )?;
 
     Ok(Response::new()
         .add_attribute("action", "remove_validator")
         .add_attribute("validator", validator_addr))
 }
 
-fn process_redelegations(deps: DepsMut) -> LstResult<Response> {
-    if VALIDATOR_EXCLUDE_LIST.is_empty(deps.storage) {
-        return Ok(Response::default());
+// Remove a validator from the registry and exclude it, so that its stake is redelegated
+pub(crate) fn deregister_validator(
+    storage: &mut dyn Storage,
+    env: &Env,
+    validator_addr: String,
+    removed_by: Addr,
+    reason: Option<String>,
+) -> LstResult<()> {
+    VALIDATOR_REGISTRY.remove(storage, validator_addr.as_bytes());
+    VALIDATOR_WEIGHTS.remove(storage, validator_addr.as_bytes());
+    VALIDATOR_METADATA.remove(storage, &validator_addr);
+    SOFT_REMOVED_VALIDATORS.remove(storage, validator_addr.clone());
+    VALIDATOR_EXCLUDE_LIST.save(storage, validator_addr.clone(), &true)?;
+    record_validator_set_change(
+        storage,
+        env,
+        ValidatorSetAction::Remove,
+        validator_addr,
+        removed_by,
+        reason,
+    )?;
+    Ok(())
+}
+
+// Stop delegating to the validator but keep its stake, so that unstaking drains it first.
+// Its remaining stake is redelegated by process_redelegations once the grace period is over
+fn soft_remove_validator(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    validator_addr: String,
+    reason: Option<String>,
+) -> LstResult<Response> {
+    let Config { owner, .. } = CONFIG.load(deps.storage)?;
+
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
     }
+    validate_reason(&reason)?;
+
+    let validator_operator_addr =
+        convert_addr_by_prefix(validator_addr.as_str(), VALIDATOR_ADDR_PREFIX);
+    if !VALIDATOR_REGISTRY.has(deps.storage, validator_operator_addr.as_bytes()) {
+        return Err(ValidatorError::ValidatorNotFound.into());
+    }
+
+    soft_exclude_validator(
+        deps.storage,
+        &env,
+        validator_operator_addr,
+        info.sender,
+        reason,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "soft_remove_validator")
+        .add_attribute("validator", validator_addr))
+}
+
+// Move a validator of the registry to the soft-removed list, its stake is drained by unstaking first
+fn soft_exclude_validator(
+    storage: &mut dyn Storage,
+    env: &Env,
+    validator_addr: String,
+    removed_by: Addr,
+    reason: Option<String>,
+) -> LstResult<()> {
+    VALIDATOR_REGISTRY.remove(storage, validator_addr.as_bytes());
+    VALIDATOR_WEIGHTS.remove(storage, validator_addr.as_bytes());
+    SOFT_REMOVED_VALIDATORS.save(storage, validator_addr.clone(), &env.block.time.seconds())?;
+    record_validator_set_change(
+        storage,
+        env,
+        ValidatorSetAction::SoftRemove,
+        validator_addr,
+        removed_by,
+        reason,
+    )?;
+    Ok(())
+}
 
+// Soft-remove a validator the hub found slashed, only the hub can report a slashing.
+// A validator that is no longer in the registry is left as it is
+fn report_slashing(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    validator_addr: String,
+    amount: Uint128,
+    height: u64,
+) -> LstResult<Response> {
     let Config { hub_contract, .. } = CONFIG.load(deps.storage)?;
 
-    let mut delegations = HashMap::<String, StdCoin>::new();
-    deps.querier
-        .query_all_delegations(&hub_contract)?
-        .into_iter()
-        .for_each(|delegation| {
-            delegations.insert(delegation.validator, delegation.amount);
-        });
+    if info.sender != hub_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "report_slashing")
+        .add_attribute("validator", validator_addr.clone())
+        .add_attribute("amount", amount)
+        .add_attribute("height", height.to_string());
+
+    if VALIDATOR_REGISTRY.has(deps.storage, validator_addr.as_bytes()) {
+        soft_exclude_validator(
+            deps.storage,
+            &env,
+            validator_addr,
+            info.sender,
+            Some(format!("slashed at height {}", height)),
+        )?;
+        res = res.add_attribute("soft_removed", "true");
+    }
+
+    Ok(res)
+}
+
+// Redelegate the stake of the excluded validators to the active validators.
+// At most `limit` excluded validators are handled per call, continuing after the stored cursor.
+// A validator that is still receiving a redelegation cannot redelegate onward, so it is deferred until it matures
+// and a validator that is fully drained is removed from the exclude list
+fn process_redelegations(deps: DepsMut, env: Env, limit: Option<u32>) -> LstResult<Response> {
+    let Config {
+        hub_contract,
+        delegation_strategy,
+        soft_removal_grace_period,
+        max_validator_share,
+        ..
+    } = CONFIG.load(deps.storage)?;
 
-    let mut active_validator_delegations: Vec<ValidatorResponse> = VALIDATOR_REGISTRY
+    let current_time = env.block.time.seconds();
+
+    // soft-removed validators past their grace period are hard removed, by the registry itself
+    let soft_removals = SOFT_REMOVED_VALIDATORS
         .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-        .map(|data| {
-            let val_address = data?.1.address;
-            Ok(ValidatorResponse {
-                address: val_address.clone(),
-                total_delegated: delegations
-                    .get(&val_address)
-                    .map(|coin| coin.amount)
-                    .unwrap_or(Uint128::zero()),
-            })
+        .collect::<StdResult<Vec<_>>>()?;
+    let expired_soft_removals = soft_removals
+        .into_iter()
+        .filter(|(_, removal_time)| {
+            removal_time.saturating_add(soft_removal_grace_period) <= current_time
         })
-        .collect::<LstResult<Vec<_>>>()?;
-    active_validator_delegations.sort_by(|v1, v2| v1.total_delegated.cmp(&v2.total_delegated));
+        .map(|(validator_addr, _)| validator_addr);
+    for validator_addr in expired_soft_removals {
+        SOFT_REMOVED_VALIDATORS.remove(deps.storage, validator_addr.clone());
+        VALIDATOR_EXCLUDE_LIST.save(deps.storage, validator_addr.clone(), &true)?;
+        record_validator_set_change(
+            deps.storage,
+            &env,
+            ValidatorSetAction::Remove,
+            validator_addr,
+            env.contract.address.clone(),
+            Some("soft removal grace period expired".to_string()),
+        )?;
+    }
+
+    if VALIDATOR_EXCLUDE_LIST.is_empty(deps.storage) && max_validator_share >= Decimal::one() {
+        return Ok(Response::default());
+    }
+
+    prune_matured_redelegations(deps.storage, current_time)?;
+    let completion_time = current_time + fetch_unbonding_period(&deps.querier, &hub_contract)?;
+
+    let delegations = query_hub_delegations(deps.as_ref(), &hub_contract)?;
+    let total_stake: Uint128 = delegations.values().sum();
+    let denom = deps.querier.query_bonded_denom()?;
+
+    // sorted once, the validators receiving a redelegation are moved back in place afterwards
+    let mut active_validator_delegations = registry_validators(deps.as_ref(), &delegations)?;
+    active_validator_delegations.sort_by_key(|v| v.total_delegated);
+
+    let limit = limit
+        .unwrap_or(DEFAULT_REDELEGATION_LIMIT)
+        .clamp(1, MAX_REDELEGATION_LIMIT) as usize;
+    let cursor = REDELEGATION_CURSOR.may_load(deps.storage)?;
+    let mut excluded_validators = VALIDATOR_EXCLUDE_LIST
+        .keys(
+            deps.storage,
+            cursor.map(Bound::exclusive),
+            None,
+            cosmwasm_std::Order::Ascending,
+        )
+        .take(limit + 1)
+        .collect::<StdResult<Vec<String>>>()?;
+
+    // continue after the last handled validator next time, or start over once the end is reached
+    if excluded_validators.len() > limit {
+        excluded_validators.truncate(limit);
+        REDELEGATION_CURSOR.save(deps.storage, &excluded_validators[limit - 1])?;
+    } else {
+        REDELEGATION_CURSOR.remove(deps.storage);
+    }
 
     let mut messages: Vec<CosmosMsg> = vec![];
+    let mut deferred: Vec<String> = vec![];
+    let mut drained: Vec<String> = vec![];
+    let mut failed: Vec<String> = vec![];
+
+    for validator_addr in excluded_validators {
+        let delegation = match delegations.get(&validator_addr) {
+            Some(delegation) if !delegation.is_zero() => *delegation,
+            _ => {
+                VALIDATOR_EXCLUDE_LIST.remove(deps.storage, validator_addr.clone());
+                drained.push(validator_addr);
+                continue;
+            }
+        };
 
-    VALIDATOR_EXCLUDE_LIST
-        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-        .for_each(|item| {
-            let (validator_addr, _) = item.ok().unwrap();
+        if active_validator_delegations.is_empty() {
+            return Err(ValidatorError::EmptyValidatorSet.into());
+        }
 
-            let validator_delegation = delegations.get(&validator_addr);
+        // a validator whose stake cannot be distributed is skipped so that it doesn't block the others
+        // the redelegated stake stays in the hub, so the total stake after the move is unchanged
+        let Ok(coin_distribution) = capped_delegations(
+            &delegation_strategy,
+            delegation,
+            active_validator_delegations.as_slice(),
+            total_stake - delegation,
+            max_validator_share,
+        ) else {
+            failed.push(validator_addr);
+            continue;
+        };
 
-            if let Some(delegation) = validator_delegation {
-                if delegation.amount.is_zero() {
-                    return;
-                }
+        match redelegate(
+            deps.storage,
+            &hub_contract,
+            &validator_addr,
+            &denom,
+            &coin_distribution,
+            &mut active_validator_delegations,
+            current_time,
+            completion_time,
+        ) {
+            Ok(msg) => messages.push(msg),
+            Err(ContractError::Validator(ValidatorError::RedelegationCooldownNotMet)) => {
+                deferred.push(validator_addr);
+                continue;
+            }
+            Err(err) => return Err(err),
+        }
+        restore_delegation_order(&mut active_validator_delegations, &coin_distribution);
+    }
 
-                let coin_distribution = calculate_delegations(
-                    delegation.amount,
-                    active_validator_delegations.as_slice(),
-                )
-                .unwrap();
-                let redelegations = active_validator_delegations
-                    .iter()
-                    .zip(coin_distribution.iter())
-                    .filter(|(_, amt)| !amt.is_zero())
-                    .map(|(val, amt)| {
-                        (
-                            val.address.clone(),
-                            Coin::new(amt.u128(), delegation.denom.as_str()),
-                        )
-                    })
-                    .collect::<Vec<_>>();
+    // the stake of the active validators above the concentration cap goes to the validators below it
+    let cap = concentration_cap(total_stake, max_validator_share);
+    let over_cap_validators = active_validator_delegations
+        .iter()
+        .filter(|validator| validator.total_delegated > cap)
+        .map(|validator| validator.address.clone())
+        .collect::<Vec<_>>();
+    let mut capped: Vec<String> = vec![];
+
+    for validator_addr in over_cap_validators {
+        let Some(position) = active_validator_delegations
+            .iter()
+            .position(|validator| validator.address == validator_addr)
+        else {
+            continue;
+        };
+        let mut recipients = active_validator_delegations.clone();
+        let source = recipients.remove(position);
+
+        // only what the other validators can take without going over the cap is moved
+        let headroom: Uint128 = recipients
+            .iter()
+            .map(|validator| cap.saturating_sub(validator.total_delegated))
+            .sum();
+        let excess = (source.total_delegated - cap).min(headroom);
+        if excess.is_zero() {
+            continue;
+        }
 
-                let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: hub_contract.to_string(),
-                    msg: to_json_binary(&RedelegateProxy {
-                        src_validator: validator_addr.clone(),
-                        redelegations,
-                    })
-                    .unwrap(),
-                    funds: vec![],
-                });
-                messages.push(msg);
+        let Ok(mut coin_distribution) = capped_delegations(
+            &delegation_strategy,
+            excess,
+            recipients.as_slice(),
+            total_stake - excess,
+            max_validator_share,
+        ) else {
+            failed.push(validator_addr);
+            continue;
+        };
+        coin_distribution.insert(position, Uint128::zero());
+
+        match redelegate(
+            deps.storage,
+            &hub_contract,
+            &validator_addr,
+            &denom,
+            &coin_distribution,
+            &mut active_validator_delegations,
+            current_time,
+            completion_time,
+        ) {
+            Ok(msg) => messages.push(msg),
+            Err(ContractError::Validator(ValidatorError::RedelegationCooldownNotMet)) => {
+                deferred.push(validator_addr);
+                continue;
             }
-        });
+            Err(err) => return Err(err),
+        }
+        capped.push(validator_addr);
+        active_validator_delegations[position].total_delegated -= excess;
+        coin_distribution[position] = excess;
+        restore_delegation_order(&mut active_validator_delegations, &coin_distribution);
+    }
+
+    let mut res = Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "process_redelegation");
+    if !deferred.is_empty() {
+        res = res.add_attribute("deferred", deferred.join(","));
+    }
+    if !drained.is_empty() {
+        res = res.add_attribute("drained", drained.join(","));
+    }
+    if !failed.is_empty() {
+        res = res.add_attribute("failed", failed.join(","));
+    }
+    if !capped.is_empty() {
+        res = res.add_attribute("capped", capped.join(","));
+    }
+
+    Ok(res)
+}
+
+// Build the redelegation of `src_validator` following `distribution` over the active validators.
+// The active validators are updated with the redelegated amounts and the redelegation is recorded as pending.
+// Fails if the source is still receiving a redelegation, which the chain would reject
+#[allow(clippy::too_many_arguments)]
+fn redelegate(
+    storage: &mut dyn Storage,
+    hub_contract: &Addr,
+    src_validator: &str,
+    denom: &str,
+    distribution: &[Uint128],
+    active_validator_delegations: &mut [ValidatorResponse],
+    current_time: u64,
+    completion_time: u64,
+) -> LstResult<CosmosMsg> {
+    if is_receiving_redelegation(storage, src_validator, current_time)? {
+        return Err(ValidatorError::RedelegationCooldownNotMet.into());
+    }
+
+    let mut redelegations = vec![];
+    for (val, amt) in active_validator_delegations
+        .iter_mut()
+        .zip(distribution.iter())
+    {
+        if amt.is_zero() {
+            continue;
+        }
+        val.total_delegated = val
+            .total_delegated
+            .checked_add(*amt)
+            .map_err(|e| ContractError::Overflow(e.to_string()))?;
+        redelegations.push((val.address.clone(), Coin::new(amt.u128(), denom)));
+    }
+
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: hub_contract.to_string(),
+        msg: to_json_binary(&RedelegateProxy {
+            src_validator: src_validator.to_string(),
+            redelegations: redelegations.clone(),
+        })?,
+        funds: vec![],
+    });
+
+    save_pending_redelegation(
+        storage,
+        PendingRedelegation {
+            src_validator: src_validator.to_string(),
+            redelegations,
+            timestamp: completion_time,
+        },
+    )?;
+    Ok(msg)
+}
+
+// Restore the ascending delegation order after the validators with a non-zero change had their delegation updated.
+// Ties keep their previous order, as a stable sort would, at the cost of sorting the changed validators only
+fn restore_delegation_order(validators: &mut Vec<ValidatorResponse>, changes: &[Uint128]) {
+    let (mut changed, unchanged): (Vec<_>, Vec<_>) = std::mem::take(validators)
+        .into_iter()
+        .enumerate()
+        .partition(|(index, _)| changes.get(*index).is_some_and(|change| !change.is_zero()));
+    changed.sort_by_key(|(index, validator)| (validator.total_delegated, *index));
+
+    let mut changed = changed.into_iter().peekable();
+    for (index, validator) in unchanged {
+        while let Some((_, next)) = changed.next_if(|(changed_index, next)| {
+            (next.total_delegated, *changed_index) < (validator.total_delegated, index)
+        }) {
+            validators.push(next);
+        }
+        validators.push(validator);
+    }
+    validators.extend(changed.map(|(_, validator)| validator));
+}
+
+// Redelegate from the validators above their target delegation to the validators below it
+// At most `max_moves` redelegations are issued and a rebalance can only happen once per cooldown
+fn rebalance(deps: DepsMut, env: Env, max_moves: u32) -> LstResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    let Config {
+        hub_contract,
+        rebalance_cooldown,
+        ..
+    } = config.clone();
+
+    let current_time = env.block.time.seconds();
+    if let Some(last_rebalance_time) = LAST_REBALANCE_TIME.may_load(deps.storage)? {
+        if current_time < last_rebalance_time.saturating_add(rebalance_cooldown) {
+            return Err(ValidatorError::RebalanceCooldownNotMet.into());
+        }
+    }
+
+    prune_matured_redelegations(deps.storage, current_time)?;
+
+    // validators still receiving a redelegation cannot be used as a source
+    let delegations = query_hub_delegations(deps.as_ref(), &hub_contract)?;
+    let validators = registry_validators(deps.as_ref(), &delegations)?;
+    let locked = validators
+        .iter()
+        .map(|validator| is_receiving_redelegation(deps.storage, &validator.address, current_time))
+        .collect::<StdResult<Vec<_>>>()?;
+    let targets = capped_target_delegations(&config, &validators, delegations.values().sum())?;
+    let moves = calculate_rebalance_moves(&validators, &targets, max_moves as usize, &locked);
+    if moves.is_empty() {
+        return Ok(Response::new()
+            .add_attribute("action", "rebalance")
+            .add_attribute("moves", "0"));
+    }
+
+    let denom = deps.querier.query_bonded_denom()?;
+    let completion_time = current_time + fetch_unbonding_period(&deps.querier, &hub_contract)?;
+
+    // group the moves by source validator, one redelegate proxy call per source
+    let mut redelegations: Vec<(usize, Vec<(String, StdCoin)>)> = vec![];
+    for (src, dst, amount) in moves.iter() {
+        let redelegation = (
+            validators[*dst].address.clone(),
+            Coin::new(*amount, denom.as_str()),
+        );
+        match redelegations.iter_mut().find(|(index, _)| index == src) {
+            Some((_, dst_redelegations)) => dst_redelegations.push(redelegation),
+            None => redelegations.push((*src, vec![redelegation])),
+        }
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (src, redelegations) in redelegations {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: hub_contract.to_string(),
+            msg: to_json_binary(&RedelegateProxy {
+                src_validator: validators[src].address.clone(),
+                redelegations: redelegations.clone(),
+            })?,
+            funds: vec![],
+        }));
+
+        save_pending_redelegation(
+            deps.storage,
+            PendingRedelegation {
+                src_validator: validators[src].address.clone(),
+                redelegations,
+                timestamp: completion_time,
+            },
+        )?;
+    }
+
+    LAST_REBALANCE_TIME.save(deps.storage, &current_time)?;
 
     Ok(Response::new()
         .add_messages(messages)
-        .add_attribute("action", "process_redelegation"))
+        .add_attribute("action", "rebalance")
+        .add_attribute("moves", moves.len().to_string()))
+}
+
+// Record the commission, jailed flag and voting power of every validator in the registry
+// Anyone can call this method, at most once per snapshot interval
+fn record_snapshots(deps: DepsMut, env: Env) -> LstResult<Response> {
+    let current_time = env.block.time.seconds();
+    if let Some(last_snapshot_time) = LAST_SNAPSHOT_TIME.may_load(deps.storage)? {
+        if current_time < last_snapshot_time + SNAPSHOT_INTERVAL {
+            return Err(ValidatorError::SnapshotIntervalNotMet.into());
+        }
+    }
+
+    let validators = VALIDATOR_REGISTRY
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| Ok(item?.1.address))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut recorded = 0u32;
+    for address in validators {
+        let validator_addr = convert_addr_by_prefix(address.as_str(), VALIDATOR_ADDR_PREFIX);
+        let Some(validator_info) = fetch_validator_info(&deps.querier, validator_addr.clone())?
+        else {
+            continue;
+        };
+        let Some(status) = fetch_validator_status(&deps.querier, validator_addr.clone())? else {
+            continue;
+        };
+
+        VALIDATOR_SNAPSHOTS.save(
+            deps.storage,
+            (&validator_addr, current_time),
+            &ValidatorSnapshot {
+                timestamp: current_time,
+                commission: validator_info.commission,
+                jailed: status.jailed,
+                voting_power: status.voting_power,
+            },
+        )?;
+        recorded += 1;
+    }
+
+    LAST_SNAPSHOT_TIME.save(deps.storage, &current_time)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "record_snapshots")
+        .add_attribute("validators", recorded.to_string()))
+}
+
+// Set the weight of a validator in the registry, used by the weighted delegation strategy
+// Only owner can execute the function
+fn update_validator_weight(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    weight: u64,
+) -> LstResult<Response> {
+    let Config { owner, .. } = CONFIG.load(deps.storage)?;
+
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let validator_operator_addr = convert_addr_by_prefix(address.as_str(), VALIDATOR_ADDR_PREFIX);
+    if !VALIDATOR_REGISTRY.has(deps.storage, validator_operator_addr.as_bytes()) {
+        return Err(ValidatorError::ValidatorNotFound.into());
+    }
+    VALIDATOR_WEIGHTS.save(deps.storage, validator_operator_addr.as_bytes(), &weight)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_validator_weight")
+        .add_attribute("validator", validator_operator_addr)
+        .add_attribute("weight", weight.to_string()))
 }
 
-// Update validator registry contract config. owner/hub_contract
+// Update validator registry contract config. owner/hub_contract/rebalance_cooldown/delegation_strategy/soft_removal_grace_period
 // Only owner can execute the function
+#[allow(clippy::too_many_arguments)]
 fn update_config(
     deps: DepsMut,
-    _env: Env,
     info: MessageInfo,
     owner: Option<String>,
     hub_contract: Option<String>,
+    rebalance_cooldown: Option<u64>,
+    delegation_strategy: Option<DelegationStrategyKind>,
+    soft_removal_grace_period: Option<u64>,
+    max_validator_share: Option<Decimal>,
+    max_commission: Option<Decimal>,
+    max_validators_per_delegation: Option<u32>,
+    min_delegation_chunk: Option<Uint128>,
 ) -> LstResult<Response> {
     let config = CONFIG.load(deps.storage)?;
 
@@ -250,50 +1089,400 @@ fn update_config(
         res = res.add_attribute("hub", hub_contract);
     }
 
-    Ok(res)
-}
+    if let Some(rebalance_cooldown) = rebalance_cooldown {
+        CONFIG.update(deps.storage, |mut old_config| -> LstResult<Config> {
+            old_config.rebalance_cooldown = rebalance_cooldown;
+            Ok(old_config)
+        })?;
+        res = res.add_attribute("rebalance_cooldown", rebalance_cooldown.to_string());
+    }
 
-#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> LstResult<Binary> {
-    match msg {
-        QueryMsg::Config {} => query_config(deps),
-        QueryMsg::ValidatorsDelegation {} => Ok(to_json_binary(&query_validators(deps)?)?),
-        QueryMsg::ExcludeList => Ok(to_json_binary(&query_exclude_list(deps)?)?),
+    if let Some(delegation_strategy) = delegation_strategy {
+        CONFIG.update(deps.storage, |mut old_config| -> LstResult<Config> {
+            old_config.delegation_strategy = delegation_strategy.clone();
+            Ok(old_config)
+        })?;
+        res = res.add_attribute("delegation_strategy", format!("{:?}", delegation_strategy));
     }
-}
 
-fn query_config(deps: Deps) -> LstResult<Binary> {
+    if let Some(soft_removal_grace_period) = soft_removal_grace_period {
+        CONFIG.update(deps.storage, |mut old_config| -> LstResult<Config> {
+            old_config.soft_removal_grace_period = soft_removal_grace_period;
+            Ok(old_config)
+        })?;
+        res = res.add_attribute(
+            "soft_removal_grace_period",
+            soft_removal_grace_period.to_string(),
+        );
+    }
+
+    if let Some(max_validator_share) = max_validator_share {
+        if max_validator_share.is_zero() || max_validator_share > Decimal::one() {
+            return Err(ValidatorError::InvalidMaxValidatorShare.into());
+        }
+        CONFIG.update(deps.storage, |mut old_config| -> LstResult<Config> {
+            old_config.max_validator_share = max_validator_share;
+            Ok(old_config)
+        })?;
+        res = res.add_attribute("max_validator_share", max_validator_share.to_string());
+    }
+
+    if let Some(max_commission) = max_commission {
+        if max_commission > Decimal::one() {
+            return Err(ValidatorError::InvalidMaxCommission.into());
+        }
+        CONFIG.update(deps.storage, |mut old_config| -> LstResult<Config> {
+            old_config.max_commission = max_commission;
+            Ok(old_config)
+        })?;
+        res = res.add_attribute("max_commission", max_commission.to_string());
+    }
+
+    if let Some(max_validators_per_delegation) = max_validators_per_delegation {
+        CONFIG.update(deps.storage, |mut old_config| -> LstResult<Config> {
+            old_config.max_validators_per_delegation = max_validators_per_delegation;
+            Ok(old_config)
+        })?;
+        res = res.add_attribute(
+            "max_validators_per_delegation",
+            max_validators_per_delegation.to_string(),
+        );
+    }
+
+    if let Some(min_delegation_chunk) = min_delegation_chunk {
+        CONFIG.update(deps.storage, |mut old_config| -> LstResult<Config> {
+            old_config.min_delegation_chunk = min_delegation_chunk;
+            Ok(old_config)
+        })?;
+        res = res.add_attribute("min_delegation_chunk", min_delegation_chunk);
+    }
+
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> LstResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => query_config(deps),
+        QueryMsg::ValidatorsDelegation {} => {
+            Ok(to_json_binary(&query_validators_delegation(deps)?)?)
+        }
+        QueryMsg::ExcludeList => Ok(to_json_binary(&query_exclude_list(deps)?)?),
+        QueryMsg::SoftRemovedList {} => Ok(to_json_binary(&query_soft_removed_list(deps)?)?),
+        QueryMsg::DelegationDeviation {} => Ok(to_json_binary(&query_delegation_deviation(deps)?)?),
+        QueryMsg::OverCapValidators {} => Ok(to_json_binary(&query_over_cap_validators(deps)?)?),
+        QueryMsg::ValidatorInfo { start_from, limit } => Ok(to_json_binary(
+            &query_validator_info(deps, start_from, limit)?,
+        )?),
+        QueryMsg::ValidatorHistory {
+            address,
+            start_from,
+            limit,
+        } => Ok(to_json_binary(&query_validator_history(
+            deps, address, start_from, limit,
+        )?)?),
+        QueryMsg::ValidatorSetChanges {
+            validator,
+            start_time,
+            end_time,
+            start_from,
+            limit,
+        } => Ok(to_json_binary(&query_validator_set_changes(
+            deps, validator, start_time, end_time, start_from, limit,
+        )?)?),
+        QueryMsg::PendingRedelegations {} => {
+            Ok(to_json_binary(&query_pending_redelegations(deps, env)?)?)
+        }
+        QueryMsg::PendingApplications { start_from, limit } => Ok(to_json_binary(
+            &query_pending_applications(deps, start_from, limit)?,
+        )?),
+        QueryMsg::GaugeConfig {} => Ok(to_json_binary(&query_gauge_config(deps)?)?),
+        QueryMsg::GaugeVotes { start_from, limit } => Ok(to_json_binary(&query_gauge_votes(
+            deps, start_from, limit,
+        )?)?),
+        QueryMsg::GaugeResults {} => Ok(to_json_binary(&query_gauge_results(deps)?)?),
+        QueryMsg::CurationConfig {} => Ok(to_json_binary(&query_curation_config(deps)?)?),
+    }
+}
+
+fn query_config(deps: Deps) -> LstResult<Binary> {
     Ok(to_json_binary(&CONFIG.load(deps.storage)?)?)
 }
 
-fn query_validators(deps: Deps) -> LstResult<Vec<ValidatorResponse>> {
+fn query_validators_delegation(deps: Deps) -> LstResult<ValidatorsDelegationResponse> {
     let Config {
-        owner: _,
         hub_contract,
+        delegation_strategy,
+        max_validator_share,
+        max_validators_per_delegation,
+        min_delegation_chunk,
+        ..
     } = CONFIG.load(deps.storage)?;
 
-    let mut delegations = HashMap::<String, Uint128>::new();
+    let delegations = query_hub_delegations(deps, &hub_contract)?;
+
+    let mut draining = SOFT_REMOVED_VALIDATORS
+        .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|address| {
+            let address = address?;
+            Ok(ValidatorResponse {
+                total_delegated: *delegations.get(&address).unwrap_or(&Uint128::zero()),
+                address,
+                weight: default_validator_weight(),
+            })
+        })
+        .collect::<LstResult<Vec<_>>>()?;
+    draining.retain(|validator| !validator.total_delegated.is_zero());
+    draining.sort_by_key(|v| v.total_delegated);
+
+    // the delegation strategies fill the validators in ascending delegation order
+    let mut validators = registry_validators(deps, &delegations)?;
+    validators.sort_by_key(|v| v.total_delegated);
+
+    Ok(ValidatorsDelegationResponse {
+        strategy: delegation_strategy,
+        validators,
+        draining,
+        max_validator_share,
+        max_validators_per_delegation,
+        min_delegation_chunk,
+    })
+}
 
-    deps.querier
-        .query_all_delegations(&hub_contract)?
+// Delegated amount of the hub contract per validator, as tracked by the hub including the delegations queued for the epoch
+fn query_hub_delegations(deps: Deps, hub_contract: &Addr) -> LstResult<HashMap<String, Uint128>> {
+    Ok(fetch_hub_delegations(&deps.querier, hub_contract)?
         .into_iter()
-        .for_each(|delegation| {
-            delegations.insert(delegation.validator, delegation.amount.amount);
-        });
+        .map(|delegation| (delegation.validator, delegation.delegated))
+        .collect())
+}
 
-    let mut responses: Vec<ValidatorResponse> = VALIDATOR_REGISTRY
+// Validators in the registry with their delegation, in registry order
+fn registry_validators(
+    deps: Deps,
+    delegations: &HashMap<String, Uint128>,
+) -> LstResult<Vec<ValidatorResponse>> {
+    VALIDATOR_REGISTRY
         .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
         .map(|data| {
-            let val_address = data?.1.address;
+            let (key, validator) = data?;
             Ok(ValidatorResponse {
-                address: val_address.clone(),
-                total_delegated: *delegations.get(&val_address).unwrap_or(&Uint128::zero()),
+                total_delegated: *delegations
+                    .get(&validator.address)
+                    .unwrap_or(&Uint128::zero()),
+                address: validator.address,
+                weight: VALIDATOR_WEIGHTS
+                    .may_load(deps.storage, &key)?
+                    .unwrap_or_else(default_validator_weight),
+            })
+        })
+        .collect()
+}
+
+// Target delegation of each validator under the delegation strategy, where no validator goes over the
+// concentration cap of the total hub stake so that rebalancing and the over-cap redelegations agree
+fn capped_target_delegations(
+    config: &Config,
+    validators: &[ValidatorResponse],
+    total_stake: Uint128,
+) -> LstResult<Vec<Uint128>> {
+    let targets = config.delegation_strategy.target_delegations(validators)?;
+    let cap = concentration_cap(total_stake, config.max_validator_share);
+
+    Ok(capped_targets(&targets, cap))
+}
+
+fn query_delegation_deviation(deps: Deps) -> LstResult<Vec<DelegationDeviationResponse>> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let delegations = query_hub_delegations(deps, &config.hub_contract)?;
+    let validators = registry_validators(deps, &delegations)?;
+    if validators.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let targets = capped_target_delegations(&config, &validators, delegations.values().sum())?;
+
+    Ok(validators
+        .into_iter()
+        .zip(targets)
+        .map(|(validator, target)| DelegationDeviationResponse {
+            deviation: SignedInt::from_subtraction(validator.total_delegated, target),
+            address: validator.address,
+            total_delegated: validator.total_delegated,
+            target_delegation: target,
+        })
+        .collect())
+}
+
+fn query_over_cap_validators(deps: Deps) -> LstResult<Vec<OverCapValidatorResponse>> {
+    let Config {
+        hub_contract,
+        max_validator_share,
+        ..
+    } = CONFIG.load(deps.storage)?;
+
+    let delegations = query_hub_delegations(deps, &hub_contract)?;
+    let total_stake: Uint128 = delegations.values().sum();
+    if total_stake.is_zero() {
+        return Ok(vec![]);
+    }
+    let cap = concentration_cap(total_stake, max_validator_share);
+
+    let mut over_cap = delegations
+        .into_iter()
+        .filter(|(_, total_delegated)| *total_delegated > cap)
+        .map(|(address, total_delegated)| OverCapValidatorResponse {
+            address,
+            total_delegated,
+            share: Decimal::from_ratio(total_delegated, total_stake),
+            excess: total_delegated - cap,
+        })
+        .collect::<Vec<_>>();
+    over_cap.sort_by(|a, b| a.address.cmp(&b.address));
+
+    Ok(over_cap)
+}
+
+fn query_pending_redelegations(deps: Deps, env: Env) -> LstResult<Vec<PendingRedelegation>> {
+    let current_time = env.block.time.seconds();
+
+    let pending = PENDING_REDELEGATIONS
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect::<LstResult<Vec<_>>>()?;
+
+    Ok(pending
+        .into_iter()
+        .filter(|redelegation| redelegation.timestamp > current_time)
+        .collect())
+}
+
+fn query_validator_info(
+    deps: Deps,
+    start_from: Option<String>,
+    limit: Option<u32>,
+) -> LstResult<Vec<ValidatorInfoResponse>> {
+    let start_from =
+        start_from.map(|address| convert_addr_by_prefix(address.as_str(), VALIDATOR_ADDR_PREFIX));
+
+    VALIDATOR_METADATA
+        .range(
+            deps.storage,
+            start_from.as_deref().map(Bound::inclusive),
+            None,
+            cosmwasm_std::Order::Ascending,
+        )
+        .take(limit.unwrap_or(u32::MAX) as usize)
+        .map(|item| {
+            let (address, metadata) = item?;
+            let latest_snapshot = VALIDATOR_SNAPSHOTS
+                .prefix(&address)
+                .range(deps.storage, None, None, cosmwasm_std::Order::Descending)
+                .next()
+                .transpose()?
+                .map(|(_, snapshot)| snapshot);
+
+            Ok(ValidatorInfoResponse {
+                address,
+                metadata,
+                latest_snapshot,
+            })
+        })
+        .collect()
+}
+
+fn query_validator_history(
+    deps: Deps,
+    address: String,
+    start_from: Option<u64>,
+    limit: Option<u32>,
+) -> LstResult<Vec<ValidatorSnapshot>> {
+    let validator_addr = convert_addr_by_prefix(address.as_str(), VALIDATOR_ADDR_PREFIX);
+
+    VALIDATOR_SNAPSHOTS
+        .prefix(&validator_addr)
+        .range(
+            deps.storage,
+            start_from.map(Bound::inclusive),
+            None,
+            cosmwasm_std::Order::Ascending,
+        )
+        .take(limit.unwrap_or(u32::MAX) as usize)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
+// Filters are applied before the limit, so a page only holds matching changes
+fn query_validator_set_changes(
+    deps: Deps,
+    validator: Option<String>,
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+    start_from: Option<u64>,
+    limit: Option<u32>,
+) -> LstResult<Vec<ValidatorSetChange>> {
+    let validator =
+        validator.map(|address| convert_addr_by_prefix(address.as_str(), VALIDATOR_ADDR_PREFIX));
+
+    VALIDATOR_SET_CHANGES
+        .range(
+            deps.storage,
+            start_from.map(Bound::inclusive),
+            None,
+            cosmwasm_std::Order::Ascending,
+        )
+        // changes are appended in block time order, so nothing after end_time can match
+        .take_while(|item| {
+            !matches!(item, Ok((_, change)) if end_time.is_some_and(|end_time| change.timestamp > end_time))
+        })
+        .filter(|item| match item {
+            Ok((_, change)) => {
+                validator
+                    .as_ref()
+                    .is_none_or(|validator| &change.validator == validator)
+                    && start_time.is_none_or(|start_time| change.timestamp >= start_time)
+            }
+            Err(_) => true,
+        })
+        .take(limit.unwrap_or(u32::MAX) as usize)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
+fn query_pending_applications(
+    deps: Deps,
+    start_from: Option<String>,
+    limit: Option<u32>,
+) -> LstResult<Vec<ValidatorApplication>> {
+    let start_from =
+        start_from.map(|address| convert_addr_by_prefix(address.as_str(), VALIDATOR_ADDR_PREFIX));
+
+    VALIDATOR_APPLICATIONS
+        .range(
+            deps.storage,
+            start_from.as_deref().map(Bound::inclusive),
+            None,
+            cosmwasm_std::Order::Ascending,
+        )
+        .take(limit.unwrap_or(u32::MAX) as usize)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
+fn query_soft_removed_list(deps: Deps) -> LstResult<Vec<SoftRemovedValidator>> {
+    let soft_removed = SOFT_REMOVED_VALIDATORS
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| {
+            let (address, removal_time) = item?;
+            Ok(SoftRemovedValidator {
+                address,
+                removal_time,
             })
         })
         .collect::<LstResult<Vec<_>>>()?;
-    responses.sort_by(|v1, v2| v1.total_delegated.cmp(&v2.total_delegated));
 
-    Ok(responses)
+    Ok(soft_removed)
 }
 
 fn query_exclude_list(deps: Deps) -> LstResult<Vec<String>> {
@@ -307,21 +1496,77 @@ fn query_exclude_list(deps: Deps) -> LstResult<Vec<String>> {
 
 #[cfg(test)]
 mod tests {
-    use crate::contract::{instantiate, query_config, query_exclude_list, remove_validator};
+    use crate::contract::{
+        instantiate, query_config, query_delegation_deviation, query_exclude_list,
+        query_over_cap_validators, query_pending_redelegations, rebalance, remove_validator,
+    };
+    use crate::testing::{mock_dependencies_with_grpc, proto_validator, GrpcMockQuerier};
+    use cosmos_sdk_proto::cosmos::staking::v1beta1::BondStatus;
     use cosmwasm_std::{
-        attr, coin, coins,
-        testing::{message_info, mock_dependencies, mock_env},
-        to_json_binary, CosmosMsg, Decimal, FullDelegation, SubMsg, Uint128,
-        Validator as StdValidator, WasmMsg,
+        attr, coin, coins, from_json,
+        testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+        to_json_binary, Addr, ContractResult, CosmosMsg, Decimal, FullDelegation, OwnedDeps,
+        SubMsg, SystemError, SystemResult, Uint128, Validator as StdValidator, WasmMsg, WasmQuery,
     };
     use lst_common::{
         address::VALIDATOR_ADDR_PREFIX,
-        hub::ExecuteMsg as HubExecuteMsg,
-        validator::{Config, InstantiateMsg, Validator, ValidatorResponse},
-        ContractError,
+        delegation::DelegationStrategyKind,
+        hub::{
+            ExecuteMsg as HubExecuteMsg, Parameters, QueryMsg as HubQueryMsg,
+            ValidatorDelegationResponse,
+        },
+        validator::{
+            default_validator_weight, Config, DelegationDeviationResponse, InstantiateMsg,
+            OverCapValidatorResponse, PendingRedelegation, SoftRemovedValidator, Validator,
+            ValidatorApplication, ValidatorInfoResponse, ValidatorMetadata, ValidatorResponse,
+            ValidatorSetAction, ValidatorSetChange, ValidatorSnapshot,
+            ValidatorsDelegationResponse, DEFAULT_REBALANCE_COOLDOWN,
+            DEFAULT_SOFT_REMOVAL_GRACE_PERIOD, MAX_REASON_LENGTH, SNAPSHOT_INTERVAL,
+        },
+        ContractError, SignedInt, ValidatorError,
+    };
+
+    use super::{
+        add_validator, apply_validator, approve_application, process_redelegations,
+        query_pending_applications, query_soft_removed_list, query_validator_history,
+        query_validator_info, query_validator_set_changes, query_validators_delegation,
+        record_snapshots, redelegate, reject_application, report_slashing,
+        restore_delegation_order, soft_remove_validator, update_config, update_validator_weight,
+        withdraw_application,
     };
 
-    use super::{add_validator, process_redelegations, query_validators, update_config};
+    const UNSTAKING_PERIOD: u64 = 100;
+
+    // Mock the hub answering its parameters and its delegation ledger
+    fn mock_hub(querier: &mut MockQuerier, delegations: &[FullDelegation]) {
+        let delegations = delegations
+            .iter()
+            .map(|delegation| ValidatorDelegationResponse {
+                validator: delegation.validator.clone(),
+                delegated: delegation.amount.amount,
+                bonded: delegation.amount.amount,
+            })
+            .collect::<Vec<_>>();
+        querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => match from_json(msg).unwrap() {
+                HubQueryMsg::Parameters {} => SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&Parameters {
+                        epoch_length: 10,
+                        staking_coin_denom: "denom".to_string(),
+                        unstaking_period: UNSTAKING_PERIOD,
+                        paused: false,
+                        batched_delegation: false,
+                    })
+                    .unwrap(),
+                )),
+                HubQueryMsg::Delegations {} => {
+                    SystemResult::Ok(ContractResult::Ok(to_json_binary(&delegations).unwrap()))
+                }
+                _ => panic!("unexpected query"),
+            },
+            _ => SystemResult::Err(SystemError::Unknown {}),
+        });
+    }
 
     #[test]
     fn test_instantiate() {
@@ -370,6 +1615,13 @@ mod tests {
         deps.querier.staking.update(
             denom,
             &[validator1_info, validator2_info],
+            &[
+                validator1_full_delegation.clone(),
+                validator2_full_delegation.clone(),
+            ],
+        );
+        mock_hub(
+            &mut deps.querier,
             &[validator1_full_delegation, validator2_full_delegation],
         );
         let msg = InstantiateMsg {
@@ -384,96 +1636,1783 @@ mod tests {
             hub_contract: hub_contract.to_string(),
         };
 
-        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    #[test]
+    fn test_add_validator() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate successfully
+        {
+            let msg = InstantiateMsg {
+                validators: vec![],
+                hub_contract: hub_contract.to_string(),
+            };
+
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+
+        // add validator successfully
+        {
+            let validator1_info = StdValidator::create(
+                validator1.to_string(),
+                Decimal::percent(5),
+                Decimal::percent(10),
+                Decimal::percent(1),
+            );
+
+            let validator1_full_delegation = FullDelegation::create(
+                hub_contract,
+                validator1.to_string(),
+                coin(100, denom),
+                coin(120, denom),
+                coins(1000, denom),
+            );
+
+            deps.querier.staking.update(
+                denom,
+                &[validator1_info],
+                std::slice::from_ref(&validator1_full_delegation),
+            );
+            mock_hub(&mut deps.querier, &[validator1_full_delegation]);
+
+            let validator = Validator {
+                address: validator1.to_string(),
+            };
+            let response =
+                add_validator(deps.as_mut(), env.clone(), info, validator, None).unwrap();
+
+            assert_eq!(
+                response.attributes,
+                vec![
+                    attr("action", "add_validator"),
+                    attr("validator", validator1.to_string())
+                ]
+            );
+        }
+
+        // unauthorized error
+        {
+            let new_owner = deps.api.addr_make("new_owner");
+            let info = message_info(&new_owner, &[]);
+
+            let validator = Validator {
+                address: validator1.to_string(),
+            };
+            let err = add_validator(deps.as_mut(), env.clone(), info, validator, None).unwrap_err();
+
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+
+        // query validators
+        {
+            let result = query_validators_delegation(deps.as_ref())
+                .unwrap()
+                .validators;
+            assert_eq!(
+                result,
+                vec![ValidatorResponse {
+                    total_delegated: Uint128::new(100),
+                    address: validator1.to_string(),
+                    weight: 1,
+                }]
+            )
+        }
+    }
+
+    #[test]
+    fn test_remove_validator() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate successfully
+        {
+            let msg = InstantiateMsg {
+                validators: vec![],
+                hub_contract: hub_contract.to_string(),
+            };
+
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+
+        // add validator successfully
+        {
+            let validator1_info = StdValidator::create(
+                validator1.to_string(),
+                Decimal::percent(5),
+                Decimal::percent(10),
+                Decimal::percent(1),
+            );
+
+            let validator1_full_delegation = FullDelegation::create(
+                env.clone().contract.address,
+                validator1.to_string(),
+                coin(100, denom),
+                coin(120, denom),
+                coins(1000, denom),
+            );
+
+            deps.querier.staking.update(
+                denom,
+                &[validator1_info],
+                std::slice::from_ref(&validator1_full_delegation),
+            );
+            mock_hub(&mut deps.querier, &[validator1_full_delegation]);
+
+            let validator = Validator {
+                address: validator1.to_string(),
+            };
+            add_validator(deps.as_mut(), env.clone(), info.clone(), validator, None).unwrap();
+        }
+
+        // remove validator successfully
+        {
+            let response = remove_validator(
+                deps.as_mut(),
+                env.clone(),
+                info,
+                validator1.to_string(),
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(
+                response.attributes,
+                vec![
+                    attr("action", "remove_validator"),
+                    attr("validator", validator1.to_string())
+                ]
+            );
+        }
+
+        // query validators
+        {
+            let result = query_validators_delegation(deps.as_ref())
+                .unwrap()
+                .validators;
+            assert_eq!(result, vec![])
+        }
+
+        // query exclude list
+        {
+            let result = query_exclude_list(deps.as_ref()).unwrap();
+            assert_eq!(result, vec![validator1.to_string()])
+        }
+
+        // unauthorized error
+        {
+            let new_owner = deps.api.addr_make("new_owner");
+            let info = message_info(&new_owner, &[]);
+
+            let err = remove_validator(
+                deps.as_mut(),
+                env.clone(),
+                info,
+                validator1.to_string(),
+                None,
+            )
+            .unwrap_err();
+
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+    }
+
+    #[test]
+    fn test_process_redelegations() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let validator2 = mock_api.addr_make("validator2");
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate successfully
+        {
+            let validator1_info = StdValidator::create(
+                validator1.to_string(),
+                Decimal::percent(5),
+                Decimal::percent(10),
+                Decimal::percent(1),
+            );
+            let validator2_info = StdValidator::create(
+                validator2.to_string(),
+                Decimal::percent(5),
+                Decimal::percent(10),
+                Decimal::percent(1),
+            );
+
+            let validator1_full_delegation = FullDelegation::create(
+                hub_contract.clone(),
+                validator1.to_string(),
+                coin(100, denom),
+                coin(120, denom),
+                coins(1000, denom),
+            );
+            let validator2_full_delegation = FullDelegation::create(
+                hub_contract.clone(),
+                validator2.to_string(),
+                coin(200, denom),
+                coin(220, denom),
+                coins(2000, denom),
+            );
+
+            // instantiate successfully
+            deps.querier.staking.update(
+                denom,
+                &[validator1_info, validator2_info],
+                &[
+                    validator1_full_delegation.clone(),
+                    validator2_full_delegation.clone(),
+                ],
+            );
+            mock_hub(
+                &mut deps.querier,
+                &[validator1_full_delegation, validator2_full_delegation],
+            );
+            let msg = InstantiateMsg {
+                validators: vec![
+                    Validator {
+                        address: validator1.to_string(),
+                    },
+                    Validator {
+                        address: validator2.to_string(),
+                    },
+                ],
+                hub_contract: hub_contract.to_string(),
+            };
+
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // remove validator successfully
+        {
+            let response = remove_validator(
+                deps.as_mut(),
+                env.clone(),
+                info,
+                validator1.to_string(),
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(
+                response.attributes,
+                vec![
+                    attr("action", "remove_validator"),
+                    attr("validator", validator1.to_string())
+                ]
+            );
+        }
+
+        // process redelegations successfully
+        {
+            let response = process_redelegations(deps.as_mut(), env.clone(), None).unwrap();
+
+            assert_eq!(
+                response.attributes,
+                vec![attr("action", "process_redelegation")]
+            );
+
+            let redelegate_proxy_msg = HubExecuteMsg::RedelegateProxy {
+                src_validator: validator1.to_string(),
+                redelegations: vec![(validator2.to_string(), coin(100, denom))],
+            };
+
+            assert_eq!(
+                response.messages,
+                vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: hub_contract.to_string(),
+                    msg: to_json_binary(&redelegate_proxy_msg).unwrap(),
+                    funds: vec![],
+                })),]
+            );
+
+            let pending = query_pending_redelegations(deps.as_ref(), env.clone()).unwrap();
+            assert_eq!(
+                pending,
+                vec![PendingRedelegation {
+                    src_validator: validator1.to_string(),
+                    redelegations: vec![(validator2.to_string(), coin(100, denom))],
+                    timestamp: env.block.time.seconds() + UNSTAKING_PERIOD,
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_redelegations_with_cursor() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let validator2 = mock_api.addr_make("validator2");
+        let validator3 = mock_api.addr_make("validator3");
+        let active_validator = mock_api.addr_make("active_validator");
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate successfully, validator3 holds no stake
+        {
+            let validators_info =
+                [&validator1, &validator2, &validator3, &active_validator].map(|validator| {
+                    StdValidator::create(
+                        validator.to_string(),
+                        Decimal::percent(5),
+                        Decimal::percent(10),
+                        Decimal::percent(1),
+                    )
+                });
+            let delegations = [&validator1, &validator2].map(|validator| {
+                FullDelegation::create(
+                    hub_contract.clone(),
+                    validator.to_string(),
+                    coin(100, denom),
+                    coin(100, denom),
+                    coins(1000, denom),
+                )
+            });
+
+            deps.querier
+                .staking
+                .update(denom, &validators_info, &delegations);
+            mock_hub(&mut deps.querier, &delegations);
+
+            let msg = InstantiateMsg {
+                validators: [&validator1, &validator2, &validator3, &active_validator]
+                    .map(|validator| Validator {
+                        address: validator.to_string(),
+                    })
+                    .to_vec(),
+                hub_contract: hub_contract.to_string(),
+            };
+
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            for validator in [&validator1, &validator2, &validator3] {
+                remove_validator(
+                    deps.as_mut(),
+                    env.clone(),
+                    info.clone(),
+                    validator.to_string(),
+                    None,
+                )
+                .unwrap();
+            }
+        }
+
+        let mut excluded = query_exclude_list(deps.as_ref()).unwrap();
+
+        // one excluded validator is handled per call, in order
+        for validator in excluded.clone() {
+            let response = process_redelegations(deps.as_mut(), env.clone(), Some(1)).unwrap();
+
+            if validator == validator3.to_string() {
+                assert!(response.messages.is_empty());
+                assert_eq!(
+                    response.attributes,
+                    vec![
+                        attr("action", "process_redelegation"),
+                        attr("drained", validator3.to_string())
+                    ]
+                );
+            } else {
+                let redelegate_proxy_msg = HubExecuteMsg::RedelegateProxy {
+                    src_validator: validator.clone(),
+                    redelegations: vec![(active_validator.to_string(), coin(100, denom))],
+                };
+                assert_eq!(
+                    response.messages,
+                    vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: hub_contract.to_string(),
+                        msg: to_json_binary(&redelegate_proxy_msg).unwrap(),
+                        funds: vec![],
+                    }))]
+                );
+            }
+        }
+
+        // the drained validator is removed from the exclude list
+        excluded.retain(|validator| *validator != validator3.to_string());
+        assert_eq!(query_exclude_list(deps.as_ref()).unwrap(), excluded);
+
+        // the cursor starts over once the end of the exclude list is reached
+        {
+            let response = process_redelegations(deps.as_mut(), env.clone(), Some(1)).unwrap();
+            let redelegate_proxy_msg = HubExecuteMsg::RedelegateProxy {
+                src_validator: excluded[0].clone(),
+                redelegations: vec![(active_validator.to_string(), coin(100, denom))],
+            };
+            assert_eq!(
+                response.messages,
+                vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: hub_contract.to_string(),
+                    msg: to_json_binary(&redelegate_proxy_msg).unwrap(),
+                    funds: vec![],
+                }))]
+            );
+        }
+    }
+
+    #[test]
+    fn test_concentration_cap() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let validator2 = mock_api.addr_make("validator2");
+        let validator3 = mock_api.addr_make("validator3");
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate successfully
+        {
+            let validators_info = [&validator1, &validator2, &validator3].map(|validator| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(5),
+                    Decimal::percent(10),
+                    Decimal::percent(1),
+                )
+            });
+            let delegations =
+                [(&validator1, 500), (&validator2, 100)].map(|(validator, amount)| {
+                    FullDelegation::create(
+                        hub_contract.clone(),
+                        validator.to_string(),
+                        coin(amount, denom),
+                        coin(amount, denom),
+                        coins(1000, denom),
+                    )
+                });
+
+            deps.querier
+                .staking
+                .update(denom, &validators_info, &delegations);
+            mock_hub(&mut deps.querier, &delegations);
+
+            let msg = InstantiateMsg {
+                validators: [&validator1, &validator2, &validator3]
+                    .map(|validator| Validator {
+                        address: validator.to_string(),
+                    })
+                    .to_vec(),
+                hub_contract: hub_contract.to_string(),
+            };
+
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // no validator over the default cap
+        {
+            assert!(query_over_cap_validators(deps.as_ref()).unwrap().is_empty());
+            let response = process_redelegations(deps.as_mut(), env.clone(), None).unwrap();
+            assert!(response.messages.is_empty());
+        }
+
+        // validator over a 40% cap is flagged
+        {
+            update_config(
+                deps.as_mut(),
+                info.clone(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(Decimal::percent(40)),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+            let result = query_over_cap_validators(deps.as_ref()).unwrap();
+            assert_eq!(
+                result,
+                vec![OverCapValidatorResponse {
+                    address: validator1.to_string(),
+                    total_delegated: Uint128::new(500),
+                    share: Decimal::from_ratio(500u128, 600u128),
+                    excess: Uint128::new(260),
+                }]
+            );
+        }
+
+        // the excess is redelegated to the validators below the cap
+        {
+            let response = process_redelegations(deps.as_mut(), env.clone(), None).unwrap();
+            assert_eq!(
+                response.attributes,
+                vec![
+                    attr("action", "process_redelegation"),
+                    attr("capped", validator1.to_string())
+                ]
+            );
+
+            let redelegate_proxy_msg = HubExecuteMsg::RedelegateProxy {
+                src_validator: validator1.to_string(),
+                redelegations: vec![
+                    (validator3.to_string(), coin(180, denom)),
+                    (validator2.to_string(), coin(80, denom)),
+                ],
+            };
+            assert_eq!(
+                response.messages,
+                vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: hub_contract.to_string(),
+                    msg: to_json_binary(&redelegate_proxy_msg).unwrap(),
+                    funds: vec![],
+                }))]
+            );
+        }
+    }
+
+    #[test]
+    fn test_transitive_redelegations() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let validator2 = mock_api.addr_make("validator2");
+        let validator3 = mock_api.addr_make("validator3");
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate successfully
+        {
+            let validators_info = [&validator1, &validator2, &validator3].map(|validator| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(5),
+                    Decimal::percent(10),
+                    Decimal::percent(1),
+                )
+            });
+            let delegations =
+                [(&validator1, 300), (&validator2, 100)].map(|(validator, amount)| {
+                    FullDelegation::create(
+                        hub_contract.clone(),
+                        validator.to_string(),
+                        coin(amount, denom),
+                        coin(amount, denom),
+                        coins(1000, denom),
+                    )
+                });
+
+            deps.querier
+                .staking
+                .update(denom, &validators_info, &delegations);
+            mock_hub(&mut deps.querier, &delegations);
+
+            let msg = InstantiateMsg {
+                validators: vec![
+                    Validator {
+                        address: validator1.to_string(),
+                    },
+                    Validator {
+                        address: validator2.to_string(),
+                    },
+                    Validator {
+                        address: validator3.to_string(),
+                    },
+                ],
+                hub_contract: hub_contract.to_string(),
+            };
+
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // validator1 redelegates to validator2 and validator3
+        {
+            remove_validator(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                validator1.to_string(),
+                None,
+            )
+            .unwrap();
+            let response = process_redelegations(deps.as_mut(), env.clone(), None).unwrap();
+
+            let redelegate_proxy_msg = HubExecuteMsg::RedelegateProxy {
+                src_validator: validator1.to_string(),
+                redelegations: vec![
+                    (validator3.to_string(), coin(200, denom)),
+                    (validator2.to_string(), coin(100, denom)),
+                ],
+            };
+            assert_eq!(
+                response.messages,
+                vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: hub_contract.to_string(),
+                    msg: to_json_binary(&redelegate_proxy_msg).unwrap(),
+                    funds: vec![],
+                }))]
+            );
+        }
+
+        // validator2 is still receiving a redelegation, so its redelegation is deferred
+        {
+            remove_validator(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                validator2.to_string(),
+                None,
+            )
+            .unwrap();
+            let response = process_redelegations(deps.as_mut(), env.clone(), None).unwrap();
+
+            let redelegate_proxy_msg = HubExecuteMsg::RedelegateProxy {
+                src_validator: validator1.to_string(),
+                redelegations: vec![(validator3.to_string(), coin(300, denom))],
+            };
+            assert_eq!(
+                response.messages,
+                vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: hub_contract.to_string(),
+                    msg: to_json_binary(&redelegate_proxy_msg).unwrap(),
+                    funds: vec![],
+                }))]
+            );
+            assert_eq!(
+                response.attributes,
+                vec![
+                    attr("action", "process_redelegation"),
+                    attr("deferred", validator2.to_string())
+                ]
+            );
+
+            let mut active_validators = query_validators_delegation(deps.as_ref())
+                .unwrap()
+                .validators;
+            let err = redelegate(
+                deps.as_mut().storage,
+                &hub_contract,
+                validator2.as_str(),
+                denom,
+                &[Uint128::new(100)],
+                &mut active_validators,
+                env.block.time.seconds(),
+                env.block.time.seconds() + UNSTAKING_PERIOD,
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::RedelegationCooldownNotMet)
+            );
+        }
+
+        // validator2 can redelegate once the redelegation has matured
+        {
+            env.block.time = env.block.time.plus_seconds(UNSTAKING_PERIOD);
+            let response = process_redelegations(deps.as_mut(), env.clone(), None).unwrap();
+
+            assert_eq!(response.messages.len(), 2);
+            assert_eq!(
+                response.attributes,
+                vec![attr("action", "process_redelegation")]
+            );
+
+            let pending = query_pending_redelegations(deps.as_ref(), env.clone()).unwrap();
+            assert_eq!(pending.len(), 2);
+            assert!(pending
+                .iter()
+                .all(|p| p.timestamp == env.block.time.seconds() + UNSTAKING_PERIOD));
+        }
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let validator2 = mock_api.addr_make("validator2");
+        let validator3 = mock_api.addr_make("validator3");
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate successfully
+        {
+            let validators_info = [&validator1, &validator2, &validator3].map(|validator| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(5),
+                    Decimal::percent(10),
+                    Decimal::percent(1),
+                )
+            });
+
+            let validator1_full_delegation = FullDelegation::create(
+                hub_contract.clone(),
+                validator1.to_string(),
+                coin(500, denom),
+                coin(500, denom),
+                coins(1000, denom),
+            );
+            let validator2_full_delegation = FullDelegation::create(
+                hub_contract.clone(),
+                validator2.to_string(),
+                coin(100, denom),
+                coin(100, denom),
+                coins(1000, denom),
+            );
+
+            deps.querier.staking.update(
+                denom,
+                &validators_info,
+                &[
+                    validator1_full_delegation.clone(),
+                    validator2_full_delegation.clone(),
+                ],
+            );
+            mock_hub(
+                &mut deps.querier,
+                &[validator1_full_delegation, validator2_full_delegation],
+            );
+            let msg = InstantiateMsg {
+                validators: vec![
+                    Validator {
+                        address: validator1.to_string(),
+                    },
+                    Validator {
+                        address: validator2.to_string(),
+                    },
+                    Validator {
+                        address: validator3.to_string(),
+                    },
+                ],
+                hub_contract: hub_contract.to_string(),
+            };
+
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // query delegation deviation
+        {
+            let result = query_delegation_deviation(deps.as_ref()).unwrap();
+            assert_eq!(
+                result,
+                vec![
+                    DelegationDeviationResponse {
+                        address: validator3.to_string(),
+                        total_delegated: Uint128::zero(),
+                        target_delegation: Uint128::new(200),
+                        deviation: SignedInt(Uint128::new(200), true),
+                    },
+                    DelegationDeviationResponse {
+                        address: validator1.to_string(),
+                        total_delegated: Uint128::new(500),
+                        target_delegation: Uint128::new(200),
+                        deviation: SignedInt(Uint128::new(300), false),
+                    },
+                    DelegationDeviationResponse {
+                        address: validator2.to_string(),
+                        total_delegated: Uint128::new(100),
+                        target_delegation: Uint128::new(200),
+                        deviation: SignedInt(Uint128::new(100), true),
+                    },
+                ]
+            );
+        }
+
+        // rebalance successfully, bounded by max moves
+        {
+            let response = rebalance(deps.as_mut(), env.clone(), 1).unwrap();
+
+            assert_eq!(
+                response.attributes,
+                vec![attr("action", "rebalance"), attr("moves", "1")]
+            );
+
+            let redelegate_proxy_msg = HubExecuteMsg::RedelegateProxy {
+                src_validator: validator1.to_string(),
+                redelegations: vec![(validator3.to_string(), coin(200, denom))],
+            };
+
+            assert_eq!(
+                response.messages,
+                vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: hub_contract.to_string(),
+                    msg: to_json_binary(&redelegate_proxy_msg).unwrap(),
+                    funds: vec![],
+                }))]
+            );
+        }
+
+        // RebalanceCooldownNotMet error
+        {
+            let err = rebalance(deps.as_mut(), env.clone(), 10).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::RebalanceCooldownNotMet)
+            );
+        }
+
+        // rebalance successfully after cooldown
+        {
+            env.block.time = env.block.time.plus_seconds(DEFAULT_REBALANCE_COOLDOWN);
+            let response = rebalance(deps.as_mut(), env.clone(), 10).unwrap();
+
+            let redelegate_proxy_msg = HubExecuteMsg::RedelegateProxy {
+                src_validator: validator1.to_string(),
+                redelegations: vec![
+                    (validator3.to_string(), coin(200, denom)),
+                    (validator2.to_string(), coin(100, denom)),
+                ],
+            };
+
+            assert_eq!(
+                response.messages,
+                vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: hub_contract.to_string(),
+                    msg: to_json_binary(&redelegate_proxy_msg).unwrap(),
+                    funds: vec![],
+                }))]
+            );
+        }
+    }
+
+    #[test]
+    fn test_rebalance_concentration_cap() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let validator2 = mock_api.addr_make("validator2");
+        let validator3 = mock_api.addr_make("validator3");
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate successfully
+        {
+            let validators_info = [&validator1, &validator2, &validator3].map(|validator| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(5),
+                    Decimal::percent(10),
+                    Decimal::percent(1),
+                )
+            });
+            let delegations = [&validator1, &validator2, &validator3].map(|validator| {
+                FullDelegation::create(
+                    hub_contract.clone(),
+                    validator.to_string(),
+                    coin(200, denom),
+                    coin(200, denom),
+                    coins(1000, denom),
+                )
+            });
+
+            deps.querier
+                .staking
+                .update(denom, &validators_info, &delegations);
+            mock_hub(&mut deps.querier, &delegations);
+
+            let msg = InstantiateMsg {
+                validators: [&validator1, &validator2, &validator3]
+                    .map(|validator| Validator {
+                        address: validator.to_string(),
+                    })
+                    .to_vec(),
+                hub_contract: hub_contract.to_string(),
+            };
+
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // validator1 weighs 80% but is capped at 50% of the stake
+        {
+            update_config(
+                deps.as_mut(),
+                info.clone(),
+                None,
+                None,
+                None,
+                Some(DelegationStrategyKind::Weighted),
+                None,
+                Some(Decimal::percent(50)),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            update_validator_weight(deps.as_mut(), info.clone(), validator1.to_string(), 800)
+                .unwrap();
+            update_validator_weight(deps.as_mut(), info.clone(), validator2.to_string(), 100)
+                .unwrap();
+            update_validator_weight(deps.as_mut(), info.clone(), validator3.to_string(), 100)
+                .unwrap();
+
+            let result = query_delegation_deviation(deps.as_ref()).unwrap();
+            let target = |validator: &Addr| {
+                result
+                    .iter()
+                    .find(|deviation| deviation.address == validator.to_string())
+                    .unwrap()
+                    .target_delegation
+            };
+            assert_eq!(target(&validator1), Uint128::new(300));
+            assert_eq!(target(&validator2), Uint128::new(150));
+            assert_eq!(target(&validator3), Uint128::new(150));
+        }
+
+        // rebalancing stops at the cap, so the over-cap redelegations have nothing to undo
+        {
+            let response = rebalance(deps.as_mut(), env.clone(), 10).unwrap();
+            assert_eq!(
+                response.attributes,
+                vec![attr("action", "rebalance"), attr("moves", "2")]
+            );
+
+            let mut expected = [&validator2, &validator3]
+                .map(|validator| {
+                    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: hub_contract.to_string(),
+                        msg: to_json_binary(&HubExecuteMsg::RedelegateProxy {
+                            src_validator: validator.to_string(),
+                            redelegations: vec![(validator1.to_string(), coin(50, denom))],
+                        })
+                        .unwrap(),
+                        funds: vec![],
+                    }))
+                })
+                .to_vec();
+            let mut messages = response.messages.clone();
+            messages.sort_by_key(|msg| format!("{:?}", msg));
+            expected.sort_by_key(|msg| format!("{:?}", msg));
+            assert_eq!(messages, expected);
+        }
+    }
+
+    #[test]
+    fn test_soft_remove_validator() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let validator2 = mock_api.addr_make("validator2");
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate successfully
+        {
+            let validators_info = [&validator1, &validator2].map(|validator| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(5),
+                    Decimal::percent(10),
+                    Decimal::percent(1),
+                )
+            });
+            let delegations = [&validator1, &validator2].map(|validator| {
+                FullDelegation::create(
+                    hub_contract.clone(),
+                    validator.to_string(),
+                    coin(100, denom),
+                    coin(100, denom),
+                    coins(1000, denom),
+                )
+            });
+
+            deps.querier
+                .staking
+                .update(denom, &validators_info, &delegations);
+            mock_hub(&mut deps.querier, &delegations);
+
+            let msg = InstantiateMsg {
+                validators: vec![
+                    Validator {
+                        address: validator1.to_string(),
+                    },
+                    Validator {
+                        address: validator2.to_string(),
+                    },
+                ],
+                hub_contract: hub_contract.to_string(),
+            };
+
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // Unauthorized error
+        {
+            let info = message_info(&hub_contract, &[]);
+            let err = soft_remove_validator(
+                deps.as_mut(),
+                env.clone(),
+                info,
+                validator1.to_string(),
+                None,
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+
+        // soft remove validator successfully
+        {
+            let response = soft_remove_validator(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                validator1.to_string(),
+                None,
+            )
+            .unwrap();
+            assert_eq!(
+                response.attributes,
+                vec![
+                    attr("action", "soft_remove_validator"),
+                    attr("validator", validator1.to_string())
+                ]
+            );
+
+            let result = query_soft_removed_list(deps.as_ref()).unwrap();
+            assert_eq!(
+                result,
+                vec![SoftRemovedValidator {
+                    address: validator1.to_string(),
+                    removal_time: env.block.time.seconds(),
+                }]
+            );
+        }
+
+        // ValidatorNotFound error
+        {
+            let err = soft_remove_validator(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                validator1.to_string(),
+                None,
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::ValidatorNotFound)
+            );
+        }
+
+        // soft-removed validator gets no new delegations and is drained first
+        {
+            let result = query_validators_delegation(deps.as_ref()).unwrap();
+            assert_eq!(
+                result,
+                ValidatorsDelegationResponse {
+                    strategy: DelegationStrategyKind::EqualSplit,
+                    validators: vec![ValidatorResponse {
+                        total_delegated: Uint128::new(100),
+                        address: validator2.to_string(),
+                        weight: 1,
+                    }],
+                    draining: vec![ValidatorResponse {
+                        total_delegated: Uint128::new(100),
+                        address: validator1.to_string(),
+                        weight: 1,
+                    }],
+                    max_validator_share: Decimal::one(),
+                    max_validators_per_delegation: 0,
+                    min_delegation_chunk: Uint128::zero(),
+                }
+            );
+        }
+
+        // no redelegation within the grace period
+        {
+            let response = process_redelegations(deps.as_mut(), env.clone(), None).unwrap();
+            assert!(response.messages.is_empty());
+        }
+
+        // a grace period too long to ever expire does not overflow
+        {
+            let set_grace_period = |deps: cosmwasm_std::DepsMut, grace_period: u64| {
+                update_config(
+                    deps,
+                    info.clone(),
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(grace_period),
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            };
+            set_grace_period(deps.as_mut(), u64::MAX);
+            let response = process_redelegations(deps.as_mut(), env.clone(), None).unwrap();
+            assert!(response.messages.is_empty());
+            assert_eq!(query_soft_removed_list(deps.as_ref()).unwrap().len(), 1);
+            set_grace_period(deps.as_mut(), DEFAULT_SOFT_REMOVAL_GRACE_PERIOD);
+        }
+
+        // hard removal redelegation after the grace period
+        {
+            env.block.time = env
+                .block
+                .time
+                .plus_seconds(DEFAULT_SOFT_REMOVAL_GRACE_PERIOD);
+            let response = process_redelegations(deps.as_mut(), env.clone(), None).unwrap();
+
+            let redelegate_proxy_msg = HubExecuteMsg::RedelegateProxy {
+                src_validator: validator1.to_string(),
+                redelegations: vec![(validator2.to_string(), coin(100, denom))],
+            };
+            assert_eq!(
+                response.messages,
+                vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: hub_contract.to_string(),
+                    msg: to_json_binary(&redelegate_proxy_msg).unwrap(),
+                    funds: vec![],
+                }))]
+            );
+
+            assert!(query_soft_removed_list(deps.as_ref()).unwrap().is_empty());
+            assert_eq!(
+                query_exclude_list(deps.as_ref()).unwrap(),
+                vec![validator1.to_string()]
+            );
+        }
+    }
+
+    #[test]
+    fn test_validator_set_changes() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let validator2 = mock_api.addr_make("validator2");
+        let validator3 = mock_api.addr_make("validator3");
+
+        let info = message_info(&owner, &[]);
+        let start_time = env.block.time.seconds();
+
+        // instantiate successfully
+        {
+            let validators_info = [&validator1, &validator2, &validator3].map(|validator| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(5),
+                    Decimal::percent(10),
+                    Decimal::percent(1),
+                )
+            });
+            let delegations = [&validator1, &validator2].map(|validator| {
+                FullDelegation::create(
+                    hub_contract.clone(),
+                    validator.to_string(),
+                    coin(100, denom),
+                    coin(100, denom),
+                    coins(1000, denom),
+                )
+            });
+
+            deps.querier
+                .staking
+                .update(denom, &validators_info, &delegations);
+            mock_hub(&mut deps.querier, &delegations);
+
+            let msg = InstantiateMsg {
+                validators: vec![
+                    Validator {
+                        address: validator1.to_string(),
+                    },
+                    Validator {
+                        address: validator2.to_string(),
+                    },
+                ],
+                hub_contract: hub_contract.to_string(),
+            };
+
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // ReasonTooLong error
+        {
+            let err = remove_validator(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                validator1.to_string(),
+                Some("a".repeat(MAX_REASON_LENGTH + 1)),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Validator(ValidatorError::ReasonTooLong));
+        }
+
+        // changes are recorded with actor, height and reason
+        {
+            env.block.height += 1;
+            env.block.time = env.block.time.plus_seconds(10);
+            add_validator(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                Validator {
+                    address: validator3.to_string(),
+                },
+                Some("new validator".to_string()),
+            )
+            .unwrap();
+
+            env.block.height += 1;
+            env.block.time = env.block.time.plus_seconds(10);
+            soft_remove_validator(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                validator1.to_string(),
+                Some("commission increase".to_string()),
+            )
+            .unwrap();
+
+            let result =
+                query_validator_set_changes(deps.as_ref(), None, None, None, Some(2), None)
+                    .unwrap();
+            assert_eq!(
+                result,
+                vec![
+                    ValidatorSetChange {
+                        id: 2,
+                        action: ValidatorSetAction::Add,
+                        validator: validator3.to_string(),
+                        actor: owner.clone(),
+                        height: env.block.height - 1,
+                        timestamp: start_time + 10,
+                        reason: Some("new validator".to_string()),
+                    },
+                    ValidatorSetChange {
+                        id: 3,
+                        action: ValidatorSetAction::SoftRemove,
+                        validator: validator1.to_string(),
+                        actor: owner.clone(),
+                        height: env.block.height,
+                        timestamp: start_time + 20,
+                        reason: Some("commission increase".to_string()),
+                    },
+                ]
+            );
+        }
+
+        // expired soft removal is recorded as a removal by the registry
+        {
+            env.block.time = env
+                .block
+                .time
+                .plus_seconds(DEFAULT_SOFT_REMOVAL_GRACE_PERIOD);
+            process_redelegations(deps.as_mut(), env.clone(), None).unwrap();
+
+            let result = query_validator_set_changes(
+                deps.as_ref(),
+                Some(validator1.to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(
+                result
+                    .iter()
+                    .map(|change| change.action.clone())
+                    .collect::<Vec<_>>(),
+                vec![
+                    ValidatorSetAction::Add,
+                    ValidatorSetAction::SoftRemove,
+                    ValidatorSetAction::Remove
+                ]
+            );
+            assert_eq!(result[2].actor, env.contract.address);
+        }
+
+        // filter by time range and limit
+        {
+            let result = query_validator_set_changes(
+                deps.as_ref(),
+                None,
+                Some(start_time + 10),
+                Some(start_time + 20),
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(
+                result.iter().map(|change| change.id).collect::<Vec<_>>(),
+                vec![2, 3]
+            );
+
+            let result =
+                query_validator_set_changes(deps.as_ref(), None, None, None, None, Some(1))
+                    .unwrap();
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0].validator, validator1.to_string());
+        }
+    }
+
+    #[test]
+    fn test_validator_applications() {
+        let mut deps = mock_dependencies_with_grpc();
+        let env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let denom = "denom";
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let candidate = mock_api.addr_make("candidate");
+        let jailed = mock_api.addr_make("jailed");
+        let expensive = mock_api.addr_make("expensive");
+        let unbonded = mock_api.addr_make("unbonded");
+        // operator accounts share the bytes of their validator address
+        let api = deps.api;
+        let operator = |name: &str| message_info(&api.addr_make(name), &[]);
+
+        let info = message_info(&owner, &[]);
+
+        // instantiate successfully
+        {
+            let validators_info = [
+                (&validator1, 5),
+                (&candidate, 5),
+                (&jailed, 5),
+                (&expensive, 30),
+                (&unbonded, 5),
+            ]
+            .map(|(validator, commission)| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(commission),
+                    Decimal::percent(50),
+                    Decimal::percent(1),
+                )
+            });
+            deps.querier
+                .base
+                .staking
+                .update(denom, &validators_info, &[]);
+            mock_hub(&mut deps.querier.base, &[]);
+            for (validator, is_jailed) in [
+                (&validator1, false),
+                (&candidate, false),
+                (&jailed, true),
+                (&expensive, false),
+                (&unbonded, false),
+            ] {
+                deps.querier.validators.insert(
+                    validator.to_string(),
+                    proto_validator(validator.as_str(), "moniker", is_jailed, 1000),
+                );
+            }
+            deps.querier
+                .validators
+                .get_mut(unbonded.as_str())
+                .unwrap()
+                .status = BondStatus::Unbonded as i32;
+
+            let msg = InstantiateMsg {
+                validators: vec![Validator {
+                    address: validator1.to_string(),
+                }],
+                hub_contract: hub_contract.to_string(),
+            };
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            update_config(
+                deps.as_mut(),
+                info.clone(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(Decimal::percent(20)),
+                None,
+                None,
+            )
+            .unwrap();
+        }
+
+        // Unauthorized error
+        {
+            let err = apply_validator(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                candidate.to_string(),
+                "details".to_string(),
+                None,
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+
+        // ineligible validators
+        {
+            for (name, validator, error) in [
+                (
+                    "validator1",
+                    &validator1,
+                    ValidatorError::ValidatorAlreadyRegistered,
+                ),
+                ("jailed", &jailed, ValidatorError::ValidatorJailed),
+                ("expensive", &expensive, ValidatorError::CommissionTooHigh),
+                ("unbonded", &unbonded, ValidatorError::ValidatorNotBonded),
+            ] {
+                let err = apply_validator(
+                    deps.as_mut(),
+                    env.clone(),
+                    operator(name),
+                    validator.to_string(),
+                    "details".to_string(),
+                    None,
+                )
+                .unwrap_err();
+                assert_eq!(err, ContractError::Validator(error));
+            }
+        }
+
+        let application = ValidatorApplication {
+            validator: candidate.to_string(),
+            applicant: api.addr_make("candidate"),
+            moniker: "moniker".to_string(),
+            details: "details".to_string(),
+            contact: Some("contact".to_string()),
+            commission: Decimal::percent(5),
+            submitted_at: env.block.time.seconds(),
+        };
+        let apply = |deps: &mut OwnedDeps<MockStorage, MockApi, GrpcMockQuerier>| {
+            apply_validator(
+                deps.as_mut(),
+                env.clone(),
+                operator("candidate"),
+                candidate.to_string(),
+                "details".to_string(),
+                Some("contact".to_string()),
+            )
+        };
+
+        // apply and withdraw successfully
+        {
+            apply(&mut deps).unwrap();
+            let err = apply(&mut deps).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::ApplicationAlreadyExists)
+            );
+            assert_eq!(
+                query_pending_applications(deps.as_ref(), None, None).unwrap(),
+                vec![application.clone()]
+            );
+
+            let err = withdraw_application(deps.as_mut(), info.clone(), candidate.to_string())
+                .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            withdraw_application(deps.as_mut(), operator("candidate"), candidate.to_string())
+                .unwrap();
+            assert!(query_pending_applications(deps.as_ref(), None, None)
+                .unwrap()
+                .is_empty());
+        }
+
+        // apply and reject successfully
+        {
+            apply(&mut deps).unwrap();
+
+            let err = reject_application(
+                deps.as_mut(),
+                operator("candidate"),
+                candidate.to_string(),
+                None,
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let response = reject_application(
+                deps.as_mut(),
+                info.clone(),
+                candidate.to_string(),
+                Some("insufficient uptime".to_string()),
+            )
+            .unwrap();
+            assert_eq!(
+                response.attributes,
+                vec![
+                    attr("action", "reject_application"),
+                    attr("validator", candidate.to_string()),
+                    attr("reason", "insufficient uptime")
+                ]
+            );
+
+            let err = approve_application(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                candidate.to_string(),
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::ApplicationNotFound)
+            );
+        }
+
+        // apply and approve successfully
+        {
+            apply(&mut deps).unwrap();
+            approve_application(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                candidate.to_string(),
+            )
+            .unwrap();
+
+            assert!(query_pending_applications(deps.as_ref(), None, None)
+                .unwrap()
+                .is_empty());
+            assert!(query_validators_delegation(deps.as_ref())
+                .unwrap()
+                .validators
+                .iter()
+                .any(|validator| validator.address == candidate.to_string()));
+
+            let changes = query_validator_set_changes(
+                deps.as_ref(),
+                Some(candidate.to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].actor, owner);
+            assert_eq!(changes[0].reason, Some("application approved".to_string()));
+        }
     }
 
     #[test]
-    fn test_add_validator() {
-        let mut deps = mock_dependencies();
-        let env = mock_env();
+    fn test_validator_info_and_history() {
+        let mut deps = mock_dependencies_with_grpc();
+        let mut env = mock_env();
 
         let hub_contract = deps.api.addr_make("hub_contract");
         let owner = deps.api.addr_make("owner");
         let denom = "denom";
 
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let mut validators = [
+            mock_api.addr_make("validator1"),
+            mock_api.addr_make("validator2"),
+        ];
+        validators.sort();
+        let [validator1, validator2] = validators;
+
         let info = message_info(&owner, &[]);
 
         // instantiate successfully
         {
+            let validators_info = [&validator1, &validator2].map(|validator| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(5),
+                    Decimal::percent(10),
+                    Decimal::percent(1),
+                )
+            });
+            deps.querier
+                .base
+                .staking
+                .update(denom, &validators_info, &[]);
+            mock_hub(&mut deps.querier.base, &[]);
+            for (validator, moniker) in [(&validator1, "moniker1"), (&validator2, "moniker2")] {
+                deps.querier.validators.insert(
+                    validator.to_string(),
+                    proto_validator(validator.as_str(), moniker, false, 1000),
+                );
+            }
+
             let msg = InstantiateMsg {
-                validators: vec![],
+                validators: vec![
+                    Validator {
+                        address: validator1.to_string(),
+                    },
+                    Validator {
+                        address: validator2.to_string(),
+                    },
+                ],
                 hub_contract: hub_contract.to_string(),
             };
 
             instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
         }
 
-        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
-        let validator1 = mock_api.addr_make("validator1");
+        let metadata = |moniker: &str| ValidatorMetadata {
+            moniker: moniker.to_string(),
+            commission_at_admission: Decimal::percent(5),
+            admission_time: env.block.time.seconds(),
+            added_by: owner.clone(),
+        };
 
-        // add validator successfully
+        // query validator info in batches
         {
-            let validator1_info = StdValidator::create(
-                validator1.to_string(),
-                Decimal::percent(5),
-                Decimal::percent(10),
-                Decimal::percent(1),
+            let result = query_validator_info(deps.as_ref(), None, Some(1)).unwrap();
+            assert_eq!(
+                result,
+                vec![ValidatorInfoResponse {
+                    address: validator1.to_string(),
+                    metadata: metadata("moniker1"),
+                    latest_snapshot: None,
+                }]
             );
 
-            let validator1_full_delegation = FullDelegation::create(
-                hub_contract,
-                validator1.to_string(),
-                coin(100, denom),
-                coin(120, denom),
-                coins(1000, denom),
+            let result =
+                query_validator_info(deps.as_ref(), Some(validator2.to_string()), None).unwrap();
+            assert_eq!(
+                result,
+                vec![ValidatorInfoResponse {
+                    address: validator2.to_string(),
+                    metadata: metadata("moniker2"),
+                    latest_snapshot: None,
+                }]
             );
+        }
 
-            deps.querier
-                .staking
-                .update(denom, &[validator1_info], &[validator1_full_delegation]);
-
-            let validator = Validator {
-                address: validator1.to_string(),
-            };
-            let response = add_validator(deps.as_mut(), env.clone(), info, validator).unwrap();
-
+        // record snapshots successfully
+        let first_snapshot = ValidatorSnapshot {
+            timestamp: env.block.time.seconds(),
+            commission: Decimal::percent(5),
+            jailed: false,
+            voting_power: Uint128::new(1000),
+        };
+        {
+            let response = record_snapshots(deps.as_mut(), env.clone()).unwrap();
             assert_eq!(
                 response.attributes,
-                vec![
-                    attr("action", "add_validator"),
-                    attr("validator", validator1.to_string())
-                ]
+                vec![attr("action", "record_snapshots"), attr("validators", "2")]
             );
         }
 
-        // unauthorized error
+        // SnapshotIntervalNotMet error
         {
-            let new_owner = deps.api.addr_make("new_owner");
-            let info = message_info(&new_owner, &[]);
-
-            let validator = Validator {
-                address: validator1.to_string(),
-            };
-            let err = add_validator(deps.as_mut(), env.clone(), info, validator).unwrap_err();
+            let err = record_snapshots(deps.as_mut(), env.clone()).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::SnapshotIntervalNotMet)
+            );
+        }
 
-            assert_eq!(err, ContractError::Unauthorized {});
+        // validator1 gets jailed and loses voting power
+        let second_snapshot = ValidatorSnapshot {
+            timestamp: env.block.time.seconds() + SNAPSHOT_INTERVAL,
+            commission: Decimal::percent(5),
+            jailed: true,
+            voting_power: Uint128::new(900),
+        };
+        {
+            env.block.time = env.block.time.plus_seconds(SNAPSHOT_INTERVAL);
+            deps.querier.validators.insert(
+                validator1.to_string(),
+                proto_validator(validator1.as_str(), "moniker1", true, 900),
+            );
+            record_snapshots(deps.as_mut(), env.clone()).unwrap();
         }
 
-        // query validators
+        // query validator history in batches
         {
-            let result = query_validators(deps.as_ref()).unwrap();
+            let result =
+                query_validator_history(deps.as_ref(), validator1.to_string(), None, None).unwrap();
             assert_eq!(
                 result,
-                vec![ValidatorResponse {
-                    total_delegated: Uint128::new(100),
-                    address: validator1.to_string(),
-                }]
+                vec![first_snapshot.clone(), second_snapshot.clone()]
+            );
+
+            let result = query_validator_history(
+                deps.as_ref(),
+                validator1.to_string(),
+                Some(second_snapshot.timestamp),
+                Some(1),
             )
+            .unwrap();
+            assert_eq!(result, vec![second_snapshot.clone()]);
+        }
+
+        // validator info carries the latest snapshot
+        {
+            let result = query_validator_info(deps.as_ref(), None, Some(1)).unwrap();
+            assert_eq!(result[0].latest_snapshot, Some(second_snapshot));
         }
     }
 
     #[test]
-    fn test_remove_validator() {
+    fn test_update_validator_weight() {
         let mut deps = mock_dependencies();
         let env = mock_env();
 
@@ -481,86 +3420,106 @@ mod tests {
         let owner = deps.api.addr_make("owner");
         let denom = "denom";
 
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let validator2 = mock_api.addr_make("validator2");
+        let validator3 = mock_api.addr_make("validator3");
+
         let info = message_info(&owner, &[]);
 
         // instantiate successfully
         {
+            let validators_info = [&validator1, &validator2].map(|validator| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(5),
+                    Decimal::percent(10),
+                    Decimal::percent(1),
+                )
+            });
+
+            deps.querier.staking.update(denom, &validators_info, &[]);
+            mock_hub(&mut deps.querier, &[]);
             let msg = InstantiateMsg {
-                validators: vec![],
+                validators: vec![
+                    Validator {
+                        address: validator1.to_string(),
+                    },
+                    Validator {
+                        address: validator2.to_string(),
+                    },
+                ],
                 hub_contract: hub_contract.to_string(),
             };
 
             instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
         }
 
-        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
-        let validator1 = mock_api.addr_make("validator1");
-
-        // add validator successfully
-        {
-            let validator1_info = StdValidator::create(
-                validator1.to_string(),
-                Decimal::percent(5),
-                Decimal::percent(10),
-                Decimal::percent(1),
-            );
-
-            let validator1_full_delegation = FullDelegation::create(
-                env.clone().contract.address,
-                validator1.to_string(),
-                coin(100, denom),
-                coin(120, denom),
-                coins(1000, denom),
-            );
-
-            deps.querier
-                .staking
-                .update(denom, &[validator1_info], &[validator1_full_delegation]);
-
-            let validator = Validator {
-                address: validator1.to_string(),
-            };
-            add_validator(deps.as_mut(), env.clone(), info.clone(), validator).unwrap();
-        }
-
-        // remove validator successfully
+        // update validator weight successfully
         {
-            let response = remove_validator(deps.as_mut(), info, validator1.to_string()).unwrap();
+            let response =
+                update_validator_weight(deps.as_mut(), info.clone(), validator2.to_string(), 3)
+                    .unwrap();
 
             assert_eq!(
                 response.attributes,
                 vec![
-                    attr("action", "remove_validator"),
-                    attr("validator", validator1.to_string())
+                    attr("action", "update_validator_weight"),
+                    attr("validator", validator2.to_string()),
+                    attr("weight", "3"),
                 ]
             );
         }
 
-        // query validators
+        // query validators delegation with the weights and the active strategy
         {
-            let result = query_validators(deps.as_ref()).unwrap();
-            assert_eq!(result, vec![])
+            let result = query_validators_delegation(deps.as_ref()).unwrap();
+            assert_eq!(
+                result,
+                ValidatorsDelegationResponse {
+                    strategy: DelegationStrategyKind::EqualSplit,
+                    validators: vec![
+                        ValidatorResponse {
+                            total_delegated: Uint128::zero(),
+                            address: validator1.to_string(),
+                            weight: 1,
+                        },
+                        ValidatorResponse {
+                            total_delegated: Uint128::zero(),
+                            address: validator2.to_string(),
+                            weight: 3,
+                        },
+                    ],
+                    draining: vec![],
+                    max_validator_share: Decimal::one(),
+                    max_validators_per_delegation: 0,
+                    min_delegation_chunk: Uint128::zero(),
+                }
+            );
         }
 
-        // query exclude list
+        // ValidatorNotFound error
         {
-            let result = query_exclude_list(deps.as_ref()).unwrap();
-            assert_eq!(result, vec![validator1.to_string()])
+            let err =
+                update_validator_weight(deps.as_mut(), info.clone(), validator3.to_string(), 3)
+                    .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::ValidatorNotFound)
+            );
         }
 
-        // unauthorized error
+        // Unauthorized error
         {
-            let new_owner = deps.api.addr_make("new_owner");
-            let info = message_info(&new_owner, &[]);
-
-            let err = remove_validator(deps.as_mut(), info, validator1.to_string()).unwrap_err();
-
+            let info = message_info(&hub_contract, &[]);
+            let err = update_validator_weight(deps.as_mut(), info, validator1.to_string(), 3)
+                .unwrap_err();
             assert_eq!(err, ContractError::Unauthorized {});
         }
     }
 
     #[test]
-    fn test_process_redelegations() {
+    fn test_report_slashing() {
         let mut deps = mock_dependencies();
         let env = mock_env();
 
@@ -572,44 +3531,19 @@ mod tests {
         let validator1 = mock_api.addr_make("validator1");
         let validator2 = mock_api.addr_make("validator2");
 
-        let info = message_info(&owner, &[]);
-
         // instantiate successfully
         {
-            let validator1_info = StdValidator::create(
-                validator1.to_string(),
-                Decimal::percent(5),
-                Decimal::percent(10),
-                Decimal::percent(1),
-            );
-            let validator2_info = StdValidator::create(
-                validator2.to_string(),
-                Decimal::percent(5),
-                Decimal::percent(10),
-                Decimal::percent(1),
-            );
-
-            let validator1_full_delegation = FullDelegation::create(
-                hub_contract.clone(),
-                validator1.to_string(),
-                coin(100, denom),
-                coin(120, denom),
-                coins(1000, denom),
-            );
-            let validator2_full_delegation = FullDelegation::create(
-                hub_contract.clone(),
-                validator2.to_string(),
-                coin(200, denom),
-                coin(220, denom),
-                coins(2000, denom),
-            );
+            let validators_info = [&validator1, &validator2].map(|validator| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(5),
+                    Decimal::percent(10),
+                    Decimal::percent(1),
+                )
+            });
+            deps.querier.staking.update(denom, &validators_info, &[]);
+            mock_hub(&mut deps.querier, &[]);
 
-            // instantiate successfully
-            deps.querier.staking.update(
-                denom,
-                &[validator1_info, validator2_info],
-                &[validator1_full_delegation, validator2_full_delegation],
-            );
             let msg = InstantiateMsg {
                 validators: vec![
                     Validator {
@@ -621,45 +3555,93 @@ mod tests {
                 ],
                 hub_contract: hub_contract.to_string(),
             };
-
-            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            instantiate(deps.as_mut(), env.clone(), message_info(&owner, &[]), msg).unwrap();
         }
 
-        // remove validator successfully
+        // Unauthorized error
         {
-            let response = remove_validator(deps.as_mut(), info, validator1.to_string()).unwrap();
+            let err = report_slashing(
+                deps.as_mut(),
+                env.clone(),
+                message_info(&owner, &[]),
+                validator1.to_string(),
+                Uint128::new(100),
+                env.block.height,
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
 
+        // slashed validator is soft-removed
+        {
+            let response = report_slashing(
+                deps.as_mut(),
+                env.clone(),
+                message_info(&hub_contract, &[]),
+                validator1.to_string(),
+                Uint128::new(100),
+                env.block.height,
+            )
+            .unwrap();
             assert_eq!(
                 response.attributes,
                 vec![
-                    attr("action", "remove_validator"),
-                    attr("validator", validator1.to_string())
+                    attr("action", "report_slashing"),
+                    attr("validator", validator1.to_string()),
+                    attr("amount", "100"),
+                    attr("height", env.block.height.to_string()),
+                    attr("soft_removed", "true"),
                 ]
             );
-        }
-
-        // process redelegations successfully
-        {
-            let response = process_redelegations(deps.as_mut()).unwrap();
 
+            let result = query_soft_removed_list(deps.as_ref()).unwrap();
             assert_eq!(
-                response.attributes,
-                vec![attr("action", "process_redelegation")]
+                result,
+                vec![SoftRemovedValidator {
+                    address: validator1.to_string(),
+                    removal_time: env.block.time.seconds(),
+                }]
             );
 
-            let redelegate_proxy_msg = HubExecuteMsg::RedelegateProxy {
-                src_validator: validator1.to_string(),
-                redelegations: vec![(validator2.to_string(), coin(100, denom))],
-            };
-
+            let changes = query_validator_set_changes(
+                deps.as_ref(),
+                Some(validator1.to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            let change = changes.last().unwrap();
+            assert_eq!(change.action, ValidatorSetAction::SoftRemove);
+            assert_eq!(change.actor, hub_contract);
             assert_eq!(
-                response.messages,
-                vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: hub_contract.to_string(),
-                    msg: to_json_binary(&redelegate_proxy_msg).unwrap(),
-                    funds: vec![],
-                })),]
+                change.reason,
+                Some(format!("slashed at height {}", env.block.height))
+            );
+        }
+
+        // a validator that is no longer in the registry is left as it is
+        {
+            let response = report_slashing(
+                deps.as_mut(),
+                env.clone(),
+                message_info(&hub_contract, &[]),
+                validator1.to_string(),
+                Uint128::new(50),
+                env.block.height + 1,
             )
+            .unwrap();
+            assert!(!response
+                .attributes
+                .iter()
+                .any(|attribute| attribute.key == "soft_removed"));
+
+            let validators = query_validators_delegation(deps.as_ref())
+                .unwrap()
+                .validators;
+            assert_eq!(validators.len(), 1);
+            assert_eq!(validators[0].address, validator2.to_string());
         }
     }
 
@@ -690,10 +3672,16 @@ mod tests {
         {
             let response = update_config(
                 deps.as_mut(),
-                env.clone(),
                 info.clone(),
                 Some(new_owner.to_string()),
                 Some(new_hub_contract.to_string()),
+                Some(3600),
+                Some(DelegationStrategyKind::Weighted),
+                Some(7200),
+                Some(Decimal::percent(25)),
+                None,
+                Some(5),
+                Some(Uint128::new(1000)),
             )
             .unwrap();
 
@@ -701,7 +3689,13 @@ mod tests {
                 response.attributes,
                 vec![
                     attr("owner", new_owner.to_string()),
-                    attr("hub", new_hub_contract.to_string())
+                    attr("hub", new_hub_contract.to_string()),
+                    attr("rebalance_cooldown", "3600"),
+                    attr("delegation_strategy", "Weighted"),
+                    attr("soft_removal_grace_period", "7200"),
+                    attr("max_validator_share", "0.25"),
+                    attr("max_validators_per_delegation", "5"),
+                    attr("min_delegation_chunk", "1000"),
                 ]
             )
         }
@@ -712,22 +3706,91 @@ mod tests {
             assert_eq!(
                 result,
                 to_json_binary(&Config {
-                    owner: new_owner,
+                    owner: new_owner.clone(),
                     hub_contract: new_hub_contract,
+                    rebalance_cooldown: 3600,
+                    delegation_strategy: DelegationStrategyKind::Weighted,
+                    soft_removal_grace_period: 7200,
+                    max_validator_share: Decimal::percent(25),
+                    max_commission: Decimal::one(),
+                    max_validators_per_delegation: 5,
+                    min_delegation_chunk: Uint128::new(1000),
                 })
                 .unwrap()
             )
         }
 
+        // InvalidMaxValidatorShare error
+        {
+            let info = message_info(&new_owner, &[]);
+            for max_validator_share in [Decimal::zero(), Decimal::percent(101)] {
+                let err = update_config(
+                    deps.as_mut(),
+                    info.clone(),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(max_validator_share),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap_err();
+                assert_eq!(
+                    err,
+                    ContractError::Validator(ValidatorError::InvalidMaxValidatorShare)
+                );
+            }
+        }
+
         // unauthorized error
         {
             let wrong_owner = deps.api.addr_make("wrong_owner");
             let info = message_info(&wrong_owner, &[]);
 
-            let err =
-                update_config(deps.as_mut(), env.clone(), info.clone(), None, None).unwrap_err();
+            let err = update_config(
+                deps.as_mut(),
+                info.clone(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap_err();
 
             assert_eq!(err, ContractError::Unauthorized {});
         }
     }
+
+    #[test]
+    fn test_restore_delegation_order() {
+        let mut validators = [0u128, 10, 10, 20, 30, 30, 50]
+            .iter()
+            .enumerate()
+            .map(|(index, amount)| ValidatorResponse {
+                address: format!("validator{index}"),
+                total_delegated: Uint128::new(*amount),
+                weight: default_validator_weight(),
+            })
+            .collect::<Vec<_>>();
+
+        // raised onto ties, past other validators and to the end
+        let changes = [25u128, 0, 20, 0, 0, 0, 5].map(Uint128::new);
+        validators
+            .iter_mut()
+            .zip(changes)
+            .for_each(|(validator, change)| validator.total_delegated += change);
+        let mut expected = validators.clone();
+        expected.sort_by_key(|v| v.total_delegated);
+
+        restore_delegation_order(&mut validators, &changes);
+        assert_eq!(validators, expected);
+    }
 }
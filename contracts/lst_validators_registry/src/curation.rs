@@ -0,0 +1,428 @@
+use std::collections::HashSet;
+
+use cosmwasm_std::{Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Uint128};
+
+use lst_common::{
+    types::LstResult,
+    validator::{Config, CurationConfig, Validator},
+    ContractError, ValidatorError,
+};
+
+use crate::{
+    contract::{deregister_validator, register_validator},
+    helper::{fetch_bonded_validators, fetch_validator_info},
+    state::{
+        load_curation_config, CONFIG, CURATION_CONFIG, LAST_CURATION_TIME, SOFT_REMOVED_VALIDATORS,
+        VALIDATOR_EXCLUDE_LIST, VALIDATOR_REGISTRY,
+    },
+};
+
+const CURATION_REASON: &str = "auto-curation";
+
+// Pick the top `set_size` bonded validators by voting power, after skipping the top `skip_top_share` of them
+// and leaving out the jailed validators and the ones above the commission threshold.
+// Validators soft-removed or excluded by the owner, a slashing or an earlier curation are never picked.
+// The registry is then replaced by the picked validators, the removed ones are excluded
+pub fn curate_validators(mut deps: DepsMut, env: Env) -> LstResult<Response> {
+    let CurationConfig {
+        enabled,
+        set_size,
+        skip_top_share,
+        max_commission,
+        period,
+    } = load_curation_config(deps.storage)?;
+    if !enabled {
+        return Err(ValidatorError::CurationDisabled.into());
+    }
+
+    let current_time = env.block.time.seconds();
+    if let Some(last_curation_time) = LAST_CURATION_TIME.may_load(deps.storage)? {
+        if current_time < last_curation_time.saturating_add(period) {
+            return Err(ValidatorError::CurationPeriodNotMet.into());
+        }
+    }
+
+    let mut bonded_validators = fetch_bonded_validators(&deps.querier)?;
+    bonded_validators.sort_by(|a, b| {
+        b.voting_power
+            .cmp(&a.voting_power)
+            .then_with(|| a.address.cmp(&b.address))
+    });
+    let skipped = Uint128::from(bonded_validators.len() as u128)
+        .mul_floor(skip_top_share)
+        .u128() as usize;
+    let target = bonded_validators
+        .into_iter()
+        .skip(skipped)
+        .filter(|validator| !validator.jailed && validator.commission <= max_commission)
+        .filter(|validator| {
+            !SOFT_REMOVED_VALIDATORS.has(deps.storage, validator.address.clone())
+                && !VALIDATOR_EXCLUDE_LIST.has(deps.storage, validator.address.clone())
+        })
+        .take(set_size as usize)
+        .map(|validator| validator.address)
+        .collect::<Vec<_>>();
+    if target.is_empty() {
+        return Err(ValidatorError::EmptyValidatorSet.into());
+    }
+
+    let current = VALIDATOR_REGISTRY
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|key| Ok(String::from_utf8(key?)?))
+        .collect::<StdResult<HashSet<_>>>()?;
+    let target_set = target.iter().cloned().collect::<HashSet<_>>();
+
+    let mut removed = vec![];
+    for validator_addr in current.iter().filter(|addr| !target_set.contains(*addr)) {
+        deregister_validator(
+            deps.storage,
+            &env,
+            validator_addr.clone(),
+            env.contract.address.clone(),
+            Some(CURATION_REASON.to_string()),
+        )?;
+        removed.push(validator_addr.clone());
+    }
+    removed.sort();
+
+    let mut added = vec![];
+    for validator_addr in target.into_iter().filter(|addr| !current.contains(addr)) {
+        let Some(validator_info) = fetch_validator_info(&deps.querier, validator_addr.clone())?
+        else {
+            continue;
+        };
+        register_validator(
+            deps.branch(),
+            &env,
+            &Validator {
+                address: validator_addr.clone(),
+            },
+            &validator_info,
+            env.contract.address.clone(),
+            Some(CURATION_REASON.to_string()),
+        )?;
+        added.push(validator_addr);
+    }
+
+    LAST_CURATION_TIME.save(deps.storage, &current_time)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "curate_validators")
+        .add_attribute("added", added.join(","))
+        .add_attribute("removed", removed.join(",")))
+}
+
+pub fn update_curation_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: Option<bool>,
+    set_size: Option<u32>,
+    skip_top_share: Option<Decimal>,
+    max_commission: Option<Decimal>,
+    period: Option<u64>,
+) -> LstResult<Response> {
+    let Config { owner, .. } = CONFIG.load(deps.storage)?;
+
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut curation_config = load_curation_config(deps.storage)?;
+    let mut res = Response::new().add_attribute("action", "update_curation_config");
+
+    if let Some(enabled) = enabled {
+        curation_config.enabled = enabled;
+        res = res.add_attribute("enabled", enabled.to_string());
+    }
+    if let Some(set_size) = set_size {
+        curation_config.set_size = set_size;
+        res = res.add_attribute("set_size", set_size.to_string());
+    }
+    if let Some(skip_top_share) = skip_top_share {
+        curation_config.skip_top_share = skip_top_share;
+        res = res.add_attribute("skip_top_share", skip_top_share.to_string());
+    }
+    if let Some(max_commission) = max_commission {
+        if max_commission > Decimal::one() {
+            return Err(ValidatorError::InvalidMaxCommission.into());
+        }
+        curation_config.max_commission = max_commission;
+        res = res.add_attribute("max_commission", max_commission.to_string());
+    }
+    if let Some(period) = period {
+        curation_config.period = period;
+        res = res.add_attribute("period", period.to_string());
+    }
+
+    if curation_config.set_size == 0 || curation_config.skip_top_share >= Decimal::one() {
+        return Err(ValidatorError::InvalidCurationConfig.into());
+    }
+    CURATION_CONFIG.save(deps.storage, &curation_config)?;
+
+    Ok(res)
+}
+
+pub fn query_curation_config(deps: Deps) -> LstResult<CurationConfig> {
+    Ok(load_curation_config(deps.storage)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        testing::{message_info, mock_env},
+        Decimal, Order, StdResult, Validator as StdValidator,
+    };
+    use lst_common::{
+        address::VALIDATOR_ADDR_PREFIX,
+        validator::{InstantiateMsg, Validator, ValidatorSetAction},
+        ContractError, ValidatorError,
+    };
+
+    use super::{curate_validators, update_curation_config};
+    use crate::{
+        contract::instantiate,
+        state::{
+            SOFT_REMOVED_VALIDATORS, VALIDATOR_EXCLUDE_LIST, VALIDATOR_REGISTRY,
+            VALIDATOR_SET_CHANGES,
+        },
+        testing::{mock_dependencies_with_grpc, proto_commission, proto_validator},
+    };
+
+    #[test]
+    fn test_curate_validators() {
+        let mut deps = mock_dependencies_with_grpc();
+        let mut env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let owner = deps.api.addr_make("owner");
+        let info = message_info(&owner, &[]);
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let largest = mock_api.addr_make("largest");
+        let picked = mock_api.addr_make("picked");
+        let expensive = mock_api.addr_make("expensive");
+        let jailed = mock_api.addr_make("jailed");
+        let kept = mock_api.addr_make("kept");
+        let smallest = mock_api.addr_make("smallest");
+
+        // instantiate successfully
+        {
+            let chain_validators = [
+                (&largest, 600, 5, false),
+                (&picked, 500, 5, false),
+                (&expensive, 400, 20, false),
+                (&jailed, 300, 5, true),
+                (&kept, 200, 10, false),
+                (&smallest, 100, 5, false),
+            ];
+            let validators_info = chain_validators.map(|(validator, _, commission, _)| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(commission),
+                    Decimal::percent(50),
+                    Decimal::percent(1),
+                )
+            });
+            deps.querier
+                .base
+                .staking
+                .update("denom", &validators_info, &[]);
+            for (validator, tokens, commission, is_jailed) in chain_validators {
+                let mut proto = proto_validator(validator.as_str(), "moniker", is_jailed, tokens);
+                proto.commission = Some(proto_commission(Decimal::percent(commission)));
+                deps.querier.validators.insert(validator.to_string(), proto);
+            }
+
+            let msg = InstantiateMsg {
+                validators: [&largest, &kept]
+                    .map(|validator| Validator {
+                        address: validator.to_string(),
+                    })
+                    .to_vec(),
+                hub_contract: hub_contract.to_string(),
+            };
+            instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // CurationDisabled error
+        {
+            let err = curate_validators(deps.as_mut(), env.clone()).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::CurationDisabled)
+            );
+        }
+
+        // Unauthorized error
+        {
+            let random = deps.api.addr_make("random");
+            let err = update_curation_config(
+                deps.as_mut(),
+                message_info(&random, &[]),
+                Some(true),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+
+        // InvalidCurationConfig error
+        {
+            for (set_size, skip_top_share) in [(Some(0), None), (None, Some(Decimal::one()))] {
+                let err = update_curation_config(
+                    deps.as_mut(),
+                    info.clone(),
+                    Some(true),
+                    set_size,
+                    skip_top_share,
+                    None,
+                    None,
+                )
+                .unwrap_err();
+                assert_eq!(
+                    err,
+                    ContractError::Validator(ValidatorError::InvalidCurationConfig)
+                );
+            }
+        }
+
+        // curate validators successfully
+        {
+            update_curation_config(
+                deps.as_mut(),
+                info.clone(),
+                Some(true),
+                Some(2),
+                Some(Decimal::percent(20)),
+                Some(Decimal::percent(10)),
+                Some(100),
+            )
+            .unwrap();
+
+            // the largest validator is skipped as the top 20%, the expensive and jailed ones are left out
+            // and the smallest one is beyond the set size
+            let res = curate_validators(deps.as_mut(), env.clone()).unwrap();
+            assert_eq!(res.attributes[1].value, picked.to_string());
+            assert_eq!(res.attributes[2].value, largest.to_string());
+
+            let registry = VALIDATOR_REGISTRY
+                .keys(&deps.storage, None, None, Order::Ascending)
+                .map(|key| Ok(String::from_utf8(key?)?))
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            let mut expected = vec![picked.to_string(), kept.to_string()];
+            expected.sort();
+            assert_eq!(registry, expected);
+            assert!(VALIDATOR_EXCLUDE_LIST.has(&deps.storage, largest.to_string()));
+
+            let changes = VALIDATOR_SET_CHANGES
+                .range(&deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(_, change)| change))
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            let curated = &changes[changes.len() - 2..];
+            assert_eq!(curated[0].action, ValidatorSetAction::Remove);
+            assert_eq!(curated[0].validator, largest.to_string());
+            assert_eq!(curated[1].action, ValidatorSetAction::Add);
+            assert_eq!(curated[1].validator, picked.to_string());
+            assert!(curated
+                .iter()
+                .all(|change| change.actor == env.contract.address
+                    && change.reason == Some("auto-curation".to_string())));
+        }
+
+        // CurationPeriodNotMet error, also for a period running past the end of time
+        {
+            let err = curate_validators(deps.as_mut(), env.clone()).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::CurationPeriodNotMet)
+            );
+
+            for period in [u64::MAX, 100] {
+                update_curation_config(
+                    deps.as_mut(),
+                    info.clone(),
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(period),
+                )
+                .unwrap();
+                let err = curate_validators(deps.as_mut(), env.clone()).unwrap_err();
+                assert_eq!(
+                    err,
+                    ContractError::Validator(ValidatorError::CurationPeriodNotMet)
+                );
+            }
+        }
+
+        // EmptyValidatorSet error
+        {
+            env.block.time = env.block.time.plus_seconds(100);
+            update_curation_config(
+                deps.as_mut(),
+                info.clone(),
+                None,
+                None,
+                None,
+                Some(Decimal::percent(1)),
+                None,
+            )
+            .unwrap();
+
+            let err = curate_validators(deps.as_mut(), env.clone()).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::EmptyValidatorSet)
+            );
+            assert_eq!(
+                VALIDATOR_REGISTRY
+                    .keys(&deps.storage, None, None, Order::Ascending)
+                    .count(),
+                2
+            );
+        }
+
+        // soft-removed and excluded validators are not picked back
+        {
+            VALIDATOR_REGISTRY.remove(deps.as_mut().storage, picked.as_bytes());
+            SOFT_REMOVED_VALIDATORS
+                .save(
+                    deps.as_mut().storage,
+                    picked.to_string(),
+                    &env.block.time.seconds(),
+                )
+                .unwrap();
+            update_curation_config(
+                deps.as_mut(),
+                info.clone(),
+                None,
+                None,
+                Some(Decimal::zero()),
+                Some(Decimal::percent(10)),
+                None,
+            )
+            .unwrap();
+
+            // the largest validator is still excluded from the first curation
+            let res = curate_validators(deps.as_mut(), env.clone()).unwrap();
+            assert_eq!(res.attributes[1].value, smallest.to_string());
+
+            let registry = VALIDATOR_REGISTRY
+                .keys(&deps.storage, None, None, Order::Ascending)
+                .map(|key| Ok(String::from_utf8(key?)?))
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            let mut expected = vec![kept.to_string(), smallest.to_string()];
+            expected.sort();
+            assert_eq!(registry, expected);
+            assert!(SOFT_REMOVED_VALIDATORS.has(&deps.storage, picked.to_string()));
+            assert!(VALIDATOR_EXCLUDE_LIST.has(&deps.storage, largest.to_string()));
+        }
+    }
+}
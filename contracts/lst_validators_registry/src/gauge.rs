@@ -0,0 +1,689 @@
+use std::collections::{HashMap, HashSet};
+
+use cosmwasm_std::{
+    Addr, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Uint128,
+};
+use cw_storage_plus::Bound;
+
+use lst_common::{
+    address::{convert_addr_by_prefix, VALIDATOR_ADDR_PREFIX},
+    delegation::{gauge_bounds_feasible, gauge_weights, DelegationStrategyKind},
+    types::LstResult,
+    validator::{
+        Config, GaugeConfig, GaugeResult, GaugeResultsResponse, GaugeVote, GaugeVoterResponse,
+        GAUGE_WEIGHT_PRECISION,
+    },
+    ContractError, ValidatorError,
+};
+
+use crate::{
+    helper::{fetch_lst_balance, fetch_lst_token},
+    state::{
+        load_gauge_config, CONFIG, GAUGE_CONFIG, GAUGE_RESULTS, GAUGE_VOTES, LAST_GAUGE_TALLY_TIME,
+        VALIDATOR_REGISTRY, VALIDATOR_WEIGHTS,
+    },
+};
+
+// Maximum number of voters, all of them are counted by a single tally.
+// Once full, a new voter takes the place of the voter with the smallest balance if it holds more
+pub const MAX_GAUGE_VOTERS: usize = 100;
+
+pub fn vote(deps: DepsMut, info: MessageInfo, votes: Vec<GaugeVote>) -> LstResult<Response> {
+    if votes.is_empty() {
+        GAUGE_VOTES.remove(deps.storage, &info.sender);
+        return Ok(Response::new()
+            .add_attribute("action", "vote")
+            .add_attribute("voter", info.sender));
+    }
+
+    let Config { hub_contract, .. } = CONFIG.load(deps.storage)?;
+    let lst_token = fetch_lst_token(&deps.querier, &hub_contract)?;
+    let balance = fetch_lst_balance(&deps.querier, &lst_token, &info.sender)?;
+    if balance.is_zero() {
+        return Err(ValidatorError::NoVotingPower.into());
+    }
+
+    let mut validators = HashSet::new();
+    let mut total_share = Decimal::zero();
+    let mut normalized_votes = vec![];
+    for GaugeVote { validator, share } in votes {
+        let validator = convert_addr_by_prefix(validator.as_str(), VALIDATOR_ADDR_PREFIX);
+        if !VALIDATOR_REGISTRY.has(deps.storage, validator.as_bytes()) {
+            return Err(ValidatorError::ValidatorNotFound.into());
+        }
+        if share.is_zero() || !validators.insert(validator.clone()) {
+            return Err(ValidatorError::InvalidGaugeVote.into());
+        }
+        total_share = total_share
+            .checked_add(share)
+            .map_err(|e| ContractError::Overflow(e.to_string()))?;
+        normalized_votes.push(GaugeVote { validator, share });
+    }
+    if total_share > Decimal::one() {
+        return Err(ValidatorError::InvalidGaugeVote.into());
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "vote")
+        .add_attribute("voter", info.sender.clone())
+        .add_attribute("validators", normalized_votes.len().to_string());
+
+    if !GAUGE_VOTES.has(deps.storage, &info.sender) {
+        let voters = GAUGE_VOTES
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        if voters.len() >= MAX_GAUGE_VOTERS {
+            let mut smallest: Option<(Addr, Uint128)> = None;
+            for voter in voters {
+                let voter_balance = fetch_lst_balance(&deps.querier, &lst_token, &voter)?;
+                if smallest
+                    .as_ref()
+                    .is_none_or(|(_, smallest_balance)| voter_balance < *smallest_balance)
+                {
+                    smallest = Some((voter, voter_balance));
+                }
+            }
+
+            match smallest {
+                Some((voter, voter_balance)) if voter_balance < balance => {
+                    GAUGE_VOTES.remove(deps.storage, &voter);
+                    res = res.add_attribute("evicted", voter);
+                }
+                _ => return Err(ValidatorError::GaugeVotersFull.into()),
+            }
+        }
+    }
+
+    GAUGE_VOTES.save(deps.storage, &info.sender, &normalized_votes)?;
+
+    Ok(res)
+}
+
+// Votes are counted with the LST balance of every voter at the height of the tally, so moving LST cannot count twice.
+// Voters without a balance left are dropped, which keeps the number of voters within `MAX_GAUGE_VOTERS`
+pub fn tally_votes(deps: DepsMut, env: Env) -> LstResult<Response> {
+    let Config {
+        hub_contract,
+        delegation_strategy,
+        ..
+    } = CONFIG.load(deps.storage)?;
+    if delegation_strategy != DelegationStrategyKind::Weighted {
+        return Err(ValidatorError::GaugeRequiresWeightedStrategy.into());
+    }
+    let GaugeConfig {
+        period,
+        min_weight,
+        max_weight,
+    } = load_gauge_config(deps.storage)?;
+    let current_time = env.block.time.seconds();
+
+    if let Some(last_tally_time) = LAST_GAUGE_TALLY_TIME.may_load(deps.storage)? {
+        if current_time < last_tally_time.saturating_add(period) {
+            return Err(ValidatorError::GaugePeriodNotMet.into());
+        }
+    }
+
+    let lst_token = fetch_lst_token(&deps.querier, &hub_contract)?;
+    let voters = GAUGE_VOTES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut tally_power: HashMap<String, Uint128> = HashMap::new();
+    let mut dropped_voters = 0;
+    for (voter, votes) in voters.iter() {
+        let balance = fetch_lst_balance(&deps.querier, &lst_token, voter)?;
+        if balance.is_zero() {
+            GAUGE_VOTES.remove(deps.storage, voter);
+            dropped_voters += 1;
+            continue;
+        }
+        for vote in votes {
+            let power = tally_power.entry(vote.validator.clone()).or_default();
+            *power = power
+                .checked_add(balance.mul_floor(vote.share))
+                .map_err(|e| ContractError::Overflow(e.to_string()))?;
+        }
+    }
+    LAST_GAUGE_TALLY_TIME.save(deps.storage, &current_time)?;
+
+    let res = Response::new()
+        .add_attribute("action", "tally_votes")
+        .add_attribute("voters", (voters.len() - dropped_voters).to_string())
+        .add_attribute("dropped_voters", dropped_voters.to_string());
+
+    // validators removed since they were voted for are left out
+    let powers = VALIDATOR_REGISTRY
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, validator) = item?;
+            let validator_addr =
+                convert_addr_by_prefix(validator.address.as_str(), VALIDATOR_ADDR_PREFIX);
+            let power = tally_power
+                .get(&validator_addr)
+                .copied()
+                .unwrap_or_default();
+            Ok((key, validator_addr, power))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // without any voting power the current weights are kept
+    if powers.iter().all(|(_, _, power)| power.is_zero()) {
+        GAUGE_RESULTS.save(deps.storage, &vec![])?;
+        return Ok(res);
+    }
+
+    // the registry may have changed size since the bounds were set
+    if !gauge_bounds_feasible(powers.len(), min_weight, max_weight) {
+        return Err(ValidatorError::InvalidGaugeWeightBounds.into());
+    }
+
+    let weights = gauge_weights(
+        &powers
+            .iter()
+            .map(|(_, _, power)| *power)
+            .collect::<Vec<_>>(),
+        min_weight,
+        max_weight,
+    );
+    let mut results = vec![];
+    for ((key, validator, power), weight) in powers.into_iter().zip(weights) {
+        let validator_weight = Uint128::from(GAUGE_WEIGHT_PRECISION).mul_floor(weight);
+        VALIDATOR_WEIGHTS.save(deps.storage, &key, &(validator_weight.u128() as u64))?;
+        results.push(GaugeResult {
+            validator,
+            power,
+            weight,
+        });
+    }
+    GAUGE_RESULTS.save(deps.storage, &results)?;
+
+    Ok(res)
+}
+
+pub fn update_gauge_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    period: Option<u64>,
+    min_weight: Option<Decimal>,
+    max_weight: Option<Decimal>,
+) -> LstResult<Response> {
+    let Config { owner, .. } = CONFIG.load(deps.storage)?;
+
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut gauge_config = load_gauge_config(deps.storage)?;
+    let mut res = Response::new().add_attribute("action", "update_gauge_config");
+
+    if let Some(period) = period {
+        gauge_config.period = period;
+        res = res.add_attribute("period", period.to_string());
+    }
+    if let Some(min_weight) = min_weight {
+        gauge_config.min_weight = min_weight;
+        res = res.add_attribute("min_weight", min_weight.to_string());
+    }
+    if let Some(max_weight) = max_weight {
+        gauge_config.max_weight = max_weight;
+        res = res.add_attribute("max_weight", max_weight.to_string());
+    }
+
+    let validator_count = VALIDATOR_REGISTRY
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count();
+    if gauge_config.max_weight > Decimal::one()
+        || !gauge_bounds_feasible(
+            validator_count,
+            gauge_config.min_weight,
+            gauge_config.max_weight,
+        )
+    {
+        return Err(ValidatorError::InvalidGaugeWeightBounds.into());
+    }
+    GAUGE_CONFIG.save(deps.storage, &gauge_config)?;
+
+    Ok(res)
+}
+
+pub fn query_gauge_config(deps: Deps) -> LstResult<GaugeConfig> {
+    Ok(load_gauge_config(deps.storage)?)
+}
+
+pub fn query_gauge_votes(
+    deps: Deps,
+    start_from: Option<String>,
+    limit: Option<u32>,
+) -> LstResult<Vec<GaugeVoterResponse>> {
+    let start_from = start_from.map(Addr::unchecked);
+
+    GAUGE_VOTES
+        .range(
+            deps.storage,
+            start_from.as_ref().map(Bound::inclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit.unwrap_or(u32::MAX) as usize)
+        .map(|item| {
+            let (voter, votes) = item?;
+            Ok(GaugeVoterResponse { voter, votes })
+        })
+        .collect()
+}
+
+pub fn query_gauge_results(deps: Deps) -> LstResult<GaugeResultsResponse> {
+    Ok(GaugeResultsResponse {
+        tallied_at: LAST_GAUGE_TALLY_TIME
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        results: GAUGE_RESULTS.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use cosmwasm_std::{
+        attr, from_json,
+        testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+        to_json_binary, Addr, ContractResult, Decimal, OwnedDeps, StdResult, SystemError,
+        SystemResult, Uint128, Validator as StdValidator, WasmQuery,
+    };
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+    use lst_common::{
+        address::VALIDATOR_ADDR_PREFIX,
+        delegation::DelegationStrategyKind,
+        hub::{ConfigResponse, QueryMsg as HubQueryMsg},
+        validator::{
+            GaugeResult, GaugeVote, GaugeVoterResponse, InstantiateMsg, Validator,
+            DEFAULT_GAUGE_PERIOD,
+        },
+        ContractError, ValidatorError,
+    };
+
+    use super::{
+        query_gauge_results, query_gauge_votes, tally_votes, update_gauge_config, vote,
+        MAX_GAUGE_VOTERS,
+    };
+    use crate::{
+        contract::instantiate,
+        state::{CONFIG, GAUGE_VOTES, VALIDATOR_WEIGHTS},
+    };
+
+    fn mock_lst_balances(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        hub_contract: &Addr,
+        lst_token: &Addr,
+        balances: HashMap<String, u128>,
+    ) {
+        let hub_contract = hub_contract.to_string();
+        let lst_token = lst_token.to_string();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if *contract_addr == hub_contract => {
+                match from_json(msg).unwrap() {
+                    HubQueryMsg::Config {} => SystemResult::Ok(ContractResult::Ok(
+                        to_json_binary(&ConfigResponse {
+                            owner: hub_contract.clone(),
+                            reward_dispatcher_contract: None,
+                            validators_registry_contract: None,
+                            lst_token: Some(lst_token.clone()),
+                            insurance_fund: None,
+                        })
+                        .unwrap(),
+                    )),
+                    _ => panic!("unexpected query"),
+                }
+            }
+            WasmQuery::Smart { msg, .. } => match from_json(msg).unwrap() {
+                Cw20QueryMsg::Balance { address } => SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&BalanceResponse {
+                        balance: Uint128::new(*balances.get(&address).unwrap_or(&0)),
+                    })
+                    .unwrap(),
+                )),
+                _ => panic!("unexpected query"),
+            },
+            _ => SystemResult::Err(SystemError::Unknown {}),
+        });
+    }
+
+    #[test]
+    fn test_gauge_voting() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let hub_contract = deps.api.addr_make("hub_contract");
+        let lst_token = deps.api.addr_make("lst_token");
+        let owner = deps.api.addr_make("owner");
+        let voter1 = deps.api.addr_make("voter1");
+        let voter2 = deps.api.addr_make("voter2");
+        let voter3 = deps.api.addr_make("voter3");
+        let no_balance = deps.api.addr_make("no_balance");
+        let whale = deps.api.addr_make("whale");
+
+        let mock_api = deps.api.with_prefix(VALIDATOR_ADDR_PREFIX);
+        let validator1 = mock_api.addr_make("validator1");
+        let validator2 = mock_api.addr_make("validator2");
+        let validator3 = mock_api.addr_make("validator3");
+        let validators = [&validator1, &validator2, &validator3];
+
+        // instantiate successfully
+        {
+            let validators_info = validators.map(|validator| {
+                StdValidator::create(
+                    validator.to_string(),
+                    Decimal::percent(5),
+                    Decimal::percent(10),
+                    Decimal::percent(1),
+                )
+            });
+            deps.querier.staking.update("denom", &validators_info, &[]);
+            mock_lst_balances(
+                &mut deps,
+                &hub_contract,
+                &lst_token,
+                HashMap::from([
+                    (voter1.to_string(), 600),
+                    (voter2.to_string(), 400),
+                    (voter3.to_string(), 100),
+                ]),
+            );
+
+            let msg = InstantiateMsg {
+                validators: validators
+                    .map(|validator| Validator {
+                        address: validator.to_string(),
+                    })
+                    .to_vec(),
+                hub_contract: hub_contract.to_string(),
+            };
+            instantiate(deps.as_mut(), env.clone(), message_info(&owner, &[]), msg).unwrap();
+        }
+
+        let gauge_vote = |validator: &Addr, percent: u64| GaugeVote {
+            validator: validator.to_string(),
+            share: Decimal::percent(percent),
+        };
+
+        // NoVotingPower error
+        {
+            let err = vote(
+                deps.as_mut(),
+                message_info(&no_balance, &[]),
+                vec![gauge_vote(&validator1, 100)],
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Validator(ValidatorError::NoVotingPower));
+        }
+
+        // InvalidGaugeVote error
+        {
+            for votes in [
+                vec![gauge_vote(&validator1, 60), gauge_vote(&validator2, 50)],
+                vec![gauge_vote(&validator1, 50), gauge_vote(&validator1, 50)],
+                vec![gauge_vote(&validator1, 0)],
+            ] {
+                let err = vote(deps.as_mut(), message_info(&voter1, &[]), votes).unwrap_err();
+                assert_eq!(
+                    err,
+                    ContractError::Validator(ValidatorError::InvalidGaugeVote)
+                );
+            }
+        }
+
+        // ValidatorNotFound error
+        {
+            let unknown = mock_api.addr_make("unknown");
+            let err = vote(
+                deps.as_mut(),
+                message_info(&voter1, &[]),
+                vec![gauge_vote(&unknown, 100)],
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::ValidatorNotFound)
+            );
+        }
+
+        // vote successfully
+        {
+            vote(
+                deps.as_mut(),
+                message_info(&voter1, &[]),
+                vec![gauge_vote(&validator1, 50), gauge_vote(&validator2, 50)],
+            )
+            .unwrap();
+            vote(
+                deps.as_mut(),
+                message_info(&voter2, &[]),
+                vec![gauge_vote(&validator1, 100)],
+            )
+            .unwrap();
+            vote(
+                deps.as_mut(),
+                message_info(&voter3, &[]),
+                vec![gauge_vote(&validator3, 100)],
+            )
+            .unwrap();
+
+            let result =
+                query_gauge_votes(deps.as_ref(), Some(voter2.to_string()), Some(1)).unwrap();
+            assert_eq!(
+                result,
+                vec![GaugeVoterResponse {
+                    voter: voter2.clone(),
+                    votes: vec![gauge_vote(&validator1, 100)],
+                }]
+            );
+        }
+
+        // GaugeVotersFull error, the set is filled up with dust voters
+        let dust_voters = (0..MAX_GAUGE_VOTERS - 3)
+            .map(|i| deps.api.addr_make(&format!("voter{}", i + 4)))
+            .collect::<Vec<_>>();
+        {
+            let voters = dust_voters.clone();
+            for voter in voters.iter() {
+                GAUGE_VOTES
+                    .save(
+                        deps.as_mut().storage,
+                        voter,
+                        &vec![gauge_vote(&validator1, 100)],
+                    )
+                    .unwrap();
+            }
+
+            let err = vote(
+                deps.as_mut(),
+                message_info(&no_balance, &[]),
+                vec![gauge_vote(&validator1, 100)],
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Validator(ValidatorError::NoVotingPower));
+
+            // voters already counted can still change their vote
+            vote(
+                deps.as_mut(),
+                message_info(&voter3, &[]),
+                vec![gauge_vote(&validator3, 100)],
+            )
+            .unwrap();
+
+            let mut balances = dust_voters
+                .iter()
+                .map(|voter| (voter.to_string(), 1))
+                .collect::<HashMap<_, _>>();
+            balances.extend([
+                (voter1.to_string(), 600),
+                (voter2.to_string(), 400),
+                (voter3.to_string(), 100),
+                (no_balance.to_string(), 1),
+                (whale.to_string(), 1000),
+            ]);
+            mock_lst_balances(&mut deps, &hub_contract, &lst_token, balances);
+
+            // a voter holding no more than the smallest voter cannot take its place
+            let err = vote(
+                deps.as_mut(),
+                message_info(&no_balance, &[]),
+                vec![gauge_vote(&validator1, 100)],
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::GaugeVotersFull)
+            );
+        }
+
+        // a large holder takes the place of a dust voter
+        {
+            let response = vote(
+                deps.as_mut(),
+                message_info(&whale, &[]),
+                vec![gauge_vote(&validator2, 100)],
+            )
+            .unwrap();
+            let evicted = response
+                .attributes
+                .iter()
+                .find(|attr| attr.key == "evicted")
+                .unwrap()
+                .value
+                .clone();
+            assert!(dust_voters.iter().any(|voter| voter.to_string() == evicted));
+            assert_eq!(
+                query_gauge_votes(deps.as_ref(), None, None).unwrap().len(),
+                MAX_GAUGE_VOTERS
+            );
+
+            // the dust voters and the large holder have sold their LST by the tally
+            mock_lst_balances(
+                &mut deps,
+                &hub_contract,
+                &lst_token,
+                HashMap::from([
+                    (voter1.to_string(), 600),
+                    (voter2.to_string(), 400),
+                    (voter3.to_string(), 0),
+                ]),
+            );
+        }
+
+        // InvalidGaugeWeightBounds error
+        {
+            // three validators cannot all stay under 30% or above 40%
+            for (min_weight, max_weight) in [(70, 60), (0, 30), (40, 100)] {
+                let err = update_gauge_config(
+                    deps.as_mut(),
+                    message_info(&owner, &[]),
+                    None,
+                    Some(Decimal::percent(min_weight)),
+                    Some(Decimal::percent(max_weight)),
+                )
+                .unwrap_err();
+                assert_eq!(
+                    err,
+                    ContractError::Validator(ValidatorError::InvalidGaugeWeightBounds)
+                );
+            }
+        }
+
+        // GaugeRequiresWeightedStrategy error
+        {
+            let err = tally_votes(deps.as_mut(), env.clone()).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::GaugeRequiresWeightedStrategy)
+            );
+        }
+
+        // tally votes with a floor and a ceiling
+        {
+            CONFIG
+                .update(deps.as_mut().storage, |mut config| -> StdResult<_> {
+                    config.delegation_strategy = DelegationStrategyKind::Weighted;
+                    Ok(config)
+                })
+                .unwrap();
+            update_gauge_config(
+                deps.as_mut(),
+                message_info(&owner, &[]),
+                None,
+                Some(Decimal::percent(10)),
+                Some(Decimal::percent(60)),
+            )
+            .unwrap();
+
+            // the voters without a balance are dropped
+            let response = tally_votes(deps.as_mut(), env.clone()).unwrap();
+            assert_eq!(
+                response.attributes,
+                vec![
+                    attr("action", "tally_votes"),
+                    attr("voters", "2"),
+                    attr("dropped_voters", (MAX_GAUGE_VOTERS - 2).to_string()),
+                ]
+            );
+            assert_eq!(
+                query_gauge_votes(deps.as_ref(), None, None).unwrap().len(),
+                2
+            );
+
+            // validator1 gets 700 of 1000 votes, validator2 300 and validator3 none
+            let result = query_gauge_results(deps.as_ref()).unwrap();
+            assert_eq!(result.tallied_at, env.block.time.seconds());
+            for (validator, power, percent, weight) in [
+                (&validator1, 700, 60, 600_000),
+                (&validator2, 300, 30, 300_000),
+                (&validator3, 0, 10, 100_000),
+            ] {
+                assert!(result.results.contains(&GaugeResult {
+                    validator: validator.to_string(),
+                    power: Uint128::new(power),
+                    weight: Decimal::percent(percent),
+                }));
+                assert_eq!(
+                    VALIDATOR_WEIGHTS
+                        .load(&deps.storage, validator.as_bytes())
+                        .unwrap(),
+                    weight
+                );
+            }
+        }
+
+        // GaugePeriodNotMet error
+        {
+            let err = tally_votes(deps.as_mut(), env.clone()).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Validator(ValidatorError::GaugePeriodNotMet)
+            );
+        }
+
+        // weights are kept when nobody votes
+        {
+            vote(deps.as_mut(), message_info(&voter1, &[]), vec![]).unwrap();
+            vote(deps.as_mut(), message_info(&voter2, &[]), vec![]).unwrap();
+            assert!(query_gauge_votes(deps.as_ref(), None, None)
+                .unwrap()
+                .is_empty());
+
+            env.block.time = env.block.time.plus_seconds(DEFAULT_GAUGE_PERIOD);
+            tally_votes(deps.as_mut(), env.clone()).unwrap();
+
+            assert!(query_gauge_results(deps.as_ref())
+                .unwrap()
+                .results
+                .is_empty());
+            assert_eq!(
+                VALIDATOR_WEIGHTS
+                    .load(&deps.storage, validator1.as_bytes())
+                    .unwrap(),
+                600_000
+            );
+        }
+    }
+}
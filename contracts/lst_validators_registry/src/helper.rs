@@ -1,5 +1,43 @@
-use cosmwasm_std::{QuerierWrapper, Validator};
-use lst_common::types::LstResult;
+use cosmos_sdk_proto::{
+    cosmos::{
+        base::query::v1beta1::PageRequest,
+        staking::v1beta1::{
+            BondStatus, QueryValidatorRequest, QueryValidatorResponse, QueryValidatorsRequest,
+            QueryValidatorsResponse,
+        },
+    },
+    prost::Message,
+};
+use cosmwasm_std::{Addr, Binary, Decimal, QuerierWrapper, StdError, Uint128, Validator};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+use lst_common::{
+    errors::HubError,
+    hub::{ConfigResponse, Parameters, QueryMsg as HubQueryMsg, ValidatorDelegationResponse},
+    types::LstResult,
+    ContractError,
+};
+
+const VALIDATOR_GRPC_QUERY_PATH: &str = "/cosmos.staking.v1beta1.Query/Validator";
+const VALIDATORS_GRPC_QUERY_PATH: &str = "/cosmos.staking.v1beta1.Query/Validators";
+
+// Decimals of the chain are encoded as integers with 18 decimal places
+const LEGACY_DEC_PLACES: u32 = 18;
+
+/// Validator state that isn't part of the cosmwasm staking query
+pub(crate) struct ValidatorStatus {
+    pub moniker: String,
+    pub bonded: bool,
+    pub jailed: bool,
+    pub voting_power: Uint128,
+}
+
+/// Bonded validator of the chain, as considered by auto-curation
+pub(crate) struct BondedValidator {
+    pub address: String,
+    pub jailed: bool,
+    pub voting_power: Uint128,
+    pub commission: Decimal,
+}
 
 pub(crate) fn fetch_validator_info(
     querier: &QuerierWrapper,
@@ -7,3 +45,134 @@ pub(crate) fn fetch_validator_info(
 ) -> LstResult<Option<Validator>> {
     Ok(querier.query_validator(val_address)?)
 }
+
+// Redelegations mature after the unstaking period of the chain, which is tracked by the hub
+pub(crate) fn fetch_unbonding_period(
+    querier: &QuerierWrapper,
+    hub_contract: &Addr,
+) -> LstResult<u64> {
+    let params: Parameters = querier.query_wasm_smart(hub_contract, &HubQueryMsg::Parameters {})?;
+    Ok(params.unstaking_period)
+}
+
+// The hub keeps a ledger of its delegations, which includes the delegations queued for the current epoch
+pub(crate) fn fetch_hub_delegations(
+    querier: &QuerierWrapper,
+    hub_contract: &Addr,
+) -> LstResult<Vec<ValidatorDelegationResponse>> {
+    Ok(querier.query_wasm_smart(hub_contract, &HubQueryMsg::Delegations {})?)
+}
+
+// Gauge votes are weighted by the balance of the LST token, which is tracked by the hub
+pub(crate) fn fetch_lst_token(querier: &QuerierWrapper, hub_contract: &Addr) -> LstResult<Addr> {
+    let config: ConfigResponse = querier.query_wasm_smart(hub_contract, &HubQueryMsg::Config {})?;
+    config
+        .lst_token
+        .map(Addr::unchecked)
+        .ok_or(HubError::LstTokenNotSet.into())
+}
+
+pub(crate) fn fetch_lst_balance(
+    querier: &QuerierWrapper,
+    lst_token: &Addr,
+    address: &Addr,
+) -> LstResult<Uint128> {
+    let response: BalanceResponse = querier.query_wasm_smart(
+        lst_token,
+        &Cw20QueryMsg::Balance {
+            address: address.to_string(),
+        },
+    )?;
+    Ok(response.balance)
+}
+
+// Queries the validator through the staking grpc query, which exposes the moniker, jailed flag and bonded tokens
+pub(crate) fn fetch_validator_status(
+    querier: &QuerierWrapper,
+    val_address: String,
+) -> LstResult<Option<ValidatorStatus>> {
+    let request = QueryValidatorRequest {
+        validator_addr: val_address,
+    };
+    let response = querier.query_grpc(
+        VALIDATOR_GRPC_QUERY_PATH.to_string(),
+        Binary::new(request.encode_to_vec()),
+    )?;
+    let response = QueryValidatorResponse::decode(response.as_slice())
+        .map_err(|e| StdError::parse_err("QueryValidatorResponse", e))?;
+
+    response
+        .validator
+        .map(|validator| {
+            Ok(ValidatorStatus {
+                moniker: validator
+                    .description
+                    .map(|description| description.moniker)
+                    .unwrap_or_default(),
+                bonded: validator.status == BondStatus::Bonded as i32,
+                jailed: validator.jailed,
+                voting_power: validator
+                    .tokens
+                    .parse::<u128>()
+                    .map(Uint128::new)
+                    .map_err(|e| ContractError::Std(StdError::parse_err("Uint128", e)))?,
+            })
+        })
+        .transpose()
+}
+
+// Queries every bonded validator of the chain, following the pagination of the staking grpc query
+pub(crate) fn fetch_bonded_validators(querier: &QuerierWrapper) -> LstResult<Vec<BondedValidator>> {
+    let mut validators = vec![];
+    let mut next_key = vec![];
+    loop {
+        let request = QueryValidatorsRequest {
+            status: BondStatus::Bonded.as_str_name().to_string(),
+            pagination: Some(PageRequest {
+                key: next_key,
+                ..Default::default()
+            }),
+        };
+        let response = querier.query_grpc(
+            VALIDATORS_GRPC_QUERY_PATH.to_string(),
+            Binary::new(request.encode_to_vec()),
+        )?;
+        let response = QueryValidatorsResponse::decode(response.as_slice())
+            .map_err(|e| StdError::parse_err("QueryValidatorsResponse", e))?;
+
+        for validator in response.validators {
+            let rate = validator
+                .commission
+                .and_then(|commission| commission.commission_rates)
+                .map(|rates| rates.rate)
+                .unwrap_or_default();
+            let commission = if rate.is_empty() {
+                Decimal::zero()
+            } else {
+                let atomics = rate
+                    .parse::<u128>()
+                    .map_err(|e| ContractError::Std(StdError::parse_err("Decimal", e)))?;
+                Decimal::from_atomics(atomics, LEGACY_DEC_PLACES)
+                    .map_err(|e| ContractError::Std(StdError::parse_err("Decimal", e)))?
+            };
+            validators.push(BondedValidator {
+                address: validator.operator_address,
+                jailed: validator.jailed,
+                voting_power: validator
+                    .tokens
+                    .parse::<u128>()
+                    .map(Uint128::new)
+                    .map_err(|e| ContractError::Std(StdError::parse_err("Uint128", e)))?,
+                commission,
+            });
+        }
+
+        next_key = response
+            .pagination
+            .map(|pagination| pagination.next_key)
+            .unwrap_or_default();
+        if next_key.is_empty() {
+            return Ok(validators);
+        }
+    }
+}
@@ -1,4 +1,6 @@
 pub mod contract;
+pub mod curation;
+pub mod gauge;
 pub mod helper;
 mod state;
 pub mod testing;
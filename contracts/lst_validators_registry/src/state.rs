@@ -1,7 +1,152 @@
-use cw_storage_plus::{Item, Map};
-use lst_common::validator::{Config, Validator};
+use cosmwasm_std::{Addr, Env, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map};
+use lst_common::validator::{
+    Config, CurationConfig, GaugeConfig, GaugeResult, GaugeVote, PendingRedelegation, Validator,
+    ValidatorApplication, ValidatorMetadata, ValidatorSetAction, ValidatorSetChange,
+    ValidatorSnapshot,
+};
 
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const VALIDATOR_REGISTRY: Map<&[u8], Validator> = Map::new("validator_registry");
 
+/// Weights of the validators used by the weighted delegation strategy, validators without an entry have the default weight
+pub const VALIDATOR_WEIGHTS: Map<&[u8], u64> = Map::new("validator_weights");
+
 pub const VALIDATOR_EXCLUDE_LIST: Map<String, bool> = Map::new("validator_exclude_list");
+
+/// Soft-removed validators and the time they were removed at
+pub const SOFT_REMOVED_VALIDATORS: Map<String, u64> = Map::new("soft_removed_validators");
+
+/// Last excluded validator handled by process redelegations, the next call continues after it
+pub const REDELEGATION_CURSOR: Item<String> = Item::new("redelegation_cursor");
+
+pub const LAST_REBALANCE_TIME: Item<u64> = Item::new("last_rebalance_time");
+
+/// Metadata recorded when a validator is added to the registry
+pub const VALIDATOR_METADATA: Map<&str, ValidatorMetadata> = Map::new("validator_metadata");
+
+/// Performance snapshots of the validators, keyed by (validator, snapshot time)
+pub const VALIDATOR_SNAPSHOTS: Map<(&str, u64), ValidatorSnapshot> =
+    Map::new("validator_snapshots");
+
+pub const LAST_SNAPSHOT_TIME: Item<u64> = Item::new("last_snapshot_time");
+
+/// Append-only log of the validator set changes, keyed by change id
+pub const VALIDATOR_SET_CHANGES: Map<u64, ValidatorSetChange> = Map::new("validator_set_changes");
+pub const VALIDATOR_SET_CHANGE_COUNT: Item<u64> = Item::new("validator_set_change_count");
+
+/// Pending validator applications, keyed by validator address
+pub const VALIDATOR_APPLICATIONS: Map<&str, ValidatorApplication> =
+    Map::new("validator_applications");
+
+pub const CURATION_CONFIG: Item<CurationConfig> = Item::new("curation_config");
+pub const LAST_CURATION_TIME: Item<u64> = Item::new("last_curation_time");
+
+pub const GAUGE_CONFIG: Item<GaugeConfig> = Item::new("gauge_config");
+/// Current gauge votes, keyed by voter
+pub const GAUGE_VOTES: Map<&Addr, Vec<GaugeVote>> = Map::new("gauge_votes");
+pub const GAUGE_RESULTS: Item<Vec<GaugeResult>> = Item::new("gauge_results");
+pub const LAST_GAUGE_TALLY_TIME: Item<u64> = Item::new("last_gauge_tally_time");
+
+/// Redelegations issued through the hub that have not matured yet, keyed by (source validator, completion time)
+pub const PENDING_REDELEGATIONS: Map<(String, u64), PendingRedelegation> =
+    Map::new("pending_redelegations");
+/// Destinations of the pending redelegations, keyed by (destination validator, completion time)
+pub const INBOUND_REDELEGATIONS: Map<(&str, u64), ()> = Map::new("inbound_redelegations");
+
+// Record a redelegation issued through the hub.
+// Redelegations from the same source completing at the same time are merged into a single entry
+pub fn save_pending_redelegation(
+    storage: &mut dyn Storage,
+    redelegation: PendingRedelegation,
+) -> StdResult<()> {
+    for (dst_validator, _) in redelegation.redelegations.iter() {
+        INBOUND_REDELEGATIONS.save(storage, (dst_validator, redelegation.timestamp), &())?;
+    }
+
+    let key = (redelegation.src_validator.clone(), redelegation.timestamp);
+    PENDING_REDELEGATIONS.update(storage, key, |existing| -> StdResult<_> {
+        Ok(match existing {
+            Some(mut existing) => {
+                existing.redelegations.extend(redelegation.redelegations);
+                existing
+            }
+            None => redelegation,
+        })
+    })?;
+    Ok(())
+}
+
+// Remove the redelegations that have matured by the given time
+pub fn prune_matured_redelegations(storage: &mut dyn Storage, now: u64) -> StdResult<()> {
+    let keys = PENDING_REDELEGATIONS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for key in keys {
+        if key.1 <= now {
+            PENDING_REDELEGATIONS.remove(storage, key);
+        }
+    }
+
+    let inbound_keys = INBOUND_REDELEGATIONS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (dst_validator, completion_time) in inbound_keys {
+        if completion_time <= now {
+            INBOUND_REDELEGATIONS.remove(storage, (&dst_validator, completion_time));
+        }
+    }
+    Ok(())
+}
+
+// A validator that received a redelegation cannot redelegate onward until the redelegation has matured
+pub fn is_receiving_redelegation(
+    storage: &dyn Storage,
+    validator: &str,
+    now: u64,
+) -> StdResult<bool> {
+    Ok(INBOUND_REDELEGATIONS
+        .prefix(validator)
+        .keys(storage, Some(Bound::exclusive(now)), None, Order::Ascending)
+        .next()
+        .transpose()?
+        .is_some())
+}
+
+// Append a change to the validator set change log
+pub fn record_validator_set_change(
+    storage: &mut dyn Storage,
+    env: &Env,
+    action: ValidatorSetAction,
+    validator: String,
+    actor: Addr,
+    reason: Option<String>,
+) -> StdResult<()> {
+    let id = VALIDATOR_SET_CHANGE_COUNT
+        .may_load(storage)?
+        .unwrap_or_default();
+    VALIDATOR_SET_CHANGES.save(
+        storage,
+        id,
+        &ValidatorSetChange {
+            id,
+            action,
+            validator,
+            actor,
+            height: env.block.height,
+            timestamp: env.block.time.seconds(),
+            reason,
+        },
+    )?;
+    VALIDATOR_SET_CHANGE_COUNT.save(storage, &(id + 1))
+}
+
+// Gauge parameters, the defaults apply until the owner updates them
+pub fn load_gauge_config(storage: &dyn Storage) -> StdResult<GaugeConfig> {
+    Ok(GAUGE_CONFIG.may_load(storage)?.unwrap_or_default())
+}
+
+// Auto-curation parameters, curation stays disabled until the owner enables it
+pub fn load_curation_config(storage: &dyn Storage) -> StdResult<CurationConfig> {
+    Ok(CURATION_CONFIG.may_load(storage)?.unwrap_or_default())
+}
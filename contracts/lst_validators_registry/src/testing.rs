@@ -1,7 +1,21 @@
 #![cfg(not(target_arch = "wasm32"))]
 
+use std::{collections::HashMap, marker::PhantomData};
+
 use crate::contract::{execute, instantiate, query};
-use cosmwasm_std::{Addr, Env};
+use cosmos_sdk_proto::{
+    cosmos::staking::v1beta1::{
+        BondStatus, Commission, CommissionRates, Description, QueryValidatorRequest,
+        QueryValidatorResponse, QueryValidatorsResponse, Validator as ProtoValidator,
+    },
+    prost::Message,
+};
+use cosmwasm_std::{
+    from_json,
+    testing::{MockApi, MockQuerier, MockStorage},
+    Addr, Binary, ContractResult, Decimal, Empty, Env, GrpcQuery, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemResult,
+};
 use cw_multi_test::{Contract, ContractWrapper};
 use lst_common::babylon::{EpochingMsg, EpochingQuery};
 use lst_common::testing::{BabylonApp, TestingContract};
@@ -37,3 +51,81 @@ impl TestingContract<InstantiateMsg, ExecuteMsg, QueryMsg> for ValidatorRegistry
         &self.addr
     }
 }
+
+/// Mock querier answering the staking grpc validator queries, other queries go to the base querier
+pub struct GrpcMockQuerier {
+    pub base: MockQuerier,
+    pub validators: HashMap<String, ProtoValidator>,
+}
+
+impl Querier for GrpcMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        match from_json::<QueryRequest<Empty>>(bin_request) {
+            Ok(QueryRequest::Grpc(GrpcQuery { path, data }))
+                if path == "/cosmos.staking.v1beta1.Query/Validator" =>
+            {
+                let request = QueryValidatorRequest::decode(data.as_slice()).unwrap();
+                let response = QueryValidatorResponse {
+                    validator: self.validators.get(&request.validator_addr).cloned(),
+                };
+                SystemResult::Ok(ContractResult::Ok(Binary::new(response.encode_to_vec())))
+            }
+            // all bonded validators are returned in a single page
+            Ok(QueryRequest::Grpc(GrpcQuery { path, .. }))
+                if path == "/cosmos.staking.v1beta1.Query/Validators" =>
+            {
+                let response = QueryValidatorsResponse {
+                    validators: self
+                        .validators
+                        .values()
+                        .filter(|validator| validator.status == BondStatus::Bonded as i32)
+                        .cloned()
+                        .collect(),
+                    pagination: None,
+                };
+                SystemResult::Ok(ContractResult::Ok(Binary::new(response.encode_to_vec())))
+            }
+            _ => self.base.raw_query(bin_request),
+        }
+    }
+}
+
+pub fn mock_dependencies_with_grpc() -> OwnedDeps<MockStorage, MockApi, GrpcMockQuerier> {
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: GrpcMockQuerier {
+            base: MockQuerier::default(),
+            validators: HashMap::new(),
+        },
+        custom_query_type: PhantomData,
+    }
+}
+
+/// Bonded validator as returned by the staking grpc queries, with a 5% commission
+pub fn proto_validator(address: &str, moniker: &str, jailed: bool, tokens: u128) -> ProtoValidator {
+    ProtoValidator {
+        operator_address: address.to_string(),
+        jailed,
+        status: BondStatus::Bonded as i32,
+        tokens: tokens.to_string(),
+        description: Some(Description {
+            moniker: moniker.to_string(),
+            ..Default::default()
+        }),
+        commission: Some(proto_commission(Decimal::percent(5))),
+        ..Default::default()
+    }
+}
+
+/// Commission as encoded by the chain, where decimals are integers with 18 decimal places
+pub fn proto_commission(rate: Decimal) -> Commission {
+    Commission {
+        commission_rates: Some(CommissionRates {
+            rate: rate.atomics().to_string(),
+            max_rate: Decimal::one().atomics().to_string(),
+            max_change_rate: Decimal::one().atomics().to_string(),
+        }),
+        update_time: None,
+    }
+}
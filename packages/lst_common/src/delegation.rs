@@ -1,118 +1,764 @@
-use std::ops::Sub;
+use std::cmp::Reverse;
 
-use cosmwasm_std::Uint128;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Decimal, Uint128};
 
 use crate::validator::ValidatorResponse;
 use crate::{types::LstResult, ContractError, ValidatorError};
 
-pub fn calculate_delegations(
-    mut amt_to_delegate: Uint128,
+/// Decides how stake is spread across the validators of the registry.
+/// Amounts are returned in the same order as `validators`.
+pub trait DelegationStrategy {
+    /// Amount to delegate to each validator so that `amount` is delegated in total
+    fn delegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>>;
+
+    /// Amount to undelegate from each validator so that `amount` is undelegated in total
+    fn undelegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>>;
+
+    /// Delegation each validator should hold for the current total delegation
+    fn target_delegations(&self, validators: &[ValidatorResponse]) -> LstResult<Vec<Uint128>> {
+        calculate_target_delegations(validators)
+    }
+}
+
+/// Delegation strategy used by the registry, selectable in its config
+#[cw_serde]
+#[derive(Default)]
+pub enum DelegationStrategyKind {
+    /// Keep every validator at the same delegation
+    #[default]
+    EqualSplit,
+    /// Keep every validator at a delegation proportional to its weight
+    Weighted,
+    /// Delegate to the smallest validators first and undelegate from the largest first
+    InverseStake,
+    /// Use as few delegate/undelegate messages as possible
+    MinimalMessage,
+}
+
+impl DelegationStrategy for DelegationStrategyKind {
+    fn delegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        match self {
+            DelegationStrategyKind::EqualSplit => EqualSplit.delegations(amount, validators),
+            DelegationStrategyKind::Weighted => Weighted.delegations(amount, validators),
+            DelegationStrategyKind::InverseStake => InverseStake.delegations(amount, validators),
+            DelegationStrategyKind::MinimalMessage => {
+                MinimalMessage.delegations(amount, validators)
+            }
+        }
+    }
+
+    fn undelegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        match self {
+            DelegationStrategyKind::EqualSplit => EqualSplit.undelegations(amount, validators),
+            DelegationStrategyKind::Weighted => Weighted.undelegations(amount, validators),
+            DelegationStrategyKind::InverseStake => InverseStake.undelegations(amount, validators),
+            DelegationStrategyKind::MinimalMessage => {
+                MinimalMessage.undelegations(amount, validators)
+            }
+        }
+    }
+
+    fn target_delegations(&self, validators: &[ValidatorResponse]) -> LstResult<Vec<Uint128>> {
+        match self {
+            DelegationStrategyKind::Weighted => Weighted.target_delegations(validators),
+            _ => calculate_target_delegations(validators),
+        }
+    }
+}
+
+/// Spread the stake evenly across the validators
+pub struct EqualSplit;
+
+impl DelegationStrategy for EqualSplit {
+    fn delegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        calculate_delegations(amount, validators)
+    }
+
+    fn undelegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        calculate_undelegations(amount, validators)
+    }
+}
+
+/// Spread the stake proportionally to the weight of each validator
+pub struct Weighted;
+
+impl DelegationStrategy for Weighted {
+    fn delegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        let total_delegated = total_delegated(validators)?;
+        let total_after_delegation = total_delegated
+            .checked_add(amount)
+            .map_err(|e| ContractError::Overflow(e.to_string()))?;
+        let targets = weighted_targets(total_after_delegation, validators)?;
+
+        // every validator below its target takes its deficit until the amount is delegated
+        let mut remaining = amount;
+        let mut delegations = vec![Uint128::zero(); validators.len()];
+        for (index, (validator, target)) in validators.iter().zip(targets).enumerate() {
+            let to_delegate = target
+                .saturating_sub(validator.total_delegated)
+                .min(remaining);
+            delegations[index] = to_delegate;
+            remaining -= to_delegate;
+        }
+
+        if !remaining.is_zero() {
+            return Err(ValidatorError::DistributionFailed.into());
+        }
+        Ok(delegations)
+    }
+
+    fn undelegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        let total_delegated = total_delegated(validators)?;
+        if amount > total_delegated {
+            return Err(ValidatorError::ExceedUndelegation.into());
+        }
+        let targets = weighted_targets(total_delegated - amount, validators)?;
+
+        // every validator above its target gives up its surplus until the amount is undelegated
+        let mut remaining = amount;
+        let mut undelegations = vec![Uint128::zero(); validators.len()];
+        for (index, (validator, target)) in validators.iter().zip(targets).enumerate() {
+            let to_undelegate = validator
+                .total_delegated
+                .saturating_sub(target)
+                .min(remaining);
+            undelegations[index] = to_undelegate;
+            remaining -= to_undelegate;
+        }
+
+        if !remaining.is_zero() {
+            return Err(ValidatorError::DistributionFailed.into());
+        }
+        Ok(undelegations)
+    }
+
+    fn target_delegations(&self, validators: &[ValidatorResponse]) -> LstResult<Vec<Uint128>> {
+        weighted_targets(total_delegated(validators)?, validators)
+    }
+}
+
+/// Favour the smaller validators: delegations raise the smallest validators to a common level
+/// and undelegations cut the largest validators down to a common level
+pub struct InverseStake;
+
+impl DelegationStrategy for InverseStake {
+    fn delegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        if validators.is_empty() {
+            return Err(ValidatorError::EmptyValidatorSet.into());
+        }
+
+        let mut order: Vec<usize> = (0..validators.len()).collect();
+        order.sort_by_key(|index| validators[*index].total_delegated);
+
+        // find the k smallest validators that can be raised to a common level above the next one
+        let mut level_sum = amount;
+        let mut count = 0;
+        for (position, index) in order.iter().enumerate() {
+            level_sum = level_sum
+                .checked_add(validators[*index].total_delegated)
+                .map_err(|e| ContractError::Overflow(e.to_string()))?;
+            count = position + 1;
+
+            let level = level_sum.u128() / count as u128;
+            match order.get(count) {
+                Some(next) if level > validators[*next].total_delegated.u128() => continue,
+                _ => break,
+            }
+        }
+
+        let level = level_sum.u128() / count as u128;
+        let extra = (level_sum.u128() % count as u128) as usize;
+
+        let mut delegations = vec![Uint128::zero(); validators.len()];
+        for (position, index) in order.iter().take(count).enumerate() {
+            let extra_coin = if position < extra { 1u128 } else { 0u128 };
+            delegations[*index] =
+                Uint128::new(level + extra_coin) - validators[*index].total_delegated;
+        }
+        Ok(delegations)
+    }
+
+    fn undelegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        let total_delegated = total_delegated(validators)?;
+        if amount > total_delegated {
+            return Err(ValidatorError::ExceedUndelegation.into());
+        }
+
+        let mut order: Vec<usize> = (0..validators.len()).collect();
+        order.sort_by_key(|index| Reverse(validators[*index].total_delegated));
+
+        // find the k largest validators that can be cut to a common level below the next one
+        let mut group_sum = Uint128::zero();
+        let mut count = 0;
+        for (position, index) in order.iter().enumerate() {
+            group_sum += validators[*index].total_delegated;
+            count = position + 1;
+
+            let Some(next) = order.get(count) else {
+                break;
+            };
+            if group_sum >= amount
+                && (group_sum - amount).u128() / count as u128
+                    >= validators[*next].total_delegated.u128()
+            {
+                break;
+            }
+        }
+
+        let level_sum = group_sum - amount;
+        let level = Uint128::new(level_sum.u128() / count as u128);
+        let mut extra = Uint128::new(level_sum.u128() % count as u128);
+
+        let mut undelegations = vec![Uint128::zero(); validators.len()];
+        for index in order.iter().take(count) {
+            let mut to_undelegate = validators[*index].total_delegated - level;
+            // leave the rounding remainder on the largest validators
+            let kept = extra.min(to_undelegate);
+            to_undelegate -= kept;
+            extra -= kept;
+            undelegations[*index] = to_undelegate;
+        }
+        Ok(undelegations)
+    }
+}
+
+/// Use as few messages as possible: delegations go to the single smallest validator
+/// and undelegations drain the largest validators first
+pub struct MinimalMessage;
+
+impl DelegationStrategy for MinimalMessage {
+    fn delegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        let smallest = validators
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, validator)| validator.total_delegated)
+            .map(|(index, _)| index)
+            .ok_or(ValidatorError::EmptyValidatorSet)?;
+
+        let mut delegations = vec![Uint128::zero(); validators.len()];
+        delegations[smallest] = amount;
+        Ok(delegations)
+    }
+
+    fn undelegations(
+        &self,
+        amount: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        let total_delegated = total_delegated(validators)?;
+        if amount > total_delegated {
+            return Err(ValidatorError::ExceedUndelegation.into());
+        }
+
+        let mut order: Vec<usize> = (0..validators.len()).collect();
+        order.sort_by_key(|index| Reverse(validators[*index].total_delegated));
+
+        let mut remaining = amount;
+        let mut undelegations = vec![Uint128::zero(); validators.len()];
+        for index in order {
+            if remaining.is_zero() {
+                break;
+            }
+            let to_undelegate = validators[index].total_delegated.min(remaining);
+            undelegations[index] = to_undelegate;
+            remaining -= to_undelegate;
+        }
+        Ok(undelegations)
+    }
+}
+
+/// Maximum delegation a validator can hold for the given total hub stake
+pub fn concentration_cap(total_stake: Uint128, max_share: Decimal) -> Uint128 {
+    total_stake.mul_floor(max_share)
+}
+
+/// Delegations of `amount` spread by `strategy`, where validators reaching `max_share` of the total
+/// hub stake after the delegation are treated as saturated and their share goes to the others.
+/// `total_stake` is the current stake of the hub across all its validators.
+/// When every validator is saturated the rest is spread by the strategy alone, so staking never fails on the cap
+pub fn capped_delegations<S: DelegationStrategy>(
+    strategy: &S,
+    amount: Uint128,
     validators: &[ValidatorResponse],
+    total_stake: Uint128,
+    max_share: Decimal,
 ) -> LstResult<Vec<Uint128>> {
-    if validators.is_empty() {
-        return Err(ValidatorError::EmptyValidatorSet.into());
+    if max_share >= Decimal::one() {
+        return strategy.delegations(amount, validators);
+    }
+
+    let total_after_delegation = total_stake
+        .checked_add(amount)
+        .map_err(|e| ContractError::Overflow(e.to_string()))?;
+    let cap = concentration_cap(total_after_delegation, max_share);
+
+    let mut delegations = vec![Uint128::zero(); validators.len()];
+    let mut remaining = amount;
+    // every round either places the whole remainder or saturates at least one validator
+    while !remaining.is_zero() {
+        let open: Vec<usize> = (0..validators.len())
+            .filter(|index| validators[*index].total_delegated + delegations[*index] < cap)
+            .collect();
+        if open.is_empty() {
+            break;
+        }
+
+        let current = open
+            .iter()
+            .map(|index| ValidatorResponse {
+                total_delegated: validators[*index].total_delegated + delegations[*index],
+                ..validators[*index].clone()
+            })
+            .collect::<Vec<_>>();
+        let placed = strategy.delegations(remaining, &current)?;
+        for ((index, validator), amount) in open.iter().zip(&current).zip(placed) {
+            let to_delegate = amount.min(cap - validator.total_delegated);
+            delegations[*index] += to_delegate;
+            remaining -= to_delegate;
+        }
+    }
+
+    if !remaining.is_zero() {
+        let current = validators
+            .iter()
+            .zip(&delegations)
+            .map(|(validator, delegation)| ValidatorResponse {
+                total_delegated: validator.total_delegated + delegation,
+                ..validator.clone()
+            })
+            .collect::<Vec<_>>();
+        let placed = strategy.delegations(remaining, &current)?;
+        for (delegation, amount) in delegations.iter_mut().zip(placed) {
+            *delegation += amount;
+        }
+    }
+
+    Ok(delegations)
+}
+
+/// Targets with no validator above `cap`, the excess of the capped validators goes to the others in
+/// proportion to their target without pushing them over the cap either.
+/// When the validators cannot hold the total under the cap the targets are returned unchanged
+pub fn capped_targets(targets: &[Uint128], cap: Uint128) -> Vec<Uint128> {
+    let total: Uint128 = targets.iter().sum();
+    if cap.full_mul(targets.len() as u128) < total.into() {
+        return targets.to_vec();
+    }
+
+    // every round either fits the open validators under the cap or caps at least one more of them
+    let mut capped = vec![false; targets.len()];
+    loop {
+        let open: Vec<usize> = (0..targets.len()).filter(|index| !capped[*index]).collect();
+        let capped_count = (targets.len() - open.len()) as u128;
+        let remaining = total - cap * Uint128::new(capped_count);
+        let open_total: Uint128 = open.iter().map(|index| targets[*index]).sum();
+        let scaled = |index: usize| {
+            if open_total.is_zero() {
+                remaining / Uint128::new(open.len() as u128)
+            } else {
+                targets[index].multiply_ratio(remaining, open_total)
+            }
+        };
+
+        let over: Vec<usize> = open
+            .iter()
+            .copied()
+            .filter(|index| scaled(*index) > cap)
+            .collect();
+        if !over.is_empty() {
+            for index in over {
+                capped[index] = true;
+            }
+            continue;
+        }
+
+        let mut result: Vec<Uint128> = (0..targets.len())
+            .map(|index| if capped[index] { cap } else { scaled(index) })
+            .collect();
+        // the rounding leftover goes one coin each to the first open validators below the cap
+        let mut leftover = remaining - open.iter().map(|index| result[*index]).sum::<Uint128>();
+        for index in open {
+            if leftover.is_zero() {
+                break;
+            }
+            if result[index] < cap {
+                result[index] += Uint128::one();
+                leftover -= Uint128::one();
+            }
+        }
+        return result;
+    }
+}
+
+/// Delegations reduced to at most `max_validators` validators receiving at least `min_chunk` each, zero disables a bound.
+/// The validators with the largest planned delegation are the most under their target and are kept first,
+/// the amount of the dropped validators is added to the kept ones in proportion to their planned delegation.
+/// No validator is pushed over `max_share` of the total hub stake by the added amount, the cap goes before the bounds.
+/// The imbalance left behind is corrected by later inflows and rebalancing
+pub fn bounded_delegations(
+    delegations: Vec<Uint128>,
+    validators: &[ValidatorResponse],
+    total_stake: Uint128,
+    max_share: Decimal,
+    max_validators: u32,
+    min_chunk: Uint128,
+) -> Vec<Uint128> {
+    let mut order: Vec<usize> = (0..delegations.len())
+        .filter(|index| !delegations[*index].is_zero())
+        .collect();
+    order.sort_by_key(|index| Reverse(delegations[*index]));
+
+    let mut kept = order.len();
+    if max_validators > 0 {
+        kept = kept.min(max_validators as usize);
+    }
+    if kept == order.len() && order.iter().all(|index| delegations[*index] >= min_chunk) {
+        return delegations;
     }
 
-    let total_delegated: u128 = validators
+    // the smallest kept validators are dropped until every chunk reaches the minimum, the largest one always stays
+    let amount: Uint128 = order.iter().map(|index| delegations[*index]).sum();
+    let mut kept_sum: Uint128 = order
         .iter()
-        .map(|val| val.total_delegated.u128())
+        .take(kept)
+        .map(|index| delegations[*index])
         .sum();
+    while kept > 1 && delegations[order[kept - 1]].multiply_ratio(amount, kept_sum) < min_chunk {
+        kept -= 1;
+        kept_sum -= delegations[order[kept]];
+    }
 
-    let total_coins_to_distribute = Uint128::from(total_delegated) + amt_to_delegate;
-    let (coins_per_val, remaining_coins) =
-        distribute_coins(total_coins_to_distribute, validators.len());
+    let room: Vec<Uint128> = if max_share >= Decimal::one() {
+        vec![Uint128::MAX; delegations.len()]
+    } else {
+        let cap = concentration_cap(total_stake.saturating_add(amount), max_share);
+        validators
+            .iter()
+            .map(|validator| cap.saturating_sub(validator.total_delegated))
+            .collect()
+    };
 
-    let target_delegations = target_coins_per_validator(coins_per_val, remaining_coins, validators);
+    let mut bounded = vec![Uint128::zero(); delegations.len()];
+    let mut remaining = amount;
+    for index in order.iter().take(kept) {
+        let chunk = delegations[*index]
+            .multiply_ratio(amount, kept_sum)
+            .min(room[*index]);
+        bounded[*index] = chunk;
+        remaining -= chunk;
+    }
+    // what the cap holds back goes to the kept validators with room left, then to the dropped ones
+    for index in order.iter() {
+        let extra = room[*index].saturating_sub(bounded[*index]).min(remaining);
+        bounded[*index] += extra;
+        remaining -= extra;
+    }
+    if let Some(first) = order.first() {
+        bounded[*first] += remaining;
+    }
+    bounded
+}
 
-    let mut delegations = vec![Uint128::zero(); validators.len()];
+/// Whether `count` validators can share the whole stake with every share between `min_weight` and `max_weight`
+pub fn gauge_bounds_feasible(count: usize, min_weight: Decimal, max_weight: Decimal) -> bool {
+    let count = Decimal::from_ratio(count as u128, 1u128);
+    min_weight <= max_weight
+        && min_weight
+            .checked_mul(count)
+            .is_ok_and(|floor| floor <= Decimal::one())
+        && max_weight
+            .checked_mul(count)
+            .is_ok_and(|ceiling| ceiling >= Decimal::one())
+}
 
-    for (index, (validator, target_delegation)) in
-        validators.iter().zip(target_delegations).enumerate()
-    {
-        let val_current_delegation = validator.total_delegated;
+/// Share of the stake each validator gets from its gauge voting power, kept between `min_weight` and `max_weight`.
+/// Every weight is `clamp(t * power share, min_weight, max_weight)` for the single scale `t` at which the weights
+/// sum to one. The sum is piecewise linear in `t`, with breakpoints where a validator reaches one of its bounds,
+/// so `t` is solved exactly on the segment where the sum crosses one
+pub fn gauge_weights(powers: &[Uint128], min_weight: Decimal, max_weight: Decimal) -> Vec<Decimal> {
+    let total_power: Uint128 = powers.iter().sum();
+    if total_power.is_zero() {
+        let equal_share = Decimal::from_ratio(1u128, powers.len().max(1) as u128);
+        return vec![equal_share.clamp(min_weight, max_weight); powers.len()];
+    }
+
+    let shares: Vec<Decimal> = powers
+        .iter()
+        .map(|power| Decimal::from_ratio(*power, total_power))
+        .collect();
+    let weight_at = |scale: Decimal, share: Decimal| {
+        share
+            .checked_mul(scale)
+            .unwrap_or(Decimal::MAX)
+            .clamp(min_weight, max_weight)
+    };
+    let bounds_at = |share: Decimal| (min_weight / share, max_weight / share);
+
+    let mut breakpoints: Vec<Decimal> = shares
+        .iter()
+        .filter(|share| !share.is_zero())
+        .flat_map(|share| {
+            let (lower, upper) = bounds_at(*share);
+            [lower, upper]
+        })
+        .collect();
+    breakpoints.sort();
+    breakpoints.dedup();
 
-        if target_delegation < val_current_delegation {
+    let mut low = Decimal::zero();
+    for breakpoint in breakpoints {
+        let total: Decimal = shares
+            .iter()
+            .map(|share| weight_at(breakpoint, *share))
+            .sum();
+        if total < Decimal::one() {
+            low = breakpoint;
             continue;
         }
 
-        let mut to_delegate = target_delegation.sub(val_current_delegation);
-
-        if to_delegate > amt_to_delegate {
-            to_delegate = amt_to_delegate;
+        // between `low` and `breakpoint` each validator is either held at a bound or grows linearly with the scale
+        let mut fixed = Decimal::zero();
+        let mut free_share = Decimal::zero();
+        for share in shares.iter() {
+            let linear = !share.is_zero() && {
+                let (lower, upper) = bounds_at(*share);
+                lower <= low && upper >= breakpoint
+            };
+            if linear {
+                free_share += share;
+            } else {
+                fixed += weight_at(breakpoint, *share);
+            }
         }
+        let scale = if free_share.is_zero() {
+            breakpoint
+        } else {
+            Decimal::one().saturating_sub(fixed) / free_share
+        };
+        return shares
+            .iter()
+            .map(|share| weight_at(scale, *share))
+            .collect();
+    }
 
-        delegations[index] = to_delegate;
-        amt_to_delegate = amt_to_delegate
-            .checked_sub(to_delegate)
-            .map_err(|e| ContractError::Overflow(e.to_string()))?;
+    // the ceilings add up to less than one, every validator is held at its ceiling
+    vec![max_weight; powers.len()]
+}
 
-        if amt_to_delegate.is_zero() {
-            return Ok(delegations);
-        }
+// Equal-split delegation of `amt_to_delegate` in a single pass over the validators,
+// every validator below its target takes its deficit until the amount is delegated
+pub fn calculate_delegations(
+    amt_to_delegate: Uint128,
+    validators: &[ValidatorResponse],
+) -> LstResult<Vec<Uint128>> {
+    let total_coins_to_distribute = total_delegated(validators)?
+        .checked_add(amt_to_delegate)
+        .map_err(|e| ContractError::Overflow(e.to_string()))?;
+    let (coins_per_val, remaining_coins) =
+        distribute_coins(total_coins_to_distribute, validators.len());
+    let target_delegations = target_coins_per_validator(coins_per_val, remaining_coins, validators);
+
+    let mut remaining = amt_to_delegate;
+    let delegations = validators
+        .iter()
+        .zip(target_delegations)
+        .map(|(validator, target_delegation)| {
+            let to_delegate = target_delegation
+                .saturating_sub(validator.total_delegated)
+                .min(remaining);
+            remaining -= to_delegate;
+            to_delegate
+        })
+        .collect();
+
+    if !remaining.is_zero() {
+        return Err(ValidatorError::DistributionFailed.into());
     }
-    Err(ValidatorError::DistributionFailed.into())
+    Ok(delegations)
 }
 
+// Equal-split undelegation of `amt_to_undelegate` in a single pass over the validators,
+// every validator above its target gives up its surplus until the amount is undelegated.
+// The surpluses add up to at least the amount, so one pass always places all of it. Nothing to undelegate is an error
 pub fn calculate_undelegations(
-    mut amt_to_undelegate: Uint128,
-    mut validators: Vec<ValidatorResponse>,
+    amt_to_undelegate: Uint128,
+    validators: &[ValidatorResponse],
 ) -> LstResult<Vec<Uint128>> {
+    let total_delegated = total_delegated(validators)?;
+    if amt_to_undelegate > total_delegated {
+        return Err(ValidatorError::ExceedUndelegation.into());
+    }
+    if amt_to_undelegate.is_zero() {
+        return Err(ValidatorError::DistributionFailed.into());
+    }
+
+    let (coins_per_val, remaining_coins) =
+        distribute_coins(total_delegated - amt_to_undelegate, validators.len());
+    let target_undelegations =
+        target_coins_per_validator(coins_per_val, remaining_coins, validators);
+
+    let mut remaining = amt_to_undelegate;
+    let undelegations = validators
+        .iter()
+        .zip(target_undelegations)
+        .map(|(validator, target_undelegation)| {
+            let to_undelegate = validator
+                .total_delegated
+                .saturating_sub(target_undelegation)
+                .min(remaining);
+            remaining -= to_undelegate;
+            to_undelegate
+        })
+        .collect();
+
+    if !remaining.is_zero() {
+        return Err(ValidatorError::DistributionFailed.into());
+    }
+    Ok(undelegations)
+}
+
+// Computes the equal-split target delegation of each validator for the current total delegation
+pub fn calculate_target_delegations(validators: &[ValidatorResponse]) -> LstResult<Vec<Uint128>> {
     if validators.is_empty() {
         return Err(ValidatorError::EmptyValidatorSet.into());
     }
 
-    let mut total_delegated = validators.iter().map(|val| val.total_delegated).sum();
+    let total_delegated: Uint128 = validators.iter().map(|val| val.total_delegated).sum();
+    let (coins_per_val, remaining_coins) = distribute_coins(total_delegated, validators.len());
 
-    if amt_to_undelegate > total_delegated {
-        return Err(ValidatorError::ExceedUndelegation.into());
+    Ok(target_coins_per_validator(
+        coins_per_val,
+        remaining_coins,
+        validators,
+    ))
+}
+
+// Computes the redelegations needed to move the validators towards their target delegation.
+// Moves are returned as (source index, destination index, amount), largest surplus and deficit first,
+// and at most `max_moves` of them are returned. Validators flagged in `locked` are never used as a source.
+pub fn calculate_rebalance_moves(
+    validators: &[ValidatorResponse],
+    targets: &[Uint128],
+    max_moves: usize,
+    locked: &[bool],
+) -> Vec<(usize, usize, Uint128)> {
+    let mut surpluses: Vec<(usize, Uint128)> = vec![];
+    let mut deficits: Vec<(usize, Uint128)> = vec![];
+    for (index, (validator, target)) in validators.iter().zip(targets.iter().copied()).enumerate() {
+        if validator.total_delegated > target {
+            if locked.get(index).copied().unwrap_or(false) {
+                continue;
+            }
+            surpluses.push((index, validator.total_delegated - target));
+        } else if validator.total_delegated < target {
+            deficits.push((index, target - validator.total_delegated));
+        }
     }
+    surpluses.sort_by_key(|(_, amount)| Reverse(*amount));
+    deficits.sort_by_key(|(_, amount)| Reverse(*amount));
 
-    let mut undelegations = vec![Uint128::zero(); validators.len()];
+    let mut moves = vec![];
+    let (mut src, mut dst) = (0usize, 0usize);
+    while moves.len() < max_moves && src < surpluses.len() && dst < deficits.len() {
+        let amount = surpluses[src].1.min(deficits[dst].1);
+        moves.push((surpluses[src].0, deficits[dst].0, amount));
 
-    while !amt_to_undelegate.is_zero() {
-        let total_delegations_after_undelegation =
-            total_delegated
-                .checked_sub(amt_to_undelegate)
-                .map_err(|e| ContractError::Overflow(e.to_string()))?;
+        surpluses[src].1 -= amount;
+        deficits[dst].1 -= amount;
+        if surpluses[src].1.is_zero() {
+            src += 1;
+        }
+        if deficits[dst].1.is_zero() {
+            dst += 1;
+        }
+    }
 
-        let (coins_per_val, remaining_coins) =
-            distribute_coins(total_delegations_after_undelegation, validators.len());
+    moves
+}
 
-        let target_undelegations =
-            target_coins_per_validator(coins_per_val, remaining_coins, &validators);
+// Sums the delegation of all validators, failing on an empty validator set
+fn total_delegated(validators: &[ValidatorResponse]) -> LstResult<Uint128> {
+    if validators.is_empty() {
+        return Err(ValidatorError::EmptyValidatorSet.into());
+    }
 
-        for (index, (validator, target_undelegation)) in
-            validators.iter_mut().zip(target_undelegations).enumerate()
-        {
-            let mut to_undelegate = validator
-                .total_delegated
-                .checked_sub(target_undelegation.min(validator.total_delegated))
-                .map_err(|e| ContractError::Overflow(e.to_string()))?;
+    validators.iter().try_fold(Uint128::zero(), |total, val| {
+        total
+            .checked_add(val.total_delegated)
+            .map_err(|e| ContractError::Overflow(e.to_string()))
+    })
+}
 
-            if to_undelegate > amt_to_undelegate {
-                to_undelegate = amt_to_undelegate;
-            }
+// Splits `total` proportionally to the validator weights
+// the rounding leftover goes one coin each to the first weighted validators
+fn weighted_targets(total: Uint128, validators: &[ValidatorResponse]) -> LstResult<Vec<Uint128>> {
+    let total_weight: u128 = validators.iter().map(|val| val.weight as u128).sum();
+    if total_weight == 0 {
+        return Err(ValidatorError::InvalidValidatorWeights.into());
+    }
 
-            undelegations[index] = undelegations[index]
-                .checked_add(to_undelegate)
-                .map_err(|e| ContractError::Overflow(e.to_string()))?;
-            amt_to_undelegate = amt_to_undelegate
-                .checked_sub(to_undelegate)
-                .map_err(|e| ContractError::Overflow(e.to_string()))?;
-            total_delegated = total_delegated
-                .checked_sub(to_undelegate)
-                .map_err(|e| ContractError::Overflow(e.to_string()))?;
-            validator.total_delegated = validator
-                .total_delegated
-                .checked_sub(to_undelegate)
-                .map_err(|e| ContractError::Overflow(e.to_string()))?;
+    let mut targets = validators
+        .iter()
+        .map(|val| total.multiply_ratio(val.weight as u128, total_weight))
+        .collect::<Vec<_>>();
 
-            if amt_to_undelegate.is_zero() {
-                return Ok(undelegations);
-            }
+    let mut leftover = total - targets.iter().sum::<Uint128>();
+    for (target, validator) in targets.iter_mut().zip(validators) {
+        if leftover.is_zero() {
+            break;
+        }
+        if validator.weight > 0 {
+            *target += Uint128::one();
+            leftover -= Uint128::one();
         }
     }
-    Err(ValidatorError::DistributionFailed.into())
+
+    Ok(targets)
 }
 
 // Splits coin evenly across validator after delegation/undelegation
@@ -144,3 +790,556 @@ fn target_coins_per_validator(
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Sub;
+
+    use super::*;
+
+    fn validators(delegations: &[u128]) -> Vec<ValidatorResponse> {
+        delegations
+            .iter()
+            .enumerate()
+            .map(|(index, amount)| ValidatorResponse {
+                total_delegated: Uint128::new(*amount),
+                address: format!("validator{}", index),
+                weight: 1,
+            })
+            .collect()
+    }
+
+    fn targets(validators: &[ValidatorResponse]) -> Vec<Uint128> {
+        calculate_target_delegations(validators).unwrap()
+    }
+
+    fn amounts(amounts: &[u128]) -> Vec<Uint128> {
+        amounts.iter().map(|amount| Uint128::new(*amount)).collect()
+    }
+
+    // Iterative implementation the single-pass delegation math is checked against
+    fn legacy_calculate_delegations(
+        mut amt_to_delegate: Uint128,
+        validators: &[ValidatorResponse],
+    ) -> LstResult<Vec<Uint128>> {
+        if validators.is_empty() {
+            return Err(ValidatorError::EmptyValidatorSet.into());
+        }
+
+        let total_delegated: u128 = validators
+            .iter()
+            .map(|val| val.total_delegated.u128())
+            .sum();
+
+        let total_coins_to_distribute = Uint128::from(total_delegated) + amt_to_delegate;
+        let (coins_per_val, remaining_coins) =
+            distribute_coins(total_coins_to_distribute, validators.len());
+
+        let target_delegations =
+            target_coins_per_validator(coins_per_val, remaining_coins, validators);
+
+        let mut delegations = vec![Uint128::zero(); validators.len()];
+
+        for (index, (validator, target_delegation)) in
+            validators.iter().zip(target_delegations).enumerate()
+        {
+            let val_current_delegation = validator.total_delegated;
+
+            if target_delegation < val_current_delegation {
+                continue;
+            }
+
+            let mut to_delegate = target_delegation.sub(val_current_delegation);
+
+            if to_delegate > amt_to_delegate {
+                to_delegate = amt_to_delegate;
+            }
+
+            delegations[index] = to_delegate;
+            amt_to_delegate = amt_to_delegate
+                .checked_sub(to_delegate)
+                .map_err(|e| ContractError::Overflow(e.to_string()))?;
+
+            if amt_to_delegate.is_zero() {
+                return Ok(delegations);
+            }
+        }
+        Err(ValidatorError::DistributionFailed.into())
+    }
+
+    fn legacy_calculate_undelegations(
+        mut amt_to_undelegate: Uint128,
+        mut validators: Vec<ValidatorResponse>,
+    ) -> LstResult<Vec<Uint128>> {
+        if validators.is_empty() {
+            return Err(ValidatorError::EmptyValidatorSet.into());
+        }
+
+        let mut total_delegated = validators.iter().map(|val| val.total_delegated).sum();
+
+        if amt_to_undelegate > total_delegated {
+            return Err(ValidatorError::ExceedUndelegation.into());
+        }
+
+        let mut undelegations = vec![Uint128::zero(); validators.len()];
+
+        while !amt_to_undelegate.is_zero() {
+            let total_delegations_after_undelegation = total_delegated
+                .checked_sub(amt_to_undelegate)
+                .map_err(|e| ContractError::Overflow(e.to_string()))?;
+
+            let (coins_per_val, remaining_coins) =
+                distribute_coins(total_delegations_after_undelegation, validators.len());
+
+            let target_undelegations =
+                target_coins_per_validator(coins_per_val, remaining_coins, &validators);
+
+            for (index, (validator, target_undelegation)) in
+                validators.iter_mut().zip(target_undelegations).enumerate()
+            {
+                let mut to_undelegate = validator
+                    .total_delegated
+                    .checked_sub(target_undelegation.min(validator.total_delegated))
+                    .map_err(|e| ContractError::Overflow(e.to_string()))?;
+
+                if to_undelegate > amt_to_undelegate {
+                    to_undelegate = amt_to_undelegate;
+                }
+
+                undelegations[index] = undelegations[index]
+                    .checked_add(to_undelegate)
+                    .map_err(|e| ContractError::Overflow(e.to_string()))?;
+                amt_to_undelegate = amt_to_undelegate
+                    .checked_sub(to_undelegate)
+                    .map_err(|e| ContractError::Overflow(e.to_string()))?;
+                total_delegated = total_delegated
+                    .checked_sub(to_undelegate)
+                    .map_err(|e| ContractError::Overflow(e.to_string()))?;
+                validator.total_delegated = validator
+                    .total_delegated
+                    .checked_sub(to_undelegate)
+                    .map_err(|e| ContractError::Overflow(e.to_string()))?;
+
+                if amt_to_undelegate.is_zero() {
+                    return Ok(undelegations);
+                }
+            }
+        }
+        Err(ValidatorError::DistributionFailed.into())
+    }
+
+    // Deterministic pseudo-random delegations, with some validators holding nothing
+    fn random_validators(seed: &mut u64, count: usize) -> Vec<ValidatorResponse> {
+        let delegations = (0..count)
+            .map(|_| {
+                *seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                match *seed >> 60 {
+                    0..=2 => 0,
+                    _ => (*seed >> 33) as u128 % 1_000_000,
+                }
+            })
+            .collect::<Vec<_>>();
+        validators(&delegations)
+    }
+
+    #[test]
+    fn test_calculate_target_delegations() {
+        let targets = calculate_target_delegations(&validators(&[100, 200, 301])).unwrap();
+        assert_eq!(
+            targets,
+            vec![Uint128::new(201), Uint128::new(200), Uint128::new(200)]
+        );
+
+        let err = calculate_target_delegations(&[]).unwrap_err();
+        assert_eq!(err, ValidatorError::EmptyValidatorSet.into());
+    }
+
+    #[test]
+    fn test_calculate_rebalance_moves() {
+        // targets are 200 each
+        let vals = validators(&[500, 100, 0, 200]);
+
+        let moves = calculate_rebalance_moves(&vals, &targets(&vals), 10, &[]);
+        assert_eq!(
+            moves,
+            vec![(0, 2, Uint128::new(200)), (0, 1, Uint128::new(100))]
+        );
+
+        // bounded by max moves
+        let moves = calculate_rebalance_moves(&vals, &targets(&vals), 1, &[]);
+        assert_eq!(moves, vec![(0, 2, Uint128::new(200))]);
+
+        // locked validators are not used as a source
+        let vals = validators(&[400, 300, 0, 100]);
+        let moves =
+            calculate_rebalance_moves(&vals, &targets(&vals), 10, &[true, false, false, false]);
+        assert_eq!(moves, vec![(1, 2, Uint128::new(100))]);
+
+        // already balanced
+        let vals = validators(&[200, 200, 200]);
+        let moves = calculate_rebalance_moves(&vals, &targets(&vals), 10, &[]);
+        assert!(moves.is_empty());
+    }
+
+    #[test]
+    fn test_single_pass_equivalence() {
+        let mut seed = 42u64;
+        for count in [1, 2, 3, 10, 50, 150] {
+            for _ in 0..50 {
+                let vals = random_validators(&mut seed, count);
+                let total: Uint128 = vals.iter().map(|val| val.total_delegated).sum();
+
+                for amount in [1u128, 7, 1_000, 123_457, 5_000_000] {
+                    assert_eq!(
+                        calculate_delegations(Uint128::new(amount), &vals),
+                        legacy_calculate_delegations(Uint128::new(amount), &vals)
+                    );
+                }
+
+                for amount in [
+                    Uint128::one(),
+                    total.multiply_ratio(1u128, 3u128),
+                    total.multiply_ratio(9u128, 10u128),
+                    total,
+                    total + Uint128::one(),
+                ] {
+                    assert_eq!(
+                        calculate_undelegations(amount, &vals),
+                        legacy_calculate_undelegations(amount, vals.clone())
+                    );
+                }
+            }
+        }
+
+        // nothing to undelegate
+        assert_eq!(
+            calculate_undelegations(Uint128::zero(), &validators(&[100, 200])),
+            Err(ValidatorError::DistributionFailed.into())
+        );
+
+        // empty validator set
+        assert_eq!(
+            calculate_delegations(Uint128::new(100), &[]),
+            legacy_calculate_delegations(Uint128::new(100), &[])
+        );
+        assert_eq!(
+            calculate_undelegations(Uint128::new(100), &[]),
+            legacy_calculate_undelegations(Uint128::new(100), vec![])
+        );
+    }
+
+    #[test]
+    fn test_equal_split_strategy() {
+        let vals = validators(&[100, 0, 50]);
+
+        let delegations = DelegationStrategyKind::EqualSplit
+            .delegations(Uint128::new(150), &vals)
+            .unwrap();
+        assert_eq!(delegations, amounts(&[0, 100, 50]));
+
+        let undelegations = DelegationStrategyKind::EqualSplit
+            .undelegations(Uint128::new(60), &vals)
+            .unwrap();
+        assert_eq!(undelegations, amounts(&[60, 0, 0]));
+    }
+
+    #[test]
+    fn test_weighted_strategy() {
+        let mut vals = validators(&[0, 0, 0]);
+        vals[0].weight = 2;
+        vals[2].weight = 0;
+
+        let delegations = DelegationStrategyKind::Weighted
+            .delegations(Uint128::new(301), &vals)
+            .unwrap();
+        assert_eq!(delegations, amounts(&[201, 100, 0]));
+
+        vals.iter_mut()
+            .zip(delegations)
+            .for_each(|(val, amount)| val.total_delegated = amount);
+
+        let targets = DelegationStrategyKind::Weighted
+            .target_delegations(&vals)
+            .unwrap();
+        assert_eq!(targets, amounts(&[201, 100, 0]));
+
+        let undelegations = DelegationStrategyKind::Weighted
+            .undelegations(Uint128::new(150), &vals)
+            .unwrap();
+        assert_eq!(undelegations, amounts(&[100, 50, 0]));
+
+        // weights sum to zero
+        let mut vals = validators(&[0, 0]);
+        vals.iter_mut().for_each(|val| val.weight = 0);
+        let err = DelegationStrategyKind::Weighted
+            .delegations(Uint128::new(100), &vals)
+            .unwrap_err();
+        assert_eq!(err, ValidatorError::InvalidValidatorWeights.into());
+    }
+
+    #[test]
+    fn test_inverse_stake_strategy() {
+        let vals = validators(&[300, 0, 100, 50]);
+
+        // the two smallest validators are raised to a common level below the next one
+        let delegations = DelegationStrategyKind::InverseStake
+            .delegations(Uint128::new(100), &vals)
+            .unwrap();
+        assert_eq!(delegations, amounts(&[0, 75, 0, 25]));
+
+        // the amount is large enough to raise every validator but the largest
+        let delegations = DelegationStrategyKind::InverseStake
+            .delegations(Uint128::new(401), &vals)
+            .unwrap();
+        assert_eq!(delegations, amounts(&[0, 184, 83, 134]));
+
+        // the largest validator is cut down first
+        let undelegations = DelegationStrategyKind::InverseStake
+            .undelegations(Uint128::new(150), &vals)
+            .unwrap();
+        assert_eq!(undelegations, amounts(&[150, 0, 0, 0]));
+
+        // the two largest validators are cut to a common level
+        let undelegations = DelegationStrategyKind::InverseStake
+            .undelegations(Uint128::new(251), &vals)
+            .unwrap();
+        assert_eq!(undelegations, amounts(&[225, 0, 26, 0]));
+
+        let err = DelegationStrategyKind::InverseStake
+            .undelegations(Uint128::new(451), &vals)
+            .unwrap_err();
+        assert_eq!(err, ValidatorError::ExceedUndelegation.into());
+    }
+
+    #[test]
+    fn test_minimal_message_strategy() {
+        let vals = validators(&[300, 0, 100, 50]);
+
+        let delegations = DelegationStrategyKind::MinimalMessage
+            .delegations(Uint128::new(1000), &vals)
+            .unwrap();
+        assert_eq!(delegations, amounts(&[0, 1000, 0, 0]));
+
+        let undelegations = DelegationStrategyKind::MinimalMessage
+            .undelegations(Uint128::new(350), &vals)
+            .unwrap();
+        assert_eq!(undelegations, amounts(&[300, 0, 50, 0]));
+
+        let err = DelegationStrategyKind::MinimalMessage
+            .delegations(Uint128::new(1000), &[])
+            .unwrap_err();
+        assert_eq!(err, ValidatorError::EmptyValidatorSet.into());
+    }
+
+    #[test]
+    fn test_capped_targets() {
+        // targets under the cap are unchanged
+        assert_eq!(
+            capped_targets(&amounts(&[200, 200, 200]), Uint128::new(300)),
+            amounts(&[200, 200, 200])
+        );
+
+        // the excess of the capped validator goes to the others in proportion to their target
+        assert_eq!(
+            capped_targets(&amounts(&[480, 80, 40]), Uint128::new(300)),
+            amounts(&[300, 200, 100])
+        );
+
+        // a validator pushed over the cap by the excess is capped too
+        assert_eq!(
+            capped_targets(&amounts(&[700, 250, 50]), Uint128::new(400)),
+            amounts(&[400, 400, 200])
+        );
+
+        // the rounding leftover stays under the cap
+        let targets = capped_targets(&amounts(&[900, 50, 50, 1]), Uint128::new(334));
+        assert_eq!(targets[0], Uint128::new(334));
+        assert_eq!(targets.iter().sum::<Uint128>(), Uint128::new(1001));
+        assert!(targets.iter().all(|target| *target <= Uint128::new(334)));
+
+        // targets that cannot fit under the cap are unchanged
+        assert_eq!(
+            capped_targets(&amounts(&[500, 100]), Uint128::new(200)),
+            amounts(&[500, 100])
+        );
+    }
+
+    #[test]
+    fn test_capped_delegations() {
+        let vals = validators(&[300, 0, 100, 50]);
+
+        // no cap
+        let delegations = capped_delegations(
+            &DelegationStrategyKind::MinimalMessage,
+            Uint128::new(1000),
+            &vals,
+            Uint128::new(450),
+            Decimal::one(),
+        )
+        .unwrap();
+        assert_eq!(delegations, amounts(&[0, 1000, 0, 0]));
+
+        // the smallest validator saturates at 40% of 1450, the rest goes to the next smallest
+        let delegations = capped_delegations(
+            &DelegationStrategyKind::MinimalMessage,
+            Uint128::new(1000),
+            &vals,
+            Uint128::new(450),
+            Decimal::percent(40),
+        )
+        .unwrap();
+        assert_eq!(delegations, amounts(&[0, 580, 0, 420]));
+
+        // stake held outside of the validators counts in the total
+        let delegations = capped_delegations(
+            &DelegationStrategyKind::MinimalMessage,
+            Uint128::new(150),
+            &validators(&[0, 50]),
+            Uint128::new(250),
+            Decimal::percent(30),
+        )
+        .unwrap();
+        assert_eq!(delegations, amounts(&[120, 30]));
+
+        // every validator saturated, the remainder is spread by the strategy
+        let delegations = capped_delegations(
+            &DelegationStrategyKind::EqualSplit,
+            Uint128::new(100),
+            &validators(&[100, 100]),
+            Uint128::new(200),
+            Decimal::percent(40),
+        )
+        .unwrap();
+        assert_eq!(delegations, amounts(&[50, 50]));
+    }
+
+    #[test]
+    fn test_bounded_delegations() {
+        let bound = |delegations: &[u128], max_validators: u32, min_chunk: Uint128| {
+            bounded_delegations(
+                amounts(delegations),
+                &validators(&vec![0; delegations.len()]),
+                Uint128::zero(),
+                Decimal::one(),
+                max_validators,
+                min_chunk,
+            )
+        };
+
+        // no bound
+        let delegations = bound(&[10, 0, 30, 20], 0, Uint128::zero());
+        assert_eq!(delegations, amounts(&[10, 0, 30, 20]));
+
+        // within both bounds
+        let delegations = bound(&[10, 0, 30, 20], 3, Uint128::new(10));
+        assert_eq!(delegations, amounts(&[10, 0, 30, 20]));
+
+        // the two most under-target validators take the amount of the others
+        let delegations = bound(&[10, 0, 30, 20], 2, Uint128::zero());
+        assert_eq!(delegations, amounts(&[0, 0, 36, 24]));
+
+        // chunks below the minimum are dropped
+        let delegations = bound(&[5, 40, 5, 50], 0, Uint128::new(10));
+        assert_eq!(delegations, amounts(&[0, 44, 0, 56]));
+
+        // a chunk reaching the minimum once the dropped amount is added is kept
+        let delegations = bound(&[40, 40, 40], 0, Uint128::new(50));
+        assert_eq!(delegations, amounts(&[60, 60, 0]));
+
+        // the largest validator takes everything when no chunk can reach the minimum
+        let delegations = bound(&[3, 4, 3], 0, Uint128::new(100));
+        assert_eq!(delegations, amounts(&[0, 10, 0]));
+
+        // rounding remainder goes to the most under-target validator
+        let delegations = bound(&[1, 1, 1, 1], 3, Uint128::zero());
+        assert_eq!(delegations, amounts(&[2, 1, 1, 0]));
+
+        // the cap of 40% of 1450 still holds once the amount of the dropped validator is added
+        let vals = validators(&[300, 100, 50]);
+        let delegations = capped_delegations(
+            &DelegationStrategyKind::EqualSplit,
+            Uint128::new(1000),
+            &vals,
+            Uint128::new(450),
+            Decimal::percent(40),
+        )
+        .unwrap();
+        let delegations = bounded_delegations(
+            delegations,
+            &vals,
+            Uint128::new(450),
+            Decimal::percent(40),
+            2,
+            Uint128::zero(),
+        );
+        assert_eq!(delegations.iter().sum::<Uint128>(), Uint128::new(1000));
+        for (validator, delegation) in vals.iter().zip(&delegations) {
+            assert!(validator.total_delegated + delegation <= Uint128::new(580));
+        }
+    }
+
+    #[test]
+    fn test_gauge_weights() {
+        let powers = amounts(&[600, 300, 100]);
+
+        let weights = gauge_weights(&powers, Decimal::zero(), Decimal::one());
+        assert_eq!(
+            weights,
+            vec![
+                Decimal::percent(60),
+                Decimal::percent(30),
+                Decimal::percent(10)
+            ]
+        );
+
+        // the smallest validator is raised to the floor, then the largest is cut to the ceiling
+        let weights = gauge_weights(&powers, Decimal::percent(20), Decimal::percent(50));
+        assert_eq!(
+            weights,
+            vec![
+                Decimal::percent(50),
+                Decimal::percent(30),
+                Decimal::percent(20)
+            ]
+        );
+
+        // without votes the stake is split evenly
+        let weights = gauge_weights(&amounts(&[0, 0]), Decimal::zero(), Decimal::one());
+        assert_eq!(weights, vec![Decimal::percent(50), Decimal::percent(50)]);
+        // stake freed by the ceiling is shared by the validators held at the floor
+        let weights = gauge_weights(
+            &amounts(&[1000, 10, 9]),
+            Decimal::percent(10),
+            Decimal::percent(40),
+        );
+        assert_eq!(weights[0], Decimal::percent(40));
+        let expected = [
+            Decimal::percent(60) * Decimal::from_ratio(10u128, 19u128),
+            Decimal::percent(60) * Decimal::from_ratio(9u128, 19u128),
+        ];
+        for (weight, expected) in weights[1..].iter().zip(expected) {
+            assert!(weight.abs_diff(expected) < Decimal::from_ratio(1u128, 1_000_000u128));
+        }
+        let total: Decimal = weights.iter().sum();
+        assert!(Decimal::one() - total < Decimal::permille(1));
+
+        // three validators cannot each get at most 30% or at least 40%
+        assert!(gauge_bounds_feasible(
+            3,
+            Decimal::percent(10),
+            Decimal::percent(40)
+        ));
+        assert!(!gauge_bounds_feasible(
+            3,
+            Decimal::zero(),
+            Decimal::percent(30)
+        ));
+        assert!(!gauge_bounds_feasible(
+            3,
+            Decimal::percent(40),
+            Decimal::one()
+        ));
+    }
+}
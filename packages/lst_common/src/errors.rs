@@ -36,6 +36,18 @@ pub enum ContractError {
     #[error("Fee rate must be less than 30%")]
     InvalidFeeRate {},
 
+    #[error("Fee recipients must be unique with non-zero weights summing to one")]
+    InvalidFeeRecipients {},
+
+    #[error("Reward stream window must be at most 7 days")]
+    InvalidRewardStreamWindow {},
+
+    #[error("Invalid denom policy: {0}")]
+    InvalidDenomPolicy(String),
+
+    #[error("Coverage rate must be at most 100%")]
+    InvalidCoverageRate {},
+
     #[error("Migration failed: current version ({0}) is not older than new version ({1})")]
     MigrationNotAllowed(String, String),
 
@@ -69,6 +81,72 @@ pub enum ValidatorError {
 
     #[error("Pending redelegation not found")]
     PendingRedelegationNotFound,
+
+    #[error("Validator weights must not all be zero")]
+    InvalidValidatorWeights,
+
+    #[error("Not enough time has passed since last snapshot")]
+    SnapshotIntervalNotMet,
+
+    #[error("Not enough time has passed since last rebalance")]
+    RebalanceCooldownNotMet,
+
+    #[error("Reason is too long")]
+    ReasonTooLong,
+
+    #[error("Maximum validator share must be greater than zero and at most one")]
+    InvalidMaxValidatorShare,
+
+    #[error("Gauge vote shares must be positive, unique per validator and sum to at most one")]
+    InvalidGaugeVote,
+
+    #[error("No LST balance to vote with")]
+    NoVotingPower,
+
+    #[error("Not enough time has passed since last gauge tally")]
+    GaugePeriodNotMet,
+
+    #[error("Gauge weights must satisfy min weight * validators <= 1 <= max weight * validators")]
+    InvalidGaugeWeightBounds,
+
+    #[error("Gauge voter limit reached")]
+    GaugeVotersFull,
+
+    #[error("Gauge votes only apply with the weighted delegation strategy")]
+    GaugeRequiresWeightedStrategy,
+
+    #[error("Validator is already in the registry")]
+    ValidatorAlreadyRegistered,
+
+    #[error("Validator already has a pending application")]
+    ApplicationAlreadyExists,
+
+    #[error("Validator application not found")]
+    ApplicationNotFound,
+
+    #[error("Application details are too long")]
+    ApplicationDetailsTooLong,
+
+    #[error("Validator is not bonded")]
+    ValidatorNotBonded,
+
+    #[error("Validator is jailed")]
+    ValidatorJailed,
+
+    #[error("Validator commission is above the registry limit")]
+    CommissionTooHigh,
+
+    #[error("Maximum commission must be at most one")]
+    InvalidMaxCommission,
+
+    #[error("Auto-curation is disabled")]
+    CurationDisabled,
+
+    #[error("Not enough time has passed since last curation")]
+    CurationPeriodNotMet,
+
+    #[error("Curation set size must be greater than zero and the skipped top share must be less than one")]
+    InvalidCurationConfig,
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -118,4 +196,10 @@ pub enum HubError {
 
     #[error("LST token already set")]
     LstTokenAlreadySet,
+
+    #[error("No pending stake to delegate")]
+    NoPendingStake,
+
+    #[error("Pending stake has already been delegated in this staking epoch")]
+    PendingStakeAlreadyDelegated,
 }
@@ -30,6 +30,9 @@ pub struct Config {
     pub validators_registry_contract: Option<Addr>,
     /// token address of the lst token
     pub lst_token: Option<Addr>,
+    /// optional address of the insurance fund topping up slashing losses
+    #[serde(default)]
+    pub insurance_fund: Option<Addr>,
 }
 
 #[cw_serde]
@@ -42,6 +45,8 @@ pub struct ConfigResponse {
     pub validators_registry_contract: Option<String>,
     /// LST token address
     pub lst_token: Option<String>,
+    /// Insurance fund contract address
+    pub insurance_fund: Option<String>,
 }
 
 #[cw_serde]
@@ -105,6 +110,26 @@ pub enum QueryMsg {
     /// Returns the pending delegation amount
     #[returns(PendingDelegation)]
     PendingDelegation {},
+    /// Returns the delegation of the hub per validator as tracked by the hub, including the changes queued for the current staking epoch
+    #[returns(Vec<ValidatorDelegationResponse>)]
+    Delegations {},
+    /// Returns the slashing detected per validator, optionally for a single validator
+    #[returns(SlashingHistoryResponse)]
+    SlashingHistory {
+        /// Only return the slashing of this validator
+        validator: Option<String>,
+        /// Starting id for the history
+        start_from: Option<u64>,
+        /// No of data to return per request
+        limit: Option<u32>,
+    },
+    /// Returns the rewards accrued by the delegations that the next `UpdateGlobalIndex` would
+    /// withdraw, with the dispatcher fee applied and the projected exchange rate
+    #[returns(PendingRewardsResponse)]
+    PendingRewards {},
+    /// Returns the exchange rate high-water mark and how far the rate is below it
+    #[returns(HighWaterMarkResponse)]
+    HighWaterMark {},
 }
 
 #[cw_serde]
@@ -132,6 +157,8 @@ pub enum ExecuteMsg {
         validator_registry: Option<String>,
         /// reward dispatcher address
         reward_dispatcher: Option<String>,
+        /// insurance fund address
+        insurance_fund: Option<String>,
     },
     /// Admin can update these parameters from this method
     UpdateParams {
@@ -141,6 +168,8 @@ pub enum ExecuteMsg {
         epoch_length: Option<u64>,
         /// Amount of time the chain takes for unstaking
         unstaking_period: Option<u64>,
+        /// Hold new stake in the hub and delegate it once per staking epoch with DelegatePending
+        batched_delegation: Option<bool>,
     },
     /// Check if slashing has happened. If slashing has happened, the exchange rate is updated accordingly.
     CheckSlashing {},
@@ -155,6 +184,18 @@ pub enum ExecuteMsg {
     /// This method is used by rewards dispatcher contract to stake the rewards accrued from staking
     StakeRewards {},
 
+    /// Used by the rewards dispatcher to restake the rewards including the fee. The fee is minted
+    /// as LST to the recipients, worth the given staking coin amounts at the exchange rate after
+    /// the rewards are restaked.
+    StakeRewardsWithFeeShares {
+        /// Fee amounts in the staking coin per recipient, included in the attached funds
+        fee_shares: Vec<(String, Uint128)>,
+    },
+
+    /// Used by the insurance fund to restake a top-up covering a slashing loss. The slashing check
+    /// that requested it already counted the top-up in the exchange rate, so it is only delegated.
+    InsuranceTopUp {},
+
     /// This method is open to call to update the state of the contract like exchange rate, rewards.
     UpdateGlobalIndex {},
 
@@ -163,6 +204,9 @@ pub enum ExecuteMsg {
 
     /// This method is used to process the unstake requests that have already passed the unstaking period
     ProcessWithdrawRequests {},
+
+    /// This method is open to call to delegate the stake held by the hub. It can be called once per staking epoch
+    DelegatePending {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
@@ -173,6 +217,9 @@ pub struct State {
     pub unclaimed_unstaked_balance: Uint128,
     pub last_unbonded_time: u64,
     pub last_processed_batch: u64,
+    /// Highest exchange rate reached, the performance fee only applies to gains above it
+    #[serde(default)]
+    pub high_water_mark: Decimal,
 }
 
 impl State {
@@ -184,9 +231,12 @@ impl State {
         let total_token_supply = total_issued_lst_token + requested_lst_token_amount;
         if self.total_staked_amount.is_zero() || total_token_supply.is_zero() {
             self.lst_exchange_rate = Decimal::one();
+            // nobody holds the token anymore, so there is no loss left to recover
+            self.high_water_mark = Decimal::one();
         } else {
             self.lst_exchange_rate =
                 Decimal::from_ratio(self.total_staked_amount, total_token_supply);
+            self.high_water_mark = self.high_water_mark.max(self.lst_exchange_rate);
         }
     }
 }
@@ -199,6 +249,9 @@ pub struct Parameters {
     pub unstaking_period: u64,
     #[serde(default = "default_hub_status")]
     pub paused: bool,
+    /// New stake is held by the hub and delegated once per staking epoch
+    #[serde(default)]
+    pub batched_delegation: bool,
 }
 
 fn default_hub_status() -> bool {
@@ -269,6 +322,9 @@ pub struct UnstakeHistory {
     pub lst_withdraw_rate: Decimal,
     /// Whether the batch is processsed/released to get updated withdraw rate
     pub released: bool,
+    /// Amount of the batch paid out of the stake waiting to be delegated instead of being undelegated
+    #[serde(default)]
+    pub netted_amount: Uint128,
 }
 
 #[cw_serde]
@@ -287,4 +343,127 @@ pub struct PendingDelegation {
     pub pending_staking_amount: Uint128,
     /// Pending amount of unstaked tokens that are not yet processed in the epoch
     pub pending_unstaking_amount: Uint128,
+    /// Staked tokens held by the hub in batched delegation mode, or to pay out the open unstake batch otherwise.
+    /// Whatever the unstake batches don't use is delegated by the next DelegatePending call
+    #[serde(default)]
+    pub batched_staking_amount: Uint128,
+    /// Start block height of the staking epoch the held stake was last delegated in
+    #[serde(default)]
+    pub last_batch_epoch_start_block_height: Option<u64>,
+}
+
+impl PendingDelegation {
+    /// Start block height of the staking epoch containing the given height
+    pub fn current_epoch_start(&self, block_height: u64) -> u64 {
+        if self.staking_epoch_length_blocks == 0
+            || block_height < self.staking_epoch_start_block_height
+        {
+            return self.staking_epoch_start_block_height;
+        }
+        let epochs_passed = (block_height - self.staking_epoch_start_block_height)
+            / self.staking_epoch_length_blocks;
+        self.staking_epoch_start_block_height + epochs_passed * self.staking_epoch_length_blocks
+    }
+}
+
+/// Delegation of the hub to a validator, wrapped staking messages only take effect at the end of the staking epoch
+#[cw_serde]
+#[derive(Default)]
+pub struct ValidatorDelegation {
+    /// Amount bonded to the validator at the start of the staking epoch
+    pub bonded: Uint128,
+    /// Amount delegated to the validator during the staking epoch
+    pub pending_delegation: Uint128,
+    /// Amount undelegated or redelegated away from the validator during the staking epoch
+    pub pending_undelegation: Uint128,
+    /// Start block height of the staking epoch the pending amounts were queued in
+    pub epoch_start_block_height: u64,
+    /// Pending delegation applied at the last settlement, which the chain may not have applied yet
+    #[serde(default)]
+    pub settled_delegation: Uint128,
+    /// Pending undelegation applied at the last settlement, which the chain may not have applied yet
+    #[serde(default)]
+    pub settled_undelegation: Uint128,
+}
+
+impl ValidatorDelegation {
+    /// Amount delegated once the pending changes are applied
+    pub fn delegated(&self) -> Uint128 {
+        (self.bonded + self.pending_delegation).saturating_sub(self.pending_undelegation)
+    }
+
+    /// Apply the pending changes if their staking epoch has ended
+    pub fn settle(&mut self, current_epoch_start: u64) {
+        if self.epoch_start_block_height < current_epoch_start {
+            self.bonded = self.delegated();
+            self.settled_delegation = self.pending_delegation;
+            self.settled_undelegation = self.pending_undelegation;
+            self.pending_delegation = Uint128::zero();
+            self.pending_undelegation = Uint128::zero();
+            self.epoch_start_block_height = current_epoch_start;
+        }
+    }
+
+    /// Amount bonded before the last settlement, which the chain holds until it applies the settled changes
+    pub fn unsettled_bonded(&self) -> Uint128 {
+        (self.bonded + self.settled_undelegation).saturating_sub(self.settled_delegation)
+    }
+}
+
+#[cw_serde]
+pub struct SlashingEvent {
+    /// Sequential id of the slashing event
+    pub id: u64,
+    /// Address of the slashed validator
+    pub validator: String,
+    /// Delegation of the hub lost to the slashing
+    pub amount: Uint128,
+    /// Block height at which the slashing was detected
+    pub height: u64,
+    /// Block time at which the slashing was detected
+    pub time: u64,
+}
+
+#[cw_serde]
+pub struct PendingRewardsResponse {
+    /// Rewards accrued per validator, in the staking coin
+    pub rewards: Vec<(String, Uint128)>,
+    /// Sum of the accrued rewards
+    pub total: Uint128,
+    /// Rewards already at the reward dispatcher and not streamed yet, dispatched with the accrued ones
+    pub held: Uint128,
+    /// Fee the reward dispatcher would take on the accrued and held rewards
+    pub fee: Uint128,
+    /// Accrued and held rewards left after the fee
+    pub net: Uint128,
+    /// Exchange rate once the next dispatch is restaked. Streamed rewards only count once vested
+    /// and fee shares are minted at the rate the rewards reach.
+    pub projected_exchange_rate: Decimal,
+}
+
+#[cw_serde]
+pub struct HighWaterMarkResponse {
+    /// Highest exchange rate reached
+    pub high_water_mark: Decimal,
+    /// Current exchange rate
+    pub exchange_rate: Decimal,
+    /// Rewards needed to bring the exchange rate back to the high-water mark, which are
+    /// exempt from the performance fee
+    pub recovery_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct SlashingHistoryResponse {
+    /// Slashing detected per validator
+    pub history: Vec<SlashingEvent>,
+}
+
+#[cw_serde]
+pub struct ValidatorDelegationResponse {
+    /// Address of the validator
+    pub validator: String,
+    /// Amount delegated once the pending changes are applied
+    pub delegated: Uint128,
+    /// Amount currently bonded to the validator
+    pub bonded: Uint128,
 }
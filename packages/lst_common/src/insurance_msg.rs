@@ -0,0 +1,125 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Decimal, Uint128};
+
+/// Instantiate the insurance fund contract. The fund is paid by listing it among the fee
+/// recipients of the reward dispatcher, and accepts plain bank transfers as well.
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Address of the staking hub contract
+    pub hub_contract: String,
+    /// Denom of the staking coin held by the fund
+    pub denom: String,
+    /// Share of a slashing loss covered by a top-up
+    pub coverage_rate: Decimal,
+    /// Largest amount paid out for a single claim
+    pub max_payout: Uint128,
+    /// Seconds after a payout during which further claims are not paid out
+    pub cooldown: u64,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Update the config by admin
+    UpdateConfig {
+        /// Owner of the contract
+        owner: Option<String>,
+        /// Address of the hub contract
+        hub_contract: Option<String>,
+        /// Share of a slashing loss covered by a top-up
+        coverage_rate: Option<Decimal>,
+        /// Largest amount paid out for a single claim
+        max_payout: Option<Uint128>,
+        /// Seconds after a payout during which further claims are not paid out
+        cooldown: Option<u64>,
+    },
+    /// Used by the staking hub when it detects slashing. The fund restakes the covered share of
+    /// the loss through the hub, capped by `max_payout` and its balance. Claims during the
+    /// cooldown are recorded without a payout rather than rejected.
+    RequestTopUp {
+        /// Amount of the staking coin lost to slashing
+        loss: Uint128,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the config values of the contract
+    #[returns(Config)]
+    Config {},
+    /// Returns the balance and payout totals of the fund
+    #[returns(FundStateResponse)]
+    FundState {},
+    /// Returns the amount a claim for the given loss would pay out right now
+    #[returns(PayoutResponse)]
+    Payout {
+        /// Amount of the staking coin lost to slashing
+        loss: Uint128,
+    },
+    /// Returns the claims made by the hub, oldest first
+    #[returns(ClaimsResponse)]
+    Claims {
+        /// Starting id for the claims
+        start_from: Option<u64>,
+        /// No of data to return per request
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct Config {
+    pub owner: Addr,
+    pub hub_contract: Addr,
+    pub denom: String,
+    pub coverage_rate: Decimal,
+    pub max_payout: Uint128,
+    pub cooldown: u64,
+}
+
+/// Payout totals over all claims
+#[cw_serde]
+#[derive(Default)]
+pub struct FundState {
+    /// Amount paid out to the hub
+    pub total_paid_out: Uint128,
+    /// Number of claims made
+    pub claim_count: u64,
+    /// Time of the last claim with a payout
+    pub last_payout_time: Option<u64>,
+}
+
+#[cw_serde]
+pub struct FundStateResponse {
+    /// Amount of the staking coin held by the fund
+    pub balance: Uint128,
+    /// Amount paid out to the hub
+    pub total_paid_out: Uint128,
+    /// Number of claims made
+    pub claim_count: u64,
+    /// Time from which claims are paid out again
+    pub cooldown_end: Option<u64>,
+}
+
+#[cw_serde]
+pub struct PayoutResponse {
+    /// Amount restaked through the hub for the claim
+    pub payout: Uint128,
+}
+
+#[cw_serde]
+pub struct Claim {
+    pub id: u64,
+    /// Block height of the claim
+    pub height: u64,
+    /// Block time of the claim in seconds
+    pub time: u64,
+    /// Slashing loss reported by the hub
+    pub loss: Uint128,
+    /// Amount restaked through the hub, zero if nothing was paid out
+    pub payout: Uint128,
+}
+
+#[cw_serde]
+pub struct ClaimsResponse {
+    pub claims: Vec<Claim>,
+}
@@ -1,8 +1,10 @@
 pub mod delegation;
 pub mod errors;
 pub mod hub;
+pub mod insurance_msg;
 pub mod msg;
 pub mod rewards_msg;
+pub mod router;
 mod signed_integer;
 pub mod types;
 pub mod validator;
@@ -14,6 +16,7 @@ use types::LstResult;
 pub mod address;
 pub mod babylon;
 pub mod babylon_msg;
+pub mod mock_router;
 pub mod testing;
 
 pub use crate::{
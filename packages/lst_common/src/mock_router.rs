@@ -0,0 +1,138 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use crate::babylon::{EpochingMsg, EpochingQuery};
+use crate::router::{QueryMsg, SimulateSwapResponse};
+use crate::testing::{BabylonApp, TestingContract};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_json_binary, Addr, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128,
+};
+use cw_multi_test::{Contract, ContractWrapper};
+use cw_storage_plus::{Item, Map};
+use serde::{Deserialize, Serialize};
+
+/// Swap rates keyed by (offer denom, ask denom)
+const RATES: Map<(&str, &str), Decimal> = Map::new("rates");
+/// Portion of the output withheld on execution but not on simulation, to trigger slippage
+const SPREAD: Item<Decimal> = Item::new("spread");
+
+#[cw_serde]
+pub struct InstantiateMsg {}
+
+/// Router `Swap` plus the knobs tests use to set prices
+#[cw_serde]
+pub enum ExecuteMsg {
+    Swap {
+        ask_denom: String,
+        min_output: Uint128,
+    },
+    SetRate {
+        offer_denom: String,
+        ask_denom: String,
+        rate: Decimal,
+    },
+    SetSpread {
+        spread: Decimal,
+    },
+}
+
+fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: InstantiateMsg,
+) -> StdResult<Response> {
+    SPREAD.save(deps.storage, &Decimal::zero())?;
+    Ok(Response::default())
+}
+
+fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::Swap {
+            ask_denom,
+            min_output,
+        } => {
+            let offer = match info.funds.as_slice() {
+                [offer] => offer.clone(),
+                _ => return Err(StdError::generic_err("Swap requires exactly one coin")),
+            };
+
+            let output = simulate(deps.as_ref(), &offer, &ask_denom)?
+                .mul_floor(Decimal::one() - SPREAD.load(deps.storage)?);
+            if output < min_output {
+                return Err(StdError::generic_err(format!(
+                    "Slippage limit exceeded: {} < {}",
+                    output, min_output
+                )));
+            }
+
+            Ok(Response::new()
+                .add_message(BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: vec![Coin::new(output, ask_denom)],
+                })
+                .add_attribute("action", "swap")
+                .add_attribute("offer", offer.to_string())
+                .add_attribute("output", output.to_string()))
+        }
+        ExecuteMsg::SetRate {
+            offer_denom,
+            ask_denom,
+            rate,
+        } => {
+            RATES.save(deps.storage, (&offer_denom, &ask_denom), &rate)?;
+            Ok(Response::default())
+        }
+        ExecuteMsg::SetSpread { spread } => {
+            SPREAD.save(deps.storage, &spread)?;
+            Ok(Response::default())
+        }
+    }
+}
+
+fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::SimulateSwap { offer, ask_denom } => to_json_binary(&SimulateSwapResponse {
+            return_amount: simulate(deps, &offer, &ask_denom)?,
+        }),
+    }
+}
+
+fn simulate(deps: Deps, offer: &Coin, ask_denom: &str) -> StdResult<Uint128> {
+    let rate = RATES
+        .may_load(deps.storage, (&offer.denom, ask_denom))?
+        .ok_or_else(|| {
+            StdError::generic_err(format!("No route from {} to {}", offer.denom, ask_denom))
+        })?;
+    Ok(offer.amount.mul_floor(rate))
+}
+
+/// DEX router for multi-test. Swaps at fixed rates set by the test and pays the output
+/// from its own balance, so it has to be funded with the ask denom.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MockRouterContract {
+    pub addr: Addr,
+    pub init: InstantiateMsg,
+}
+
+impl TestingContract<InstantiateMsg, ExecuteMsg, QueryMsg> for MockRouterContract {
+    fn wrapper() -> Box<dyn Contract<EpochingMsg, EpochingQuery>> {
+        Box::new(ContractWrapper::new_with_empty(execute, instantiate, query))
+    }
+
+    fn default_init(_app: &mut BabylonApp, _env: &Env) -> InstantiateMsg {
+        InstantiateMsg {}
+    }
+
+    fn new(app: &mut BabylonApp, env: &Env, msg: Option<InstantiateMsg>) -> Self {
+        let init = msg.unwrap_or(Self::default_init(app, env));
+        let code_id = Self::store_code(app);
+        let addr = Self::instantiate(app, code_id, "MockRouterContract", None, &init);
+        Self { addr, init }
+    }
+
+    fn addr(&self) -> &Addr {
+        &self.addr
+    }
+}
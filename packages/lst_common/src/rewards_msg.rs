@@ -1,5 +1,5 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Decimal};
+use cosmwasm_std::{Addr, Decimal, Uint128};
 
 /// Instantiate the reward contract
 #[cw_serde]
@@ -8,8 +8,8 @@ pub struct InstantiateMsg {
     pub hub_contract: String,
     /// Denom of the staking reward token
     pub reward_denom: String,
-    /// Address to receive the fee from the rewards
-    pub fee_addr: String,
+    /// Addresses receiving the fee from the rewards, with weights summing to one
+    pub fee_recipients: Vec<(String, Decimal)>,
     /// Rate at which fee is taken from rewards
     pub fee_rate: Decimal,
 }
@@ -22,10 +22,25 @@ pub enum ExecuteMsg {
         owner: Option<String>,
         /// Address of the hub contract
         hub_contract: Option<String>,
-        /// Address to receive the fee from the rewards
-        fee_addr: Option<String>,
+        /// Addresses receiving the fee from the rewards, with weights summing to one
+        fee_recipients: Option<Vec<(String, Decimal)>>,
         /// Rate at which fee is taken from rewards
         fee_rate: Option<Decimal>,
+        /// DEX router used to swap non-staking denoms into the reward denom
+        router: Option<String>,
+        /// Address receiving non-staking denoms with the forward policy
+        treasury: Option<String>,
+        /// Seconds over which dispatched rewards are released to the hub, 0 restakes them at once
+        reward_stream_window: Option<u64>,
+        /// Restake the fee with the rewards and have the hub mint it as LST to the fee recipients
+        fee_as_shares: Option<bool>,
+        /// Only take the fee on rewards raising the exchange rate above its high-water mark
+        performance_fee: Option<bool>,
+    },
+    /// Set how a non-staking denom held by the dispatcher is handled, removing the policy if none
+    SetDenomPolicy {
+        denom: String,
+        policy: Option<DenomPolicy>,
     },
     /// Dispatch the rewards to the staking hub contract and stake those rewards
     DispatchRewards {},
@@ -37,6 +52,28 @@ pub enum QueryMsg {
     /// Returns the config values of the contract
     #[returns(Config)]
     Config {},
+    /// Returns the policies for non-staking denoms
+    #[returns(DenomPoliciesResponse)]
+    DenomPolicies {},
+    /// Returns the cumulative totals over all dispatches
+    #[returns(RewardStats)]
+    RewardStats {},
+    /// Returns the streamed rewards held by the dispatcher
+    #[returns(VestingRewardsResponse)]
+    VestingRewards {},
+    /// Returns the most recent dispatches, oldest first
+    #[returns(DispatchHistoryResponse)]
+    DispatchHistory {
+        /// Starting id for the history
+        start_from: Option<u64>,
+        /// No of data to return per request
+        limit: Option<u32>,
+    },
+}
+
+/// Fee taken from the rewards at `fee_rate`, rounded up
+pub fn compute_fee(amount: Uint128, fee_rate: Decimal) -> Uint128 {
+    (Decimal::from_ratio(amount, 1u128) * fee_rate).to_uint_ceil()
 }
 
 #[cw_serde]
@@ -44,6 +81,102 @@ pub struct Config {
     pub owner: Addr,
     pub hub_contract: Addr,
     pub reward_denom: String,
-    pub fee_addr: Addr,
+    pub fee_recipients: Vec<(Addr, Decimal)>,
     pub fee_rate: Decimal,
+    #[serde(default)]
+    pub router: Option<Addr>,
+    #[serde(default)]
+    pub treasury: Option<Addr>,
+    #[serde(default)]
+    pub reward_stream_window: u64,
+    #[serde(default)]
+    pub fee_as_shares: bool,
+    #[serde(default)]
+    pub performance_fee: bool,
+}
+
+/// Handling of a denom other than the reward denom that arrives at the dispatcher.
+/// Denoms without a policy are held.
+#[cw_serde]
+pub enum DenomPolicy {
+    /// Swap into the reward denom through the router and restake it with the next dispatch.
+    /// The swap reverts below `min_price`, in reward denom per unit of the swapped denom, which the
+    /// owner keeps in line with an outside price since the router cannot vouch for its own quote.
+    Swap { min_price: Decimal },
+    /// Forward the balance to the treasury
+    Forward {},
+    /// Keep the balance in the dispatcher
+    Hold {},
+}
+
+#[cw_serde]
+pub struct DenomPoliciesResponse {
+    pub policies: Vec<(String, DenomPolicy)>,
+}
+
+/// Cumulative totals over all dispatches
+#[cw_serde]
+#[derive(Default)]
+pub struct RewardStats {
+    /// Rewards restaked, or streaming to the hub to be restaked
+    pub total_restaked: Uint128,
+    /// Fees taken from the rewards
+    pub total_fees: Uint128,
+    /// Fees paid to each recipient, including former recipients
+    pub fees_by_recipient: Vec<(Addr, Uint128)>,
+    /// Number of dispatches processed
+    pub dispatch_count: u64,
+}
+
+#[cw_serde]
+pub struct DispatchRecord {
+    pub id: u64,
+    /// Block height of the dispatch
+    pub height: u64,
+    /// Reward balance before the fee
+    pub gross: Uint128,
+    /// Fee taken from the rewards
+    pub fee: Uint128,
+    /// Rewards restaked, or added to the stream to the hub
+    pub net: Uint128,
+    /// Hub exchange rate once the rewards are restaked
+    pub exchange_rate: Decimal,
+}
+
+#[cw_serde]
+pub struct DispatchHistoryResponse {
+    pub history: Vec<DispatchRecord>,
+}
+
+/// Rewards held by the dispatcher and released to the hub linearly between `start_time`
+/// and `end_time`
+#[cw_serde]
+#[derive(Default)]
+pub struct RewardStream {
+    pub amount: Uint128,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+impl RewardStream {
+    /// Amount vested at `now`
+    pub fn vested(&self, now: u64) -> Uint128 {
+        if now >= self.end_time {
+            return self.amount;
+        }
+        self.amount.multiply_ratio(
+            now.saturating_sub(self.start_time),
+            self.end_time - self.start_time,
+        )
+    }
+}
+
+#[cw_serde]
+pub struct VestingRewardsResponse {
+    /// Held rewards that have not vested yet
+    pub vesting: Uint128,
+    /// Vested rewards sent to the hub with the next dispatch
+    pub releasable: Uint128,
+    /// Time at which all held rewards are vested
+    pub end_time: u64,
 }
@@ -0,0 +1,26 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Coin, Uint128};
+
+/// Interface the reward dispatcher expects from a DEX router contract
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Swap the attached funds into `ask_denom` and send the output back to the sender.
+    /// Fails if the output would be less than `min_output`.
+    Swap {
+        ask_denom: String,
+        min_output: Uint128,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the amount of `ask_denom` a swap of `offer` would currently yield
+    #[returns(SimulateSwapResponse)]
+    SimulateSwap { offer: Coin, ask_denom: String },
+}
+
+#[cw_serde]
+pub struct SimulateSwapResponse {
+    pub return_amount: Uint128,
+}
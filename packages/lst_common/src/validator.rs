@@ -1,8 +1,34 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Coin, Uint128};
+use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::{delegation::DelegationStrategyKind, SignedInt};
+
+/// Default minimum time in seconds between two rebalances (1 day)
+pub const DEFAULT_REBALANCE_COOLDOWN: u64 = 24 * 60 * 60;
+
+/// Default minimum time in seconds between two gauge tallies (7 days)
+pub const DEFAULT_GAUGE_PERIOD: u64 = 7 * 24 * 60 * 60;
+
+/// Default minimum time in seconds between two auto-curation runs (1 day)
+pub const DEFAULT_CURATION_PERIOD: u64 = 24 * 60 * 60;
+
+/// Validator weight given to the whole stake by a gauge tally
+pub const GAUGE_WEIGHT_PRECISION: u64 = 1_000_000;
+
+/// Maximum length of the reason recorded in the validator set change log
+pub const MAX_REASON_LENGTH: usize = 256;
+
+/// Maximum length of the details and contact of a validator application
+pub const MAX_APPLICATION_DETAILS_LENGTH: usize = 1024;
+
+/// Minimum time in seconds between two validator performance snapshots (1 day)
+pub const SNAPSHOT_INTERVAL: u64 = 24 * 60 * 60;
+
+/// Default time in seconds a soft-removed validator is drained by unstaking before its stake is redelegated (7 days)
+pub const DEFAULT_SOFT_REMOVAL_GRACE_PERIOD: u64 = 7 * 24 * 60 * 60;
+
 /// Instantiate the validator registry contract
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -18,12 +44,25 @@ pub enum ExecuteMsg {
     AddValidator {
         /// Address of the validator
         validator: Validator,
+        /// Reason recorded in the validator set change log
+        reason: Option<String>,
     },
 
     /// Remove validator from the registry
     RemoveValidator {
         /// Address of the valid
         address: String,
+        /// Reason recorded in the validator set change log
+        reason: Option<String>,
+    },
+
+    /// Stop delegating to a validator and drain it through unstaking first.
+    /// Its remaining stake is redelegated once the grace period is over
+    SoftRemoveValidator {
+        /// Address of the validator
+        address: String,
+        /// Reason recorded in the validator set change log
+        reason: Option<String>,
     },
 
     /// Admin can update the config using this method
@@ -32,22 +71,190 @@ pub enum ExecuteMsg {
         owner: Option<String>,
         /// Address of the hub contract
         hub_contract: Option<String>,
+        /// Minimum time in seconds between two rebalances
+        rebalance_cooldown: Option<u64>,
+        /// Strategy used to spread the stake across the validators
+        delegation_strategy: Option<DelegationStrategyKind>,
+        /// Time in seconds a soft-removed validator is drained before its stake is redelegated
+        soft_removal_grace_period: Option<u64>,
+        /// Maximum share of the total hub stake a single validator can hold
+        max_validator_share: Option<Decimal>,
+        /// Maximum commission of a validator applying to the registry
+        max_commission: Option<Decimal>,
+        /// Maximum number of validators a single stake is delegated to, zero means no limit
+        max_validators_per_delegation: Option<u32>,
+        /// Minimum amount delegated to a validator in a single stake
+        min_delegation_chunk: Option<Uint128>,
     },
     /// Process redelegations if validator is removed
-    ProcessRedelegations {},
+    ProcessRedelegations {
+        /// Maximum number of excluded validators to handle, the next call continues where this one stopped
+        limit: Option<u32>,
+    },
+    /// Redelegate from validators above their target delegation to validators below it
+    Rebalance {
+        /// Maximum number of redelegations to issue
+        max_moves: u32,
+    },
+    /// Record a performance snapshot of every validator in the registry, at most once per snapshot interval
+    RecordSnapshots {},
+    /// Admin can set the weight of a validator used by the weighted delegation strategy
+    UpdateValidatorWeight {
+        /// Address of the validator
+        address: String,
+        /// Relative weight of the validator
+        weight: u64,
+    },
+    /// Vote on how the stake is split across the validators, weighted by the LST balance of the voter.
+    /// Shares must not sum over one, an empty list withdraws the vote. When the voter limit is reached
+    /// a new voter replaces the voter with the smallest LST balance, if it holds more
+    Vote { votes: Vec<GaugeVote> },
+    /// Tally the gauge votes, at most once per gauge period and only with the weighted delegation strategy.
+    /// All voters are counted at the same height and the results become the validator weights
+    TallyVotes {},
+    /// Validator operator applies for its validator to be added to the registry.
+    /// The validator must be bonded, not jailed and within the commission limit of the registry
+    ApplyValidator {
+        /// Address of the validator
+        address: String,
+        /// Free-form details about the validator and its operator
+        details: String,
+        /// Contact of the operator
+        contact: Option<String>,
+    },
+    /// Admin approves a pending application and adds the validator to the registry
+    ApproveApplication {
+        /// Address of the validator
+        address: String,
+    },
+    /// Admin rejects a pending application
+    RejectApplication {
+        /// Address of the validator
+        address: String,
+        /// Reason of the rejection
+        reason: Option<String>,
+    },
+    /// Applicant withdraws its pending application
+    WithdrawApplication {
+        /// Address of the validator
+        address: String,
+    },
+    /// Admin can update the gauge voting parameters
+    UpdateGaugeConfig {
+        /// Minimum time in seconds between two tallies
+        period: Option<u64>,
+        /// Minimum share of the stake a validator gets from the gauge
+        min_weight: Option<Decimal>,
+        /// Maximum share of the stake a validator gets from the gauge
+        max_weight: Option<Decimal>,
+    },
+    /// Replace the validator set with the validators picked by auto-curation, at most once per curation period.
+    /// Removed validators are added to the exclude list, so their stake is redelegated by process redelegations
+    CurateValidators {},
+    /// Admin can update the auto-curation parameters
+    UpdateCurationConfig {
+        /// Whether the validator set is picked by auto-curation
+        enabled: Option<bool>,
+        /// Number of validators picked
+        set_size: Option<u32>,
+        /// Share of the top validators by voting power that are skipped
+        skip_top_share: Option<Decimal>,
+        /// Validators with a higher commission are not picked
+        max_commission: Option<Decimal>,
+        /// Minimum time in seconds between two curation runs
+        period: Option<u64>,
+    },
+    /// Called by the hub when it detects that a validator was slashed, the validator is soft-removed from the registry
+    ReportSlashing {
+        /// Address of the slashed validator
+        validator: String,
+        /// Delegation of the hub lost to the slashing
+        amount: Uint128,
+        /// Block height at which the slashing was detected
+        height: u64,
+    },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    /// Return the delegation done by the hub contract in the network
-    #[returns(Vec<ValidatorResponse>)]
+    /// Return the delegation done by the hub contract in the network and the active delegation strategy
+    #[returns(ValidatorsDelegationResponse)]
     ValidatorsDelegation {},
     /// Return the configuration parameters of the contract
     #[returns(Config)]
     Config {},
     #[returns(Vec<String>)]
     ExcludeList,
+    /// Return the soft-removed validators and the time they were removed at
+    #[returns(Vec<SoftRemovedValidator>)]
+    SoftRemovedList {},
+    /// Return the deviation of each validator's delegation from its target delegation
+    #[returns(Vec<DelegationDeviationResponse>)]
+    DelegationDeviation {},
+    /// Return the validators holding more than the maximum share of the total hub stake
+    #[returns(Vec<OverCapValidatorResponse>)]
+    OverCapValidators {},
+    /// Return the metadata and latest performance snapshot of the validators in batches
+    #[returns(Vec<ValidatorInfoResponse>)]
+    ValidatorInfo {
+        /// Address of the validator to start from
+        start_from: Option<String>,
+        /// No of data to return per request
+        limit: Option<u32>,
+    },
+    /// Return the performance snapshots of a validator in batches
+    #[returns(Vec<ValidatorSnapshot>)]
+    ValidatorHistory {
+        /// Address of the validator
+        address: String,
+        /// Snapshot time to start from
+        start_from: Option<u64>,
+        /// No of data to return per request
+        limit: Option<u32>,
+    },
+    /// Return the validator set change log in batches, optionally filtered by validator and time range
+    #[returns(Vec<ValidatorSetChange>)]
+    ValidatorSetChanges {
+        /// Only return the changes of this validator
+        validator: Option<String>,
+        /// Only return the changes made at or after this time
+        start_time: Option<u64>,
+        /// Only return the changes made at or before this time
+        end_time: Option<u64>,
+        /// Id of the change to start from
+        start_from: Option<u64>,
+        /// No of data to return per request
+        limit: Option<u32>,
+    },
+    /// Return the redelegations issued through the hub that have not matured yet
+    #[returns(Vec<PendingRedelegation>)]
+    PendingRedelegations {},
+    /// Return the pending validator applications in batches
+    #[returns(Vec<ValidatorApplication>)]
+    PendingApplications {
+        /// Address of the validator to start from
+        start_from: Option<String>,
+        /// No of data to return per request
+        limit: Option<u32>,
+    },
+    /// Return the gauge voting parameters
+    #[returns(GaugeConfig)]
+    GaugeConfig {},
+    /// Return the current gauge votes in batches
+    #[returns(Vec<GaugeVoterResponse>)]
+    GaugeVotes {
+        /// Address of the voter to start from
+        start_from: Option<String>,
+        /// No of data to return per request
+        limit: Option<u32>,
+    },
+    /// Return the results of the last gauge tally
+    #[returns(GaugeResultsResponse)]
+    GaugeResults {},
+    /// Return the auto-curation parameters
+    #[returns(CurationConfig)]
+    CurationConfig {},
 }
 
 #[cw_serde]
@@ -57,12 +264,262 @@ pub struct ValidatorResponse {
     pub total_delegated: Uint128,
     /// Address of the validator
     pub address: String,
+    /// Relative weight of the validator, used by the weighted delegation strategy
+    #[serde(default = "default_validator_weight")]
+    pub weight: u64,
+}
+
+/// Default weight of a validator
+pub fn default_validator_weight() -> u64 {
+    1
+}
+
+#[cw_serde]
+pub struct ValidatorsDelegationResponse {
+    /// Strategy used to spread the stake across the validators
+    pub strategy: DelegationStrategyKind,
+    /// Validators in the registry, sorted by ascending delegation
+    pub validators: Vec<ValidatorResponse>,
+    /// Soft-removed validators that still hold stake, to be undelegated from first
+    #[serde(default)]
+    pub draining: Vec<ValidatorResponse>,
+    /// Maximum share of the total hub stake a single validator can hold
+    #[serde(default = "default_max_validator_share")]
+    pub max_validator_share: Decimal,
+    /// Maximum number of validators a single stake is delegated to, zero means no limit
+    #[serde(default)]
+    pub max_validators_per_delegation: u32,
+    /// Minimum amount delegated to a validator in a single stake
+    #[serde(default)]
+    pub min_delegation_chunk: Uint128,
+}
+
+#[cw_serde]
+pub struct ValidatorMetadata {
+    /// Human-readable name of the validator
+    pub moniker: String,
+    /// Commission of the validator when it was added to the registry
+    pub commission_at_admission: Decimal,
+    /// Time at which the validator was added to the registry
+    pub admission_time: u64,
+    /// Address that added the validator to the registry
+    pub added_by: Addr,
+}
+
+#[cw_serde]
+pub struct ValidatorSnapshot {
+    /// Time at which the snapshot was taken
+    pub timestamp: u64,
+    /// Commission of the validator
+    pub commission: Decimal,
+    /// Whether the validator is jailed
+    pub jailed: bool,
+    /// Tokens bonded to the validator
+    pub voting_power: Uint128,
+}
+
+#[cw_serde]
+pub struct ValidatorInfoResponse {
+    /// Address of the validator
+    pub address: String,
+    /// Metadata recorded when the validator was added
+    pub metadata: ValidatorMetadata,
+    /// Most recent performance snapshot of the validator
+    pub latest_snapshot: Option<ValidatorSnapshot>,
+}
+
+#[cw_serde]
+pub struct ValidatorApplication {
+    /// Address of the validator
+    pub validator: String,
+    /// Account of the validator operator that applied
+    pub applicant: Addr,
+    /// Moniker of the validator on chain when it applied
+    pub moniker: String,
+    pub details: String,
+    pub contact: Option<String>,
+    /// Commission of the validator when it applied
+    pub commission: Decimal,
+    /// Time the application was submitted
+    pub submitted_at: u64,
+}
+
+#[cw_serde]
+pub struct GaugeVote {
+    /// Address of the validator
+    pub validator: String,
+    /// Share of the voting power given to the validator
+    pub share: Decimal,
+}
+
+#[cw_serde]
+pub struct GaugeConfig {
+    /// Minimum time in seconds between two tallies
+    pub period: u64,
+    /// Minimum share of the stake a validator gets from the gauge
+    pub min_weight: Decimal,
+    /// Maximum share of the stake a validator gets from the gauge
+    pub max_weight: Decimal,
+}
+
+impl Default for GaugeConfig {
+    fn default() -> Self {
+        Self {
+            period: DEFAULT_GAUGE_PERIOD,
+            min_weight: Decimal::zero(),
+            max_weight: Decimal::one(),
+        }
+    }
+}
+
+#[cw_serde]
+pub struct CurationConfig {
+    /// Whether the validator set is picked by auto-curation
+    pub enabled: bool,
+    /// Number of validators picked
+    pub set_size: u32,
+    /// Share of the top validators by voting power that are skipped
+    pub skip_top_share: Decimal,
+    /// Validators with a higher commission are not picked
+    pub max_commission: Decimal,
+    /// Minimum time in seconds between two curation runs
+    pub period: u64,
+}
+
+impl Default for CurationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            set_size: 10,
+            skip_top_share: Decimal::zero(),
+            max_commission: Decimal::percent(10),
+            period: DEFAULT_CURATION_PERIOD,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct GaugeVoterResponse {
+    pub voter: Addr,
+    pub votes: Vec<GaugeVote>,
+}
+
+#[cw_serde]
+pub struct GaugeResult {
+    /// Address of the validator
+    pub validator: String,
+    /// LST balance voted for the validator
+    pub power: Uint128,
+    /// Share of the stake given to the validator after applying the floor and ceiling
+    pub weight: Decimal,
+}
+
+#[cw_serde]
+pub struct GaugeResultsResponse {
+    /// Time of the last completed tally
+    pub tallied_at: u64,
+    pub results: Vec<GaugeResult>,
+}
+
+#[cw_serde]
+pub enum ValidatorSetAction {
+    /// Validator added to the registry
+    Add,
+    /// Validator removed from the registry, its stake is redelegated
+    Remove,
+    /// Validator soft-removed from the registry, its stake is drained by unstaking first
+    SoftRemove,
+}
+
+#[cw_serde]
+pub struct ValidatorSetChange {
+    /// Sequential id of the change
+    pub id: u64,
+    pub action: ValidatorSetAction,
+    /// Address of the validator
+    pub validator: String,
+    /// Address that made the change
+    pub actor: Addr,
+    /// Block height of the change
+    pub height: u64,
+    /// Block time of the change
+    pub timestamp: u64,
+    pub reason: Option<String>,
+}
+
+#[cw_serde]
+pub struct SoftRemovedValidator {
+    /// Address of the validator
+    pub address: String,
+    /// Time at which the validator was soft-removed
+    pub removal_time: u64,
+}
+
+#[cw_serde]
+pub struct OverCapValidatorResponse {
+    /// Address of the validator
+    pub address: String,
+    /// Total delegated amount for the validator
+    pub total_delegated: Uint128,
+    /// Share of the total hub stake held by the validator
+    pub share: Decimal,
+    /// Amount delegated above the cap
+    pub excess: Uint128,
+}
+
+#[cw_serde]
+pub struct DelegationDeviationResponse {
+    /// Address of the validator
+    pub address: String,
+    /// Total delegated amount for the validator
+    pub total_delegated: Uint128,
+    /// Delegation the validator would have if the stake was perfectly balanced
+    pub target_delegation: Uint128,
+    /// Difference between the total delegated and the target delegation
+    pub deviation: SignedInt,
 }
 
 #[cw_serde]
 pub struct Config {
     pub owner: Addr,
     pub hub_contract: Addr,
+    /// Minimum time in seconds between two rebalances
+    #[serde(default = "default_rebalance_cooldown")]
+    pub rebalance_cooldown: u64,
+    /// Strategy used to spread the stake across the validators
+    #[serde(default)]
+    pub delegation_strategy: DelegationStrategyKind,
+    /// Time in seconds a soft-removed validator is drained before its stake is redelegated
+    #[serde(default = "default_soft_removal_grace_period")]
+    pub soft_removal_grace_period: u64,
+    /// Maximum share of the total hub stake a single validator can hold, one means no cap
+    #[serde(default = "default_max_validator_share")]
+    pub max_validator_share: Decimal,
+    /// Maximum commission of a validator applying to the registry
+    #[serde(default = "default_max_commission")]
+    pub max_commission: Decimal,
+    /// Maximum number of validators a single stake is delegated to, zero means no limit
+    #[serde(default)]
+    pub max_validators_per_delegation: u32,
+    /// Minimum amount delegated to a validator in a single stake
+    #[serde(default)]
+    pub min_delegation_chunk: Uint128,
+}
+
+fn default_rebalance_cooldown() -> u64 {
+    DEFAULT_REBALANCE_COOLDOWN
+}
+
+fn default_soft_removal_grace_period() -> u64 {
+    DEFAULT_SOFT_REMOVAL_GRACE_PERIOD
+}
+
+pub fn default_max_validator_share() -> Decimal {
+    Decimal::one()
+}
+
+pub fn default_max_commission() -> Decimal {
+    Decimal::one()
 }
 
 #[cw_serde]
@@ -72,7 +529,10 @@ pub struct Validator {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PendingRedelegation {
+    /// Address of the validator the stake is redelegated from
     pub src_validator: String,
+    /// Destination validators and the amount redelegated to each of them
     pub redelegations: Vec<(String, Coin)>,
+    /// Time at which the redelegation completes. Until then the destination validators cannot redelegate onward
     pub timestamp: u64,
 }
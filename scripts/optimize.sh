@@ -9,7 +9,7 @@ RUSTC_VERS="1.85.0"
 
 MAX_WASM_SIZE=800 # 800 KB
 
-PROJECTS=("lst_token" "lst_reward_dispatcher" "lst_validators_registry" "lst_staking_hub")
+PROJECTS=("lst_token" "lst_reward_dispatcher" "lst_validators_registry" "lst_staking_hub" "lst_insurance_fund")
 
 if ! which wasm-opt; then
   curl -OL $BINARYEN_DWN